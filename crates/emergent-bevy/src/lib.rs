@@ -0,0 +1,371 @@
+//! __Bevy integration for emergent AI toolset.__
+//!
+//! Main concepts
+//! ---
+//! - __Brain__ - Component that holds decision maker together with its memory ([`Brain`])
+//! - __Brain memory__ - Memory refreshed from components of agent entity before every tick
+//!   ([`BrainMemory`])
+//! - __Plugin__ - Registers systems ticking brains of given memory type ([`EmergentPlugin`])
+//! - __Debug messages__ - Sent whenever brain changes its decision ([`BrainDecisionChanged`])
+//!
+//! Brains are ticked in [`Update`] schedule, in [`EmergentSystems`] set, so systems that apply
+//! decisions to the world can be ordered after it.
+//!
+//! # Example
+//! ```
+//! use bevy::prelude::*;
+//! use emergent::prelude::*;
+//! use emergent_bevy::prelude::*;
+//!
+//! #[derive(Component)]
+//! struct Health(Scalar);
+//!
+//! #[derive(Default)]
+//! struct Memory {
+//!     health: Scalar,
+//! }
+//!
+//! impl BrainMemory for Memory {
+//!     type Query = Ref<'static, Health>;
+//!
+//!     fn refresh(&mut self, health: Ref<Health>) -> bool {
+//!         self.health = health.0;
+//!         health.is_changed()
+//!     }
+//! }
+//!
+//! let reasoner = ReasonerBuilder::default()
+//!     .state("fight".to_owned(), ReasonerState::new(0.5, NoTask))
+//!     .state("flee".to_owned(), ReasonerState::new(
+//!         ClosureConsideration::new(|m: &Memory| 1.0 - m.health),
+//!         NoTask,
+//!     ))
+//!     .build();
+//!
+//! let mut app = App::new();
+//! app.add_plugins((MinimalPlugins, EmergentPlugin::<Memory>::default()));
+//! let agent = app
+//!     .world_mut()
+//!     .spawn((Health(1.0), Brain::new(reasoner, Memory::default())))
+//!     .id();
+//!
+//! app.update();
+//! let brain = app.world().get::<Brain<Memory>>(agent).unwrap();
+//! assert_eq!(brain.decision().map(|id| id.as_str()), Some("fight"));
+//!
+//! app.world_mut().get_mut::<Health>(agent).unwrap().0 = 0.2;
+//! app.update();
+//! let brain = app.world().get::<Brain<Memory>>(agent).unwrap();
+//! assert_eq!(brain.decision().map(|id| id.as_str()), Some("flee"));
+//! ```
+
+#[cfg(test)]
+mod tests;
+
+use bevy_app::{App, Plugin, Update};
+use bevy_ecs::{
+    prelude::*,
+    query::{QueryItem, ReadOnlyQueryData},
+};
+use emergent::{DecisionMakingTask, DefaultKey, task::TaskStopReason};
+use std::marker::PhantomData;
+
+/// Memory of agent that gets refreshed from components of agent entity before every tick.
+///
+/// Memory is owned by [`Brain`] component, so tasks can freely write to it - use your own systems
+/// to apply what tasks wrote in memory back to the world.
+///
+/// # Example
+/// ```
+/// use bevy::prelude::*;
+/// use emergent_bevy::prelude::*;
+///
+/// #[derive(Component)]
+/// struct Position(Vec2);
+///
+/// struct Memory {
+///     position: Vec2,
+/// }
+///
+/// impl BrainMemory for Memory {
+///     type Query = Ref<'static, Position>;
+///
+///     fn refresh(&mut self, position: Ref<Position>) -> bool {
+///         self.position = position.0;
+///         position.is_changed()
+///     }
+/// }
+/// ```
+pub trait BrainMemory: Send + Sync + 'static {
+    /// Read-only query data of agent entity that memory reads from.
+    type Query: ReadOnlyQueryData;
+
+    /// Refreshes memory from queried components of agent entity.
+    ///
+    /// Returns `true` if memory has changed and brain should be re-evaluated (use `Ref` in query
+    /// and its change detection to tell that).
+    fn refresh(&mut self, item: QueryItem<'_, '_, Self::Query>) -> bool;
+}
+
+impl BrainMemory for () {
+    type Query = ();
+
+    fn refresh(&mut self, _: ()) -> bool {
+        false
+    }
+}
+
+/// Tells when brain should re-evaluate its decision.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum BrainReevaluation {
+    /// Decision is made on every tick.
+    #[default]
+    EveryTick,
+    /// Decision is made only when memory or brain has changed since last tick.
+    ///
+    /// Decision maker is still processed (`Task::on_process`) on every other tick, so its running
+    /// tasks keep progressing, but its decision is read (and reported) only on re-evaluation.
+    /// Note that decision makers like [`emergent::decision_makers::reasoner::Reasoner`] process
+    /// their states on `Task::on_process` too, so [`Brain::decision`] might not reflect decision
+    /// maker active state until next re-evaluation.
+    OnChange,
+}
+
+/// Tells why brain has re-evaluated its decision.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum BrainReevaluationReason {
+    /// Brain has just started.
+    Started,
+    /// Brain component was mutated by other system.
+    BrainChanged,
+    /// [`BrainMemory::refresh`] reported change.
+    MemoryChanged,
+    /// Brain re-evaluates on every tick.
+    Scheduled,
+}
+
+/// Component that holds decision maker together with its memory.
+///
+/// Decision maker is started (`Task::on_enter`) on its first tick, then every tick it makes
+/// decision (`DecisionMaker::decide`) when [`BrainReevaluation`] allows it, otherwise it gets
+/// processed (`Task::on_process`), and then it gets updated (`Task::on_update`). Mutating brain component from other systems (for example writing into its
+/// memory) is detected and triggers re-evaluation.
+///
+/// Note that decision maker is not stopped when component gets removed or entity gets despawned -
+/// use [`Brain::stop`] before that if decision maker needs to clean up.
+#[derive(Component)]
+pub struct Brain<M, K = DefaultKey>
+where
+    M: BrainMemory,
+    K: Send + Sync + 'static,
+{
+    /// Memory of decision maker, refreshed with [`BrainMemory::refresh`] before every tick.
+    pub memory: M,
+    decision_maker: Box<dyn DecisionMakingTask<M, K>>,
+    reevaluation: BrainReevaluation,
+    decision: Option<K>,
+    started: bool,
+}
+
+impl<M, K> Brain<M, K>
+where
+    M: BrainMemory,
+    K: Send + Sync + 'static,
+{
+    /// Constructs new brain with decision maker and its memory.
+    pub fn new<D>(decision_maker: D, memory: M) -> Self
+    where
+        D: DecisionMakingTask<M, K> + 'static,
+    {
+        Self::new_raw(Box::new(decision_maker), memory)
+    }
+
+    /// Constructs new brain with raw decision maker and its memory.
+    pub fn new_raw(decision_maker: Box<dyn DecisionMakingTask<M, K>>, memory: M) -> Self {
+        Self {
+            memory,
+            decision_maker,
+            reevaluation: BrainReevaluation::default(),
+            decision: None,
+            started: false,
+        }
+    }
+
+    /// Sets when brain should re-evaluate its decision.
+    pub fn reevaluation(mut self, value: BrainReevaluation) -> Self {
+        self.reevaluation = value;
+        self
+    }
+
+    /// Returns when brain re-evaluates its decision.
+    pub fn get_reevaluation(&self) -> BrainReevaluation {
+        self.reevaluation
+    }
+
+    /// Sets when brain should re-evaluate its decision.
+    pub fn set_reevaluation(&mut self, value: BrainReevaluation) {
+        self.reevaluation = value;
+    }
+
+    /// Returns last decision made.
+    pub fn decision(&self) -> Option<&K> {
+        self.decision.as_ref()
+    }
+
+    /// Tells if decision maker has started.
+    pub fn is_started(&self) -> bool {
+        self.started
+    }
+
+    /// Returns immutable access to decision maker.
+    pub fn decision_maker(&self) -> &dyn DecisionMakingTask<M, K> {
+        self.decision_maker.as_ref()
+    }
+
+    /// Returns mutable access to decision maker.
+    pub fn decision_maker_mut(&mut self) -> &mut dyn DecisionMakingTask<M, K> {
+        self.decision_maker.as_mut()
+    }
+
+    /// Stops decision maker with given reason. It will start again on next tick.
+    pub fn stop(&mut self, reason: TaskStopReason) {
+        if self.started {
+            self.decision_maker.on_stop(&mut self.memory, reason);
+            self.started = false;
+            self.decision = None;
+        }
+    }
+
+    fn tick(&mut self, reason: Option<BrainReevaluationReason>) -> Option<BrainReevaluationReason>
+    where
+        K: PartialEq,
+    {
+        let reason = if !self.started {
+            self.decision_maker.on_enter(&mut self.memory);
+            self.started = true;
+            Some(BrainReevaluationReason::Started)
+        } else if reason.is_some() {
+            reason
+        } else if self.reevaluation == BrainReevaluation::EveryTick {
+            Some(BrainReevaluationReason::Scheduled)
+        } else {
+            None
+        };
+        let mut result = None;
+        if reason.is_some() {
+            let decision = self.decision_maker.decide(&mut self.memory);
+            if decision != self.decision {
+                self.decision = decision;
+                result = reason;
+            }
+        } else {
+            self.decision_maker.on_process(&mut self.memory);
+        }
+        self.decision_maker.on_update(&mut self.memory);
+        result
+    }
+}
+
+impl<M, K> std::fmt::Debug for Brain<M, K>
+where
+    M: BrainMemory + std::fmt::Debug,
+    K: Send + Sync + 'static + std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Brain")
+            .field("memory", &self.memory)
+            .field("reevaluation", &self.reevaluation)
+            .field("decision", &self.decision)
+            .field("started", &self.started)
+            .finish()
+    }
+}
+
+/// Message sent when brain changes its decision.
+#[derive(Message, Debug, Clone, PartialEq)]
+pub struct BrainDecisionChanged<K = DefaultKey>
+where
+    K: Send + Sync + 'static,
+{
+    /// Agent entity.
+    pub entity: Entity,
+    /// Decision made before.
+    pub previous: Option<K>,
+    /// Decision made now.
+    pub current: Option<K>,
+    /// Why brain has re-evaluated its decision.
+    pub reason: BrainReevaluationReason,
+}
+
+/// System set of systems ticking brains.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct EmergentSystems;
+
+/// Plugin that ticks brains with memory of given type.
+///
+/// Add one plugin per brain memory (and decision key) type used in the app.
+pub struct EmergentPlugin<M, K = DefaultKey> {
+    _phantom: PhantomData<fn() -> (M, K)>,
+}
+
+impl<M, K> Default for EmergentPlugin<M, K> {
+    fn default() -> Self {
+        Self {
+            _phantom: Default::default(),
+        }
+    }
+}
+
+impl<M, K> Plugin for EmergentPlugin<M, K>
+where
+    M: BrainMemory,
+    K: Clone + PartialEq + Send + Sync + 'static,
+{
+    fn build(&self, app: &mut App) {
+        app.add_message::<BrainDecisionChanged<K>>()
+            .add_systems(Update, tick_brains::<M, K>.in_set(EmergentSystems));
+    }
+}
+
+/// System that refreshes memories and ticks brains with memory of given type.
+///
+/// Registered by [`EmergentPlugin`], use it directly only if you need to tick brains in another
+/// schedule.
+pub fn tick_brains<M, K>(
+    mut brains: Query<(Entity, &mut Brain<M, K>, M::Query)>,
+    mut messages: MessageWriter<BrainDecisionChanged<K>>,
+) where
+    M: BrainMemory,
+    K: Clone + PartialEq + Send + Sync + 'static,
+{
+    for (entity, mut brain, item) in &mut brains {
+        // brain ticks itself without triggering change detection, so only changes made by other
+        // systems are detected.
+        let changed = brain.is_changed();
+        let brain = brain.bypass_change_detection();
+        let reason = if brain.memory.refresh(item) {
+            Some(BrainReevaluationReason::MemoryChanged)
+        } else if changed {
+            Some(BrainReevaluationReason::BrainChanged)
+        } else {
+            None
+        };
+        let previous = brain.decision.clone();
+        if let Some(reason) = brain.tick(reason) {
+            messages.write(BrainDecisionChanged {
+                entity,
+                previous,
+                current: brain.decision.clone(),
+                reason,
+            });
+        }
+    }
+}
+
+#[doc(hidden)]
+pub mod prelude {
+    pub use crate::{
+        Brain, BrainDecisionChanged, BrainMemory, BrainReevaluation, BrainReevaluationReason,
+        EmergentPlugin, EmergentSystems, tick_brains,
+    };
+}
@@ -0,0 +1,203 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+use emergent::prelude::*;
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+#[derive(Component)]
+struct Hunger(Scalar);
+
+#[derive(Default)]
+struct Memory {
+    hunger: Scalar,
+    ate: usize,
+}
+
+impl BrainMemory for Memory {
+    type Query = Ref<'static, Hunger>;
+
+    fn refresh(&mut self, hunger: Ref<Hunger>) -> bool {
+        self.hunger = hunger.0;
+        hunger.is_changed()
+    }
+}
+
+fn brain(decisions: Arc<AtomicUsize>) -> Brain<Memory> {
+    let reasoner = ReasonerBuilder::default()
+        .state(
+            "idle".to_owned(),
+            ReasonerState::new(
+                ClosureConsideration::new(move |_: &Memory| {
+                    decisions.fetch_add(1, Ordering::SeqCst);
+                    0.5
+                }),
+                NoTask,
+            ),
+        )
+        .state(
+            "eat".to_owned(),
+            ReasonerState::new(
+                ClosureConsideration::new(|m: &Memory| m.hunger),
+                ClosureTask::default().update(|m: &mut Memory| m.ate += 1),
+            ),
+        )
+        .build();
+    Brain::new(reasoner, Memory::default())
+}
+
+fn decision(app: &App, entity: Entity) -> Option<&str> {
+    app.world()
+        .get::<Brain<Memory>>(entity)
+        .unwrap()
+        .decision()
+        .map(|id| id.as_str())
+}
+
+fn messages(app: &mut App) -> Vec<BrainDecisionChanged> {
+    app.world_mut()
+        .resource_mut::<Messages<BrainDecisionChanged>>()
+        .drain()
+        .collect()
+}
+
+#[test]
+fn test_brain() {
+    let decisions = Arc::new(AtomicUsize::new(0));
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, EmergentPlugin::<Memory>::default()));
+    let agent = app
+        .world_mut()
+        .spawn((Hunger(0.0), brain(decisions.clone())))
+        .id();
+
+    app.update();
+    assert_eq!(decision(&app, agent), Some("idle"));
+    assert_eq!(
+        messages(&mut app),
+        vec![BrainDecisionChanged {
+            entity: agent,
+            previous: None,
+            current: Some("idle".to_owned()),
+            reason: BrainReevaluationReason::Started,
+        }]
+    );
+    let count = decisions.load(Ordering::SeqCst);
+    app.update();
+    assert!(decisions.load(Ordering::SeqCst) > count);
+    assert!(messages(&mut app).is_empty());
+
+    app.world_mut().get_mut::<Hunger>(agent).unwrap().0 = 1.0;
+    app.update();
+    assert_eq!(decision(&app, agent), Some("eat"));
+    assert_eq!(
+        messages(&mut app),
+        vec![BrainDecisionChanged {
+            entity: agent,
+            previous: Some("idle".to_owned()),
+            current: Some("eat".to_owned()),
+            reason: BrainReevaluationReason::MemoryChanged,
+        }]
+    );
+    app.update();
+    assert_eq!(
+        app.world().get::<Brain<Memory>>(agent).unwrap().memory.ate,
+        2
+    );
+
+    let mut brain = app.world_mut().get_mut::<Brain<Memory>>(agent).unwrap();
+    brain.stop(TaskStopReason::Cancelled);
+    assert!(!brain.is_started());
+    assert_eq!(brain.decision(), None);
+    app.update();
+    assert!(
+        app.world()
+            .get::<Brain<Memory>>(agent)
+            .unwrap()
+            .is_started()
+    );
+    assert_eq!(decision(&app, agent), Some("eat"));
+}
+
+#[test]
+fn test_brain_reevaluation_on_change() {
+    let decisions = Arc::new(AtomicUsize::new(0));
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, EmergentPlugin::<Memory>::default()));
+    let agent = app
+        .world_mut()
+        .spawn((
+            Hunger(0.0),
+            brain(decisions.clone()).reevaluation(BrainReevaluation::OnChange),
+        ))
+        .id();
+
+    app.update();
+    assert_eq!(messages(&mut app).len(), 1);
+    for _ in 0..3 {
+        app.update();
+    }
+    assert!(messages(&mut app).is_empty());
+    assert_eq!(decision(&app, agent), Some("idle"));
+    let count = decisions.load(Ordering::SeqCst);
+
+    app.world_mut().get_mut::<Hunger>(agent).unwrap().0 = 1.0;
+    app.update();
+    assert!(decisions.load(Ordering::SeqCst) > count);
+    assert_eq!(decision(&app, agent), Some("eat"));
+    assert_eq!(
+        messages(&mut app)
+            .into_iter()
+            .map(|message| message.reason)
+            .collect::<Vec<_>>(),
+        vec![BrainReevaluationReason::MemoryChanged]
+    );
+    app.update();
+    assert!(messages(&mut app).is_empty());
+
+    // writing into memory from other system marks brain as changed.
+    app.world_mut()
+        .get_mut::<Brain<Memory>>(agent)
+        .unwrap()
+        .memory
+        .ate = 0;
+    app.update();
+    assert!(messages(&mut app).is_empty());
+}
+
+#[test]
+fn test_brain_reevaluation_on_change_progresses_tasks() {
+    let sequencer = Sequencer::new(
+        (0..5)
+            .map(|_| {
+                SequencerState::new(
+                    true,
+                    ClosureTask::default().enter(|m: &mut Memory| m.ate += 1),
+                )
+            })
+            .collect(),
+        false,
+        false,
+    );
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, EmergentPlugin::<Memory>::default()));
+    let agent = app
+        .world_mut()
+        .spawn((
+            Hunger(0.0),
+            Brain::<_, DefaultKey>::new(sequencer, Memory::default())
+                .reevaluation(BrainReevaluation::OnChange),
+        ))
+        .id();
+
+    // running sequence advances on ticks without any change.
+    let ate = |app: &App| app.world().get::<Brain<Memory>>(agent).unwrap().memory.ate;
+    app.update();
+    let started = ate(&app);
+    app.update();
+    assert_eq!(ate(&app), started + 1);
+    app.update();
+    assert_eq!(ate(&app), started + 2);
+    assert_eq!(messages(&mut app).len(), 1);
+}
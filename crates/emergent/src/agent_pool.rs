@@ -0,0 +1,328 @@
+//! Pool of agents ticked together.
+
+use crate::task::Task;
+use std::collections::BTreeMap;
+
+/// Identifier of agent stored in [`AgentPool`].
+///
+/// Identifiers are never reused within single pool and grow with each added agent, so ordering
+/// by them gives insertion order.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AgentId(usize);
+
+impl AgentId {
+    /// Returns raw index of this identifier.
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+impl std::fmt::Display for AgentId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// Single agent owned by [`AgentPool`].
+pub struct Agent<M, D> {
+    /// Decision maker ticked with agent memory.
+    pub decision_maker: D,
+    /// Memory owned by agent.
+    pub memory: M,
+    tick_rate: usize,
+}
+
+impl<M, D> Agent<M, D> {
+    /// Returns number of pool ticks between consecutive ticks of this agent.
+    pub fn get_tick_rate(&self) -> usize {
+        self.tick_rate
+    }
+
+    fn is_due(&self, id: AgentId, tick: usize) -> bool {
+        // agents sharing the same tick rate are spread across ticks by their identifier.
+        tick.wrapping_add(id.0).is_multiple_of(self.tick_rate)
+    }
+
+    fn tick(&mut self) -> bool
+    where
+        D: Task<M>,
+    {
+        let result = self.decision_maker.on_process(&mut self.memory);
+        self.decision_maker.on_update(&mut self.memory);
+        result
+    }
+}
+
+impl<M, D> std::fmt::Debug for Agent<M, D>
+where
+    M: std::fmt::Debug,
+    D: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Agent")
+            .field("decision_maker", &self.decision_maker)
+            .field("memory", &self.memory)
+            .field("tick_rate", &self.tick_rate)
+            .finish()
+    }
+}
+
+/// Owns pairs of decision maker and its memory and ticks them together.
+///
+/// Ticking an agent means processing its decision maker (`Task::on_process`) and then updating it
+/// (`Task::on_update`), both with agent's own memory. Each agent has its own tick rate telling
+/// every how many pool ticks it gets ticked, which allows to run background agents less often.
+/// Agents with the same tick rate are spread across pool ticks by their identifiers, so the work
+/// is evenly distributed between frames.
+///
+/// [`AgentPool::tick`] always ticks agents one by one in ascending order of agent identifiers.
+/// With `parallel` feature enabled there is also `AgentPool::par_tick` that ticks agents on rayon
+/// thread pool instead, unless pool is marked as deterministic with [`AgentPool::deterministic`] -
+/// then it still ticks agents one by one in identifiers order, which is useful when agent memories
+/// share state (for example through `Arc`) and replays must give the same results.
+///
+/// Note that pool does not call `Task::on_enter` nor `Task::on_exit` of decision makers - agents
+/// are expected to be ready to tick when added and are just given back when removed.
+///
+/// # Example
+/// ```
+/// use emergent::prelude::*;
+///
+/// struct Counter(usize);
+///
+/// fn counter() -> ClosureTask<Counter> {
+///     ClosureTask::default().update(|m: &mut Counter| m.0 += 1)
+/// }
+///
+/// let mut pool = AgentPool::default();
+/// let a = pool.add(counter(), Counter(0));
+/// let b = pool.add_with_tick_rate(counter(), Counter(0), 2);
+///
+/// assert_eq!(pool.tick(), 1);
+/// assert_eq!(pool.tick(), 2);
+/// assert_eq!(pool.tick(), 1);
+/// assert_eq!(pool.memory(a).unwrap().0, 3);
+/// assert_eq!(pool.memory(b).unwrap().0, 1);
+/// ```
+pub struct AgentPool<M, D> {
+    agents: BTreeMap<AgentId, Agent<M, D>>,
+    next_id: usize,
+    tick: usize,
+    deterministic: bool,
+}
+
+impl<M, D> Default for AgentPool<M, D> {
+    fn default() -> Self {
+        Self {
+            agents: Default::default(),
+            next_id: 0,
+            tick: 0,
+            deterministic: false,
+        }
+    }
+}
+
+impl<M, D> AgentPool<M, D> {
+    /// Marks if agents should always be ticked sequentially in identifiers order, even by
+    /// `AgentPool::par_tick`.
+    ///
+    /// [`AgentPool::tick`] always ticks agents that way.
+    pub fn deterministic(mut self, value: bool) -> Self {
+        self.deterministic = value;
+        self
+    }
+
+    /// Tells if agents are always ticked sequentially in identifiers order.
+    pub fn get_deterministic(&self) -> bool {
+        self.deterministic
+    }
+
+    /// Sets if agents should always be ticked sequentially in identifiers order.
+    pub fn set_deterministic(&mut self, value: bool) {
+        self.deterministic = value;
+    }
+
+    /// Returns number of pool ticks performed so far.
+    pub fn ticks(&self) -> usize {
+        self.tick
+    }
+
+    /// Returns number of agents in pool.
+    pub fn len(&self) -> usize {
+        self.agents.len()
+    }
+
+    /// Tells if pool has no agents.
+    pub fn is_empty(&self) -> bool {
+        self.agents.is_empty()
+    }
+
+    /// Adds agent ticked on every pool tick and returns its identifier.
+    pub fn add(&mut self, decision_maker: D, memory: M) -> AgentId {
+        self.add_with_tick_rate(decision_maker, memory, 1)
+    }
+
+    /// Adds agent ticked every `tick_rate` pool ticks and returns its identifier.
+    ///
+    /// Tick rate of 0 is treated as 1.
+    pub fn add_with_tick_rate(
+        &mut self,
+        decision_maker: D,
+        memory: M,
+        tick_rate: usize,
+    ) -> AgentId {
+        let id = AgentId(self.next_id);
+        self.next_id += 1;
+        self.agents.insert(
+            id,
+            Agent {
+                decision_maker,
+                memory,
+                tick_rate: tick_rate.max(1),
+            },
+        );
+        id
+    }
+
+    /// Removes agent and gives back its decision maker and memory.
+    pub fn remove(&mut self, id: AgentId) -> Option<(D, M)> {
+        self.agents
+            .remove(&id)
+            .map(|agent| (agent.decision_maker, agent.memory))
+    }
+
+    /// Removes all agents.
+    pub fn clear(&mut self) {
+        self.agents.clear();
+    }
+
+    /// Tells if pool contains agent with given identifier.
+    pub fn contains(&self, id: AgentId) -> bool {
+        self.agents.contains_key(&id)
+    }
+
+    /// Returns agent with given identifier.
+    pub fn get(&self, id: AgentId) -> Option<&Agent<M, D>> {
+        self.agents.get(&id)
+    }
+
+    /// Returns mutable agent with given identifier.
+    pub fn get_mut(&mut self, id: AgentId) -> Option<&mut Agent<M, D>> {
+        self.agents.get_mut(&id)
+    }
+
+    /// Returns decision maker of agent with given identifier.
+    pub fn decision_maker(&self, id: AgentId) -> Option<&D> {
+        self.get(id).map(|agent| &agent.decision_maker)
+    }
+
+    /// Returns mutable decision maker of agent with given identifier.
+    pub fn decision_maker_mut(&mut self, id: AgentId) -> Option<&mut D> {
+        self.get_mut(id).map(|agent| &mut agent.decision_maker)
+    }
+
+    /// Returns memory of agent with given identifier.
+    pub fn memory(&self, id: AgentId) -> Option<&M> {
+        self.get(id).map(|agent| &agent.memory)
+    }
+
+    /// Returns mutable memory of agent with given identifier.
+    pub fn memory_mut(&mut self, id: AgentId) -> Option<&mut M> {
+        self.get_mut(id).map(|agent| &mut agent.memory)
+    }
+
+    /// Returns tick rate of agent with given identifier.
+    pub fn tick_rate(&self, id: AgentId) -> Option<usize> {
+        self.get(id).map(|agent| agent.tick_rate)
+    }
+
+    /// Sets tick rate of agent with given identifier. Tick rate of 0 is treated as 1.
+    ///
+    /// Returns `false` if agent does not exist.
+    pub fn set_tick_rate(&mut self, id: AgentId, tick_rate: usize) -> bool {
+        if let Some(agent) = self.get_mut(id) {
+            agent.tick_rate = tick_rate.max(1);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Tells if agent with given identifier will be ticked on next pool tick.
+    pub fn is_due(&self, id: AgentId) -> bool {
+        self.get(id)
+            .map(|agent| agent.is_due(id, self.tick))
+            .unwrap_or_default()
+    }
+
+    /// Iterates over agents in identifiers order.
+    pub fn iter(&self) -> impl Iterator<Item = (AgentId, &Agent<M, D>)> {
+        self.agents.iter().map(|(id, agent)| (*id, agent))
+    }
+
+    /// Iterates over mutable agents in identifiers order.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (AgentId, &mut Agent<M, D>)> {
+        self.agents.iter_mut().map(|(id, agent)| (*id, agent))
+    }
+
+    /// Ticks all agents due in this pool tick, in identifiers order, and returns how many of them
+    /// were ticked.
+    pub fn tick(&mut self) -> usize
+    where
+        D: Task<M>,
+    {
+        let tick = self.tick;
+        self.tick = self.tick.wrapping_add(1);
+        let mut result = 0;
+        for (id, agent) in &mut self.agents {
+            if agent.is_due(*id, tick) {
+                agent.tick();
+                result += 1;
+            }
+        }
+        result
+    }
+}
+
+#[cfg(feature = "parallel")]
+impl<M, D> AgentPool<M, D>
+where
+    M: Send,
+    D: Task<M>,
+{
+    /// Ticks all agents due in this pool tick in parallel and returns how many of them were
+    /// ticked.
+    ///
+    /// Deterministic pool ticks agents sequentially, just like [`Self::tick`].
+    pub fn par_tick(&mut self) -> usize {
+        use rayon::prelude::*;
+
+        if self.deterministic {
+            return self.tick();
+        }
+        let tick = self.tick;
+        self.tick = self.tick.wrapping_add(1);
+        self.agents
+            .par_iter_mut()
+            .filter(|(id, agent)| agent.is_due(**id, tick))
+            .map(|(_, agent)| {
+                agent.tick();
+            })
+            .count()
+    }
+}
+
+impl<M, D> std::fmt::Debug for AgentPool<M, D>
+where
+    M: std::fmt::Debug,
+    D: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AgentPool")
+            .field("agents", &self.agents)
+            .field("next_id", &self.next_id)
+            .field("tick", &self.tick)
+            .field("deterministic", &self.deterministic)
+            .finish()
+    }
+}
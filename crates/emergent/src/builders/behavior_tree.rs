@@ -1,10 +1,13 @@
 //! Behavior tree builder.
 
 use crate::{
+    Scalar,
+    combinators::all::*,
     condition::*,
     decision_makers::{parallelizer::*, selector::*, sequencer::*},
     task::*,
 };
+use std::{collections::HashMap, sync::Arc};
 
 /// Wrapper around task produced by [`BehaviorTree`] builder.
 pub struct BehaviorTreeTask<M = ()>(pub Box<dyn Task<M>>);
@@ -56,6 +59,13 @@ impl<M> std::fmt::Debug for BehaviorTreeTask<M> {
 /// running node reports [`Task::is_locked`] true (this means node is running and nothing should
 /// interrupt its work).
 ///
+/// Nodes can be wrapped with decorators that change their conditions and/or tasks:
+/// __Inverter__, __Repeat__, __RepeatUntil__, __Retry__, __Cooldown__, __Timeout__,
+/// __AlwaysSucceed__ and __Guard__ (see [`BehaviorTree`] variants for details). Node succeeds when
+/// its condition is met, so decorators that change success of a node do it with conditions, and all
+/// of them are backed by decorator tasks (see [`crate::task`]). Selector children can also
+/// be given abort modes that let them preempt running nodes (see [`BehaviorTree::abort`]).
+///
 /// How it works
 /// ---
 /// Imagine you have tree like this:
@@ -199,10 +209,13 @@ impl<M> std::fmt::Debug for BehaviorTreeTask<M> {
 pub enum BehaviorTree<M = ()> {
     /// Sequence node runs its children as long as they succeed (boolean AND operation).
     ///
-    /// It locks changes of higher nodes as long as its running child node is locked.
+    /// It locks changes of higher nodes as long as its running child node is locked. Reactive
+    /// sequences also unlock when condition of already passed child is not met anymore. See
+    /// [`SequencerMode`].
     Sequence {
         condition: Box<dyn Condition<M>>,
         nodes: Vec<BehaviorTree<M>>,
+        mode: SequencerMode,
     },
     /// Selector node runs its first children that succeeds (boolean OR operation).
     ///
@@ -213,10 +226,18 @@ pub enum BehaviorTree<M = ()> {
     },
     /// Parallel node runs all its children at the same time.
     ///
-    /// It locks changes of higher nodes as long as any of its running children nodes is locked.
+    /// It locks changes of higher nodes as long as any of its running children nodes is locked, or
+    /// as long as its completion policy is not resolved. See [`ParallelizerPolicy`].
+    ///
+    /// Behavior tree nodes succeed when their condition is met before they start, so there is no
+    /// way for running node to report failure to its parent. That's why parallel node supports
+    /// only success part of completion policy: when policy resolves as failed, node just unlocks
+    /// the same way it does when it succeeds, and parent node carries on. Use [`Parallelizer`]
+    /// directly and read [`Parallelizer::outcome`] when failure has to be handled.
     Parallel {
         condition: Box<dyn Condition<M>>,
         nodes: Vec<BehaviorTree<M>>,
+        policy: ParallelizerPolicy,
     },
     /// State runs certain task.
     ///
@@ -229,6 +250,66 @@ pub enum BehaviorTree<M = ()> {
     ///
     /// Commits on full completion, rolls back on interruption/cancellation.
     Transaction { node: Box<BehaviorTree<M>> },
+    /// Inverter succeeds when wrapped node fails and fails when wrapped node succeeds.
+    ///
+    /// Wrapped node runs only when its condition is met. See [`InverterTask`].
+    Inverter { node: Box<BehaviorTree<M>> },
+    /// Repeat runs wrapped node again whenever it finishes, given number of times or forever.
+    ///
+    /// It locks changes of higher nodes as long as there are runs left. See [`RepeatTask`].
+    Repeat {
+        count: Option<usize>,
+        node: Box<BehaviorTree<M>>,
+    },
+    /// Repeat until runs wrapped node again whenever it finishes, until condition is met.
+    ///
+    /// It locks changes of higher nodes as long as condition is not met. See [`RepeatTask`].
+    RepeatUntil {
+        condition: Box<dyn Condition<M>>,
+        node: Box<BehaviorTree<M>>,
+    },
+    /// Retry runs wrapped node again when it finishes without meeting success condition, up to
+    /// given number of retries.
+    ///
+    /// It locks changes of higher nodes as long as there are retries left. See [`RetryTask`].
+    Retry {
+        retries: usize,
+        condition: Box<dyn Condition<M>>,
+        node: Box<BehaviorTree<M>>,
+    },
+    /// Cooldown fails wrapped node for given duration after it stops. See [`CooldownTask`].
+    Cooldown {
+        duration: Scalar,
+        clock: TaskClock<M>,
+        node: Box<BehaviorTree<M>>,
+    },
+    /// Timeout stops wrapped node when it runs for longer than given duration, which also unlocks
+    /// changes of higher nodes. See [`TimeoutTask`].
+    Timeout {
+        duration: Scalar,
+        clock: TaskClock<M>,
+        node: Box<BehaviorTree<M>>,
+    },
+    /// Always succeed runs wrapped node only when its condition is met, but never fails.
+    ///
+    /// See [`AlwaysSucceedTask`].
+    AlwaysSucceed { node: Box<BehaviorTree<M>> },
+    /// Abort sets abort mode of wrapped node when it's a child of selector node, otherwise it has
+    /// no effect. See [`SelectorAbortMode`].
+    Abort {
+        mode: SelectorAbortMode,
+        node: Box<BehaviorTree<M>>,
+    },
+    /// Guard runs wrapped node only as long as condition is met, checking it every tick and
+    /// aborting wrapped node as soon as it is not met. See [`GuardTask`].
+    Guard {
+        condition: Box<dyn Condition<M>>,
+        node: Box<BehaviorTree<M>>,
+    },
+    /// Subtree is a reference to named tree definition, instantiated by [`BehaviorTreeLibrary`].
+    ///
+    /// Unresolved subtree never runs (its condition always fails).
+    Subtree { name: String },
 }
 
 impl<M> BehaviorTree<M> {
@@ -240,6 +321,19 @@ impl<M> BehaviorTree<M> {
         Self::Sequence {
             condition: Box::new(condition),
             nodes: vec![],
+            mode: Default::default(),
+        }
+    }
+
+    /// Constructs sequence node with condition and mode.
+    pub fn sequence_mode<C>(condition: C, mode: SequencerMode) -> Self
+    where
+        C: Condition<M> + 'static,
+    {
+        Self::Sequence {
+            condition: Box::new(condition),
+            nodes: vec![],
+            mode,
         }
     }
 
@@ -251,6 +345,7 @@ impl<M> BehaviorTree<M> {
         Self::Sequence {
             condition: Box::new(condition),
             nodes,
+            mode: Default::default(),
         }
     }
 
@@ -284,6 +379,7 @@ impl<M> BehaviorTree<M> {
         Self::Parallel {
             condition: Box::new(condition),
             nodes: vec![],
+            policy: Default::default(),
         }
     }
 
@@ -292,9 +388,24 @@ impl<M> BehaviorTree<M> {
     where
         C: Condition<M> + 'static,
     {
-        Self::Selector {
+        Self::Parallel {
             condition: Box::new(condition),
             nodes,
+            policy: Default::default(),
+        }
+    }
+
+    /// Constructs parallel node with condition and completion policy.
+    ///
+    /// See [`Self::Parallel`] for how failure policies work in behavior trees.
+    pub fn parallel_policy<C>(condition: C, policy: ParallelizerPolicy) -> Self
+    where
+        C: Condition<M> + 'static,
+    {
+        Self::Parallel {
+            condition: Box::new(condition),
+            nodes: vec![],
+            policy,
         }
     }
 
@@ -320,14 +431,173 @@ impl<M> BehaviorTree<M> {
         }
     }
 
+    /// Wraps tree node with inverter decorator.
+    pub fn inverter(node: BehaviorTree<M>) -> Self {
+        Self::Inverter {
+            node: Box::new(node),
+        }
+    }
+
+    /// Wraps tree node with decorator that runs it given number of times.
+    pub fn repeat(count: usize, node: BehaviorTree<M>) -> Self {
+        Self::Repeat {
+            count: Some(count),
+            node: Box::new(node),
+        }
+    }
+
+    /// Wraps tree node with decorator that runs it forever.
+    pub fn repeat_forever(node: BehaviorTree<M>) -> Self {
+        Self::Repeat {
+            count: None,
+            node: Box::new(node),
+        }
+    }
+
+    /// Wraps tree node with decorator that runs it until condition is met.
+    pub fn repeat_until<C>(condition: C, node: BehaviorTree<M>) -> Self
+    where
+        C: Condition<M> + 'static,
+    {
+        Self::RepeatUntil {
+            condition: Box::new(condition),
+            node: Box::new(node),
+        }
+    }
+
+    /// Wraps tree node with decorator that runs it again until success condition is met, up to
+    /// given number of retries.
+    pub fn retry<C>(retries: usize, condition: C, node: BehaviorTree<M>) -> Self
+    where
+        C: Condition<M> + 'static,
+    {
+        Self::Retry {
+            retries,
+            condition: Box::new(condition),
+            node: Box::new(node),
+        }
+    }
+
+    /// Wraps tree node with cooldown decorator that reads time from memory using clock.
+    pub fn cooldown<F>(duration: Scalar, clock: F, node: BehaviorTree<M>) -> Self
+    where
+        F: Fn(&M) -> Scalar + 'static + Send + Sync,
+    {
+        Self::Cooldown {
+            duration,
+            clock: Arc::new(clock),
+            node: Box::new(node),
+        }
+    }
+
+    /// Wraps tree node with timeout decorator that reads time from memory using clock.
+    pub fn timeout<F>(duration: Scalar, clock: F, node: BehaviorTree<M>) -> Self
+    where
+        F: Fn(&M) -> Scalar + 'static + Send + Sync,
+    {
+        Self::Timeout {
+            duration,
+            clock: Arc::new(clock),
+            node: Box::new(node),
+        }
+    }
+
+    /// Wraps tree node with decorator that never fails.
+    pub fn always_succeed(node: BehaviorTree<M>) -> Self {
+        Self::AlwaysSucceed {
+            node: Box::new(node),
+        }
+    }
+
+    /// Wraps tree node with decorator that aborts it as soon as condition is not met.
+    pub fn guard<C>(condition: C, node: BehaviorTree<M>) -> Self
+    where
+        C: Condition<M> + 'static,
+    {
+        Self::Guard {
+            condition: Box::new(condition),
+            node: Box::new(node),
+        }
+    }
+
+    /// Sets mode of sequence node (when called on other nodes it does nothing).
+    pub fn mode(mut self, mode: SequencerMode) -> Self {
+        if let Self::Sequence { mode: m, .. } = &mut self {
+            *m = mode;
+        }
+        self
+    }
+
+    /// Sets completion policy of parallel node (when called on other nodes it does nothing).
+    pub fn policy(mut self, policy: ParallelizerPolicy) -> Self {
+        if let Self::Parallel { policy: p, .. } = &mut self {
+            *p = policy;
+        }
+        self
+    }
+
+    /// Wraps tree node with abort mode used when it's a child of selector node.
+    pub fn abort(mode: SelectorAbortMode, node: BehaviorTree<M>) -> Self {
+        Self::Abort {
+            mode,
+            node: Box::new(node),
+        }
+    }
+
+    /// Constructs reference to named subtree defined in [`BehaviorTreeLibrary`].
+    pub fn subtree(name: impl ToString) -> Self {
+        Self::Subtree {
+            name: name.to_string(),
+        }
+    }
+
+    /// Returns child nodes of this node (branch nodes or node wrapped by decorator).
+    pub fn children(&self) -> Vec<&Self> {
+        match self {
+            Self::Sequence { nodes, .. }
+            | Self::Selector { nodes, .. }
+            | Self::Parallel { nodes, .. } => nodes.iter().collect(),
+            Self::State { .. } | Self::Subtree { .. } => vec![],
+            Self::Transaction { node }
+            | Self::Inverter { node }
+            | Self::Repeat { node, .. }
+            | Self::RepeatUntil { node, .. }
+            | Self::Retry { node, .. }
+            | Self::Cooldown { node, .. }
+            | Self::Timeout { node, .. }
+            | Self::AlwaysSucceed { node }
+            | Self::Abort { node, .. }
+            | Self::Guard { node, .. } => vec![node],
+        }
+    }
+
+    /// Returns mutable child nodes of this node (branch nodes or node wrapped by decorator).
+    pub fn children_mut(&mut self) -> Vec<&mut Self> {
+        match self {
+            Self::Sequence { nodes, .. }
+            | Self::Selector { nodes, .. }
+            | Self::Parallel { nodes, .. } => nodes.iter_mut().collect(),
+            Self::State { .. } | Self::Subtree { .. } => vec![],
+            Self::Transaction { node }
+            | Self::Inverter { node }
+            | Self::Repeat { node, .. }
+            | Self::RepeatUntil { node, .. }
+            | Self::Retry { node, .. }
+            | Self::Cooldown { node, .. }
+            | Self::Timeout { node, .. }
+            | Self::AlwaysSucceed { node }
+            | Self::Abort { node, .. }
+            | Self::Guard { node, .. } => vec![node],
+        }
+    }
+
     /// Adds child node to this branch (when called on state node it does nothing).
     pub fn node(mut self, node: BehaviorTree<M>) -> Self {
         match &mut self {
             Self::Sequence { nodes, .. } => nodes.push(node),
             Self::Selector { nodes, .. } => nodes.push(node),
             Self::Parallel { nodes, .. } => nodes.push(node),
-            Self::State { .. } => {}
-            Self::Transaction { .. } => {}
+            _ => {}
         }
         self
     }
@@ -346,7 +616,11 @@ impl<M> BehaviorTree<M> {
         M: 'static,
     {
         match self {
-            Self::Sequence { condition, nodes } => {
+            Self::Sequence {
+                condition,
+                nodes,
+                mode,
+            } => {
                 let states = nodes
                     .into_iter()
                     .map(|node| {
@@ -354,21 +628,29 @@ impl<M> BehaviorTree<M> {
                         SequencerState::new_raw(condition, task)
                     })
                     .collect();
-                let sequencer = Sequencer::new(states, false, false);
+                let sequencer = Sequencer::new(states, false, false).mode(mode);
                 (condition, Box::new(sequencer))
             }
             Self::Selector { condition, nodes } => {
                 let states = nodes
                     .into_iter()
                     .map(|node| {
+                        let (mode, node) = match node {
+                            Self::Abort { mode, node } => (mode, *node),
+                            node => (SelectorAbortMode::default(), node),
+                        };
                         let (condition, task) = node.consume();
-                        SelectorState::new_raw(condition, task)
+                        SelectorState::new_raw(condition, task).abort_mode(mode)
                     })
                     .collect();
                 let selector = Selector::new(states);
                 (condition, Box::new(selector))
             }
-            Self::Parallel { condition, nodes } => {
+            Self::Parallel {
+                condition,
+                nodes,
+                policy,
+            } => {
                 let states = nodes
                     .into_iter()
                     .map(|node| {
@@ -376,7 +658,7 @@ impl<M> BehaviorTree<M> {
                         ParallelizerState::new_raw(condition, task)
                     })
                     .collect();
-                let selector = Parallelizer::new(states);
+                let selector = Parallelizer::new(states).policy(policy);
                 (condition, Box::new(selector))
             }
             Self::State { condition, task } => (condition, task),
@@ -385,6 +667,72 @@ impl<M> BehaviorTree<M> {
                 let transaction_task = TransactionScopeTask::new_raw(task);
                 (condition, Box::new(transaction_task))
             }
+            Self::Inverter { node } => {
+                let (condition, task) = node.consume();
+                let task = InverterTask::new_raw(Arc::from(condition), task);
+                (Box::new(task.condition()), Box::new(task))
+            }
+            Self::Repeat { count, node } => {
+                let (condition, task) = node.consume();
+                let mut task = RepeatTask::new_raw(task);
+                if let Some(count) = count {
+                    task = task.count(count);
+                }
+                (condition, Box::new(task))
+            }
+            Self::RepeatUntil {
+                condition: until,
+                node,
+            } => {
+                let (condition, task) = node.consume();
+                let task = RepeatTask::new_raw(task).until_raw(until);
+                (condition, Box::new(task))
+            }
+            Self::Retry {
+                retries,
+                condition: success,
+                node,
+            } => {
+                let (condition, task) = node.consume();
+                let task = RetryTask::new_raw(task, success, retries);
+                (condition, Box::new(task))
+            }
+            Self::Cooldown {
+                duration,
+                clock,
+                node,
+            } => {
+                let (condition, task) = node.consume();
+                let task = CooldownTask::new_raw(task, duration, clock);
+                let condition = CombinatorAll::new(vec![condition, Box::new(task.condition())]);
+                (Box::new(condition), Box::new(task))
+            }
+            Self::Timeout {
+                duration,
+                clock,
+                node,
+            } => {
+                let (condition, task) = node.consume();
+                let task = TimeoutTask::new_raw(task, duration, clock);
+                (condition, Box::new(task))
+            }
+            Self::AlwaysSucceed { node } => {
+                let (condition, task) = node.consume();
+                let task = AlwaysSucceedTask::new_raw(condition, task);
+                (Box::new(true), Box::new(task))
+            }
+            Self::Abort { node, .. } => node.consume(),
+            Self::Subtree { .. } => (Box::new(false), Box::new(NoTask)),
+            Self::Guard {
+                condition: guard,
+                node,
+            } => {
+                let (condition, task) = node.consume();
+                let guard = Arc::<dyn Condition<M>>::from(guard);
+                let task = GuardTask::new_raw(guard.clone(), task);
+                let condition = CombinatorAll::new(vec![condition, Box::new(guard)]);
+                (Box::new(condition), Box::new(task))
+            }
         }
     }
 }
@@ -392,19 +740,236 @@ impl<M> BehaviorTree<M> {
 impl<M> std::fmt::Debug for BehaviorTree<M> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::Sequence { nodes, .. } => {
-                f.debug_struct("Sequence").field("nodes", &nodes).finish()
-            }
+            Self::Sequence { nodes, mode, .. } => f
+                .debug_struct("Sequence")
+                .field("nodes", &nodes)
+                .field("mode", &mode)
+                .finish(),
             Self::Selector { nodes, .. } => {
                 f.debug_struct("Selector").field("nodes", &nodes).finish()
             }
-            Self::Parallel { nodes, .. } => {
-                f.debug_struct("Parallel").field("nodes", &nodes).finish()
-            }
+            Self::Parallel { nodes, policy, .. } => f
+                .debug_struct("Parallel")
+                .field("nodes", &nodes)
+                .field("policy", &policy)
+                .finish(),
             Self::State { .. } => f.debug_struct("State").finish(),
             Self::Transaction { node } => {
                 f.debug_struct("Transaction").field("node", &node).finish()
             }
+            Self::Inverter { node } => f.debug_struct("Inverter").field("node", &node).finish(),
+            Self::Repeat { count, node } => f
+                .debug_struct("Repeat")
+                .field("count", &count)
+                .field("node", &node)
+                .finish(),
+            Self::RepeatUntil { node, .. } => {
+                f.debug_struct("RepeatUntil").field("node", &node).finish()
+            }
+            Self::Retry { retries, node, .. } => f
+                .debug_struct("Retry")
+                .field("retries", &retries)
+                .field("node", &node)
+                .finish(),
+            Self::Cooldown { duration, node, .. } => f
+                .debug_struct("Cooldown")
+                .field("duration", &duration)
+                .field("node", &node)
+                .finish(),
+            Self::Timeout { duration, node, .. } => f
+                .debug_struct("Timeout")
+                .field("duration", &duration)
+                .field("node", &node)
+                .finish(),
+            Self::AlwaysSucceed { node } => f
+                .debug_struct("AlwaysSucceed")
+                .field("node", &node)
+                .finish(),
+            Self::Abort { mode, node } => f
+                .debug_struct("Abort")
+                .field("mode", &mode)
+                .field("node", &node)
+                .finish(),
+            Self::Guard { node, .. } => f.debug_struct("Guard").field("node", &node).finish(),
+            Self::Subtree { name } => f.debug_struct("Subtree").field("name", &name).finish(),
         }
     }
 }
+
+/// Behavior tree library error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BehaviorTreeLibraryError {
+    /// There is no tree definition with given name.
+    TreeDoesNotExists(String),
+    /// Tree definition references itself, directly or through other definitions.
+    ///
+    /// Contains chain of names that closes the cycle.
+    RecursiveReference(Vec<String>),
+}
+
+/// Function that instantiates new behavior tree.
+pub type BehaviorTreeFactory<M = ()> = Arc<dyn Fn() -> BehaviorTree<M> + Send + Sync>;
+
+/// Collection of named behavior tree definitions that can reference each other.
+///
+/// Since tree nodes own their tasks and conditions, definitions are factories that instantiate
+/// new tree for every subtree reference (see [`BehaviorTree::subtree`]). Library resolves all
+/// references when building trees and reports unknown and recursive references as errors.
+///
+/// # Example
+/// ```
+/// use emergent::prelude::*;
+///
+/// struct Memory {
+///     danger: bool,
+///     fled: usize,
+/// }
+///
+/// let library = BehaviorTreeLibrary::default()
+///     .define("flee", || {
+///         BehaviorTree::state(
+///             ClosureCondition::new(|m: &Memory| m.danger),
+///             ClosureTask::default().enter(|m: &mut Memory| m.fled += 1),
+///         )
+///     })
+///     .define("soldier", || {
+///         BehaviorTree::selector(true)
+///             .node(BehaviorTree::subtree("flee"))
+///             .node(BehaviorTree::state(true, NoTask))
+///     })
+///     .define("worker", || {
+///         BehaviorTree::selector(true)
+///             .node(BehaviorTree::subtree("flee"))
+///             .node(BehaviorTree::state(true, NoTask))
+///     });
+/// assert!(library.validate().is_ok());
+///
+/// let mut soldier = library.build("soldier").unwrap();
+/// let mut worker = library.build("worker").unwrap();
+/// let mut memory = Memory { danger: true, fled: 0 };
+/// assert!(soldier.on_process(&mut memory));
+/// assert!(worker.on_process(&mut memory));
+/// assert_eq!(memory.fled, 2);
+/// ```
+pub struct BehaviorTreeLibrary<M = ()> {
+    definitions: HashMap<String, BehaviorTreeFactory<M>>,
+}
+
+impl<M> Default for BehaviorTreeLibrary<M> {
+    fn default() -> Self {
+        Self {
+            definitions: Default::default(),
+        }
+    }
+}
+
+impl<M> Clone for BehaviorTreeLibrary<M> {
+    fn clone(&self) -> Self {
+        Self {
+            definitions: self.definitions.clone(),
+        }
+    }
+}
+
+impl<M> BehaviorTreeLibrary<M> {
+    /// Adds tree definition with given name.
+    pub fn define<F>(mut self, name: impl ToString, factory: F) -> Self
+    where
+        F: Fn() -> BehaviorTree<M> + 'static + Send + Sync,
+    {
+        self.add(name, factory);
+        self
+    }
+
+    /// Adds tree definition with given name, replacing existing one.
+    pub fn add<F>(&mut self, name: impl ToString, factory: F)
+    where
+        F: Fn() -> BehaviorTree<M> + 'static + Send + Sync,
+    {
+        self.definitions.insert(name.to_string(), Arc::new(factory));
+    }
+
+    /// Removes tree definition with given name.
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.definitions.remove(name).is_some()
+    }
+
+    /// Tells if there is tree definition with given name.
+    pub fn has(&self, name: &str) -> bool {
+        self.definitions.contains_key(name)
+    }
+
+    /// Return iterator over tree definitions names.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.definitions.keys().map(|name| name.as_str())
+    }
+
+    /// Instantiates tree with given name and resolves all its subtree references.
+    pub fn instantiate(&self, name: &str) -> Result<BehaviorTree<M>, BehaviorTreeLibraryError> {
+        let mut tree = BehaviorTree::subtree(name);
+        self.resolve_node(&mut tree, &mut vec![])?;
+        Ok(tree)
+    }
+
+    /// Resolves all subtree references found in given tree.
+    pub fn resolve(
+        &self,
+        mut tree: BehaviorTree<M>,
+    ) -> Result<BehaviorTree<M>, BehaviorTreeLibraryError> {
+        self.resolve_node(&mut tree, &mut vec![])?;
+        Ok(tree)
+    }
+
+    /// Instantiates tree with given name and builds it as a task.
+    pub fn build(&self, name: &str) -> Result<BehaviorTreeTask<M>, BehaviorTreeLibraryError>
+    where
+        M: 'static,
+    {
+        Ok(self.instantiate(name)?.build())
+    }
+
+    /// Checks if all tree definitions can be instantiated.
+    pub fn validate(&self) -> Result<(), BehaviorTreeLibraryError> {
+        let mut names = self.names().collect::<Vec<_>>();
+        names.sort();
+        for name in names {
+            self.instantiate(name)?;
+        }
+        Ok(())
+    }
+
+    fn resolve_node(
+        &self,
+        node: &mut BehaviorTree<M>,
+        stack: &mut Vec<String>,
+    ) -> Result<(), BehaviorTreeLibraryError> {
+        if let BehaviorTree::Subtree { name } = node {
+            if stack.contains(name) {
+                let mut chain = std::mem::take(stack);
+                chain.push(name.to_owned());
+                return Err(BehaviorTreeLibraryError::RecursiveReference(chain));
+            }
+            let Some(factory) = self.definitions.get(name.as_str()) else {
+                return Err(BehaviorTreeLibraryError::TreeDoesNotExists(name.to_owned()));
+            };
+            let mut tree = factory();
+            stack.push(name.to_owned());
+            self.resolve_node(&mut tree, stack)?;
+            stack.pop();
+            *node = tree;
+            return Ok(());
+        }
+        for child in node.children_mut() {
+            self.resolve_node(child, stack)?;
+        }
+        Ok(())
+    }
+}
+
+impl<M> std::fmt::Debug for BehaviorTreeLibrary<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BehaviorTreeLibrary")
+            .field("definitions", &self.definitions.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
@@ -0,0 +1,940 @@
+//! Text format of behavior trees.
+//!
+//! Behavior trees can be written as s-expressions, where each node is a list that starts with node
+//! kind, followed by its properties (`:name value` pairs), names and child nodes:
+//!
+//! ```text
+//! ; comments start with semicolon.
+//! (selector
+//!   (sequence :if hungry
+//!     (state find_food :if food_nearby)
+//!     (state eat :if edible))
+//!   (timeout :duration 5
+//!     (state sleep :if low_energy))
+//!   (state idle))
+//! ```
+//!
+//! Node kinds:
+//! - `(sequence [:if C] [:mode memory|reactive-restart|reactive-abort] node...)`
+//! - `(selector [:if C] node...)`
+//! - `(parallel [:if C] [:success never|require-one|require-all]
+//!   [:failure never|fail-on-first|fail-on-all] [:cancel-remaining true|false] node...)` (see
+//!   [`BehaviorTree::Parallel`] for how failure policies work in behavior trees)
+//! - `(state TASK [:if C])`
+//! - `(transaction node)`
+//! - `(inverter node)`
+//! - `(repeat [:count N] node)` or `(repeat :until C node)`
+//! - `(retry :count N :until C node)`
+//! - `(cooldown :duration D node)`
+//! - `(timeout :duration D node)`
+//! - `(always-succeed node)`
+//! - `(abort :mode none|itself|lower-priority|both node)`
+//! - `(guard :if C node)`
+//! - `(subtree NAME)`
+//!
+//! Conditions (`C`) are either `true`, `false` or names of conditions, and tasks are names of tasks
+//! registered in [`BehaviorTreeRegistry`]. Cooldown and timeout nodes read time with registry clock.
+//!
+//! Nodes can be nested at most [`BEHAVIOR_TREE_DSL_MAX_DEPTH`] levels deep.
+//!
+//! Trees can be written back to text with [`BehaviorTree::to_text`]. Conditions and tasks without
+//! names (see [`Condition::condition_name`] and [`Task::task_name`]) are written as `<unnamed>`,
+//! which cannot be parsed back, so only trees made of named conditions and tasks (which is the case
+//! for trees parsed from text) make a round trip.
+
+use crate::{
+    Scalar,
+    builders::behavior_tree::*,
+    condition::*,
+    decision_makers::{parallelizer::*, selector::*, sequencer::*},
+    task::*,
+};
+use std::{collections::HashMap, sync::Arc};
+
+/// Maximal nesting depth of nodes in behavior tree text.
+pub const BEHAVIOR_TREE_DSL_MAX_DEPTH: usize = 128;
+
+const UNNAMED: &str = "<unnamed>";
+
+type ConditionFactory<M> = Arc<dyn Fn() -> Box<dyn Condition<M>> + Send + Sync>;
+type TaskFactory<M> = Arc<dyn Fn() -> Box<dyn Task<M>> + Send + Sync>;
+
+/// Behavior tree text parsing error kind.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BehaviorTreeDslErrorKind {
+    /// Text has ended before tree definition was complete.
+    UnexpectedEndOfInput,
+    /// Found character that is not part of the format.
+    UnexpectedCharacter(char),
+    /// Found token that is not expected at this place.
+    UnexpectedToken(String),
+    /// There is no node kind with given name.
+    UnknownNodeKind(String),
+    /// There is no condition with given name registered.
+    UnknownCondition(String),
+    /// There is no task with given name registered.
+    UnknownTask(String),
+    /// Node does not have property with given name.
+    UnknownProperty(String),
+    /// Node has property with given name specified more than once.
+    DuplicateProperty(String),
+    /// Property has invalid value.
+    InvalidValue {
+        /// Name of property.
+        property: String,
+        /// Text of invalid value.
+        value: String,
+    },
+    /// Node requires property with given name.
+    MissingProperty(String),
+    /// Node requires name (of task or subtree).
+    MissingName,
+    /// Node requires child node.
+    MissingNode,
+    /// Node accepts only one child node.
+    TooManyNodes,
+    /// Node requires clock that registry does not have.
+    MissingClock,
+    /// Nodes are nested deeper than [`BEHAVIOR_TREE_DSL_MAX_DEPTH`].
+    TooDeep,
+}
+
+/// Behavior tree text parsing error with position (1-based line and column) where it happened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BehaviorTreeDslError {
+    /// What went wrong.
+    pub kind: BehaviorTreeDslErrorKind,
+    /// Line where error happened.
+    pub line: usize,
+    /// Column where error happened.
+    pub column: usize,
+}
+
+impl std::fmt::Display for BehaviorTreeDslErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnexpectedEndOfInput => write!(f, "Unexpected end of input"),
+            Self::UnexpectedCharacter(c) => write!(f, "Unexpected character `{}`", c),
+            Self::UnexpectedToken(token) => write!(f, "Unexpected token `{}`", token),
+            Self::UnknownNodeKind(name) => write!(f, "Unknown node kind `{}`", name),
+            Self::UnknownCondition(name) => write!(f, "Unknown condition `{}`", name),
+            Self::UnknownTask(name) => write!(f, "Unknown task `{}`", name),
+            Self::UnknownProperty(name) => write!(f, "Unknown property `:{}`", name),
+            Self::DuplicateProperty(name) => write!(f, "Duplicate property `:{}`", name),
+            Self::InvalidValue { property, value } => {
+                write!(f, "Invalid value `{}` of property `:{}`", value, property)
+            }
+            Self::MissingProperty(name) => write!(f, "Missing property `:{}`", name),
+            Self::MissingName => write!(f, "Missing name"),
+            Self::MissingNode => write!(f, "Missing child node"),
+            Self::TooManyNodes => write!(f, "Too many child nodes"),
+            Self::MissingClock => write!(f, "Missing clock in registry"),
+            Self::TooDeep => write!(
+                f,
+                "Nodes are nested deeper than {}",
+                BEHAVIOR_TREE_DSL_MAX_DEPTH
+            ),
+        }
+    }
+}
+
+impl std::fmt::Display for BehaviorTreeDslError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} at line {}, column {}",
+            self.kind, self.line, self.column
+        )
+    }
+}
+
+impl std::error::Error for BehaviorTreeDslError {}
+
+/// Registry of named conditions and tasks used to parse behavior trees from text.
+///
+/// Registry keeps factories, so every use of given name in text creates new instance, wrapped with
+/// [`NamedCondition`] or [`NamedTask`], so parsed trees can be written back to text.
+///
+/// # Example
+/// ```
+/// use emergent::prelude::*;
+///
+/// struct Memory {
+///     hungry: bool,
+///     meals: usize,
+/// }
+///
+/// let registry = BehaviorTreeRegistry::default()
+///     .condition("hungry", || ClosureCondition::new(|m: &Memory| m.hungry))
+///     .task("eat", || ClosureTask::default().enter(|m: &mut Memory| m.meals += 1))
+///     .task("idle", || NoTask);
+///
+/// let text = "(selector\n  (state eat :if hungry)\n  (state idle))";
+/// let tree = registry.parse(text).unwrap();
+/// assert_eq!(tree.to_text(), text);
+///
+/// let mut tree = tree.build();
+/// let mut memory = Memory { hungry: true, meals: 0 };
+/// assert!(tree.on_process(&mut memory));
+/// assert_eq!(memory.meals, 1);
+///
+/// let error = registry.parse("(selector\n  (state sleep))").unwrap_err();
+/// assert_eq!(
+///     error,
+///     BehaviorTreeDslError {
+///         kind: BehaviorTreeDslErrorKind::UnknownTask("sleep".to_owned()),
+///         line: 2,
+///         column: 10,
+///     },
+/// );
+/// ```
+pub struct BehaviorTreeRegistry<M = ()> {
+    conditions: HashMap<String, ConditionFactory<M>>,
+    tasks: HashMap<String, TaskFactory<M>>,
+    clock: Option<TaskClock<M>>,
+}
+
+impl<M> Default for BehaviorTreeRegistry<M> {
+    fn default() -> Self {
+        Self {
+            conditions: Default::default(),
+            tasks: Default::default(),
+            clock: None,
+        }
+    }
+}
+
+impl<M> Clone for BehaviorTreeRegistry<M> {
+    fn clone(&self) -> Self {
+        Self {
+            conditions: self.conditions.clone(),
+            tasks: self.tasks.clone(),
+            clock: self.clock.clone(),
+        }
+    }
+}
+
+impl<M> BehaviorTreeRegistry<M> {
+    /// Registers condition factory under given name.
+    pub fn condition<F, C>(mut self, name: impl ToString, factory: F) -> Self
+    where
+        F: Fn() -> C + 'static + Send + Sync,
+        C: Condition<M> + 'static,
+    {
+        self.conditions.insert(
+            name.to_string(),
+            Arc::new(move || Box::new(factory()) as Box<dyn Condition<M>>),
+        );
+        self
+    }
+
+    /// Registers task factory under given name.
+    pub fn task<F, T>(mut self, name: impl ToString, factory: F) -> Self
+    where
+        F: Fn() -> T + 'static + Send + Sync,
+        T: Task<M> + 'static,
+    {
+        self.tasks.insert(
+            name.to_string(),
+            Arc::new(move || Box::new(factory()) as Box<dyn Task<M>>),
+        );
+        self
+    }
+
+    /// Sets clock used by cooldown and timeout nodes.
+    pub fn clock<F>(mut self, clock: F) -> Self
+    where
+        F: Fn(&M) -> Scalar + 'static + Send + Sync,
+    {
+        self.clock = Some(Arc::new(clock));
+        self
+    }
+
+    /// Tells if there is condition registered under given name.
+    pub fn has_condition(&self, name: &str) -> bool {
+        self.conditions.contains_key(name)
+    }
+
+    /// Tells if there is task registered under given name.
+    pub fn has_task(&self, name: &str) -> bool {
+        self.tasks.contains_key(name)
+    }
+
+    /// Creates new instance of condition registered under given name.
+    pub fn make_condition(&self, name: &str) -> Option<NamedCondition<M>> {
+        self.conditions
+            .get(name)
+            .map(|factory| NamedCondition::new_raw(name, factory()))
+    }
+
+    /// Creates new instance of task registered under given name.
+    pub fn make_task(&self, name: &str) -> Option<NamedTask<M>> {
+        self.tasks
+            .get(name)
+            .map(|factory| NamedTask::new_raw(name, factory()))
+    }
+
+    /// Parses behavior tree from text.
+    pub fn parse(&self, text: &str) -> Result<BehaviorTree<M>, BehaviorTreeDslError>
+    where
+        M: 'static,
+    {
+        let (tokens, end) = tokenize(text)?;
+        let mut parser = Parser {
+            registry: self,
+            tokens,
+            index: 0,
+            end,
+        };
+        let tree = parser.parse_node(1)?;
+        if let Some(token) = parser.tokens.get(parser.index) {
+            return Err(token.error(BehaviorTreeDslErrorKind::UnexpectedToken(
+                token.kind.to_string(),
+            )));
+        }
+        Ok(tree)
+    }
+}
+
+impl<M> std::fmt::Debug for BehaviorTreeRegistry<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BehaviorTreeRegistry")
+            .field("conditions", &self.conditions.keys().collect::<Vec<_>>())
+            .field("tasks", &self.tasks.keys().collect::<Vec<_>>())
+            .field("clock", &self.clock.is_some())
+            .finish()
+    }
+}
+
+impl<M> BehaviorTree<M> {
+    /// Writes this tree as text that can be parsed back with [`BehaviorTreeRegistry::parse`].
+    ///
+    /// Conditions and tasks without names are written as `<unnamed>`.
+    /// See [`crate::builders::behavior_tree_dsl`] for text format.
+    pub fn to_text(&self) -> String {
+        let mut result = String::new();
+        write_node(self, 0, &mut result);
+        result
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TokenKind {
+    Open,
+    Close,
+    Property(String),
+    Atom(String),
+}
+
+impl std::fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Open => write!(f, "("),
+            Self::Close => write!(f, ")"),
+            Self::Property(name) => write!(f, ":{}", name),
+            Self::Atom(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    line: usize,
+    column: usize,
+}
+
+impl Token {
+    fn error(&self, kind: BehaviorTreeDslErrorKind) -> BehaviorTreeDslError {
+        BehaviorTreeDslError {
+            kind,
+            line: self.line,
+            column: self.column,
+        }
+    }
+
+    fn atom(&self) -> Option<&str> {
+        match &self.kind {
+            TokenKind::Atom(name) => Some(name),
+            _ => None,
+        }
+    }
+}
+
+fn is_symbol_char(c: char) -> bool {
+    c.is_alphanumeric() || "-_./!?*+".contains(c)
+}
+
+/// Splits text into tokens, returning them along with position of the end of text.
+fn tokenize(text: &str) -> Result<(Vec<Token>, (usize, usize)), BehaviorTreeDslError> {
+    let mut tokens = vec![];
+    let mut chars = text.chars().peekable();
+    let mut line = 1;
+    let mut column = 1;
+    while let Some(c) = chars.next() {
+        let (token_line, token_column) = (line, column);
+        if c == '\n' {
+            line += 1;
+            column = 1;
+            continue;
+        }
+        column += 1;
+        let kind = match c {
+            '(' => TokenKind::Open,
+            ')' => TokenKind::Close,
+            ';' => {
+                while chars.next_if(|c| *c != '\n').is_some() {}
+                continue;
+            }
+            c if c.is_whitespace() => continue,
+            c if c == ':' || is_symbol_char(c) => {
+                let mut name = String::new();
+                if c != ':' {
+                    name.push(c);
+                }
+                while let Some(c) = chars.next_if(|c| is_symbol_char(*c)) {
+                    name.push(c);
+                    column += 1;
+                }
+                if c == ':' {
+                    if name.is_empty() {
+                        return Err(BehaviorTreeDslError {
+                            kind: BehaviorTreeDslErrorKind::UnexpectedCharacter(c),
+                            line: token_line,
+                            column: token_column,
+                        });
+                    }
+                    TokenKind::Property(name)
+                } else {
+                    TokenKind::Atom(name)
+                }
+            }
+            c => {
+                return Err(BehaviorTreeDslError {
+                    kind: BehaviorTreeDslErrorKind::UnexpectedCharacter(c),
+                    line: token_line,
+                    column: token_column,
+                });
+            }
+        };
+        tokens.push(Token {
+            kind,
+            line: token_line,
+            column: token_column,
+        });
+    }
+    Ok((tokens, (line, column)))
+}
+
+struct Parser<'a, M> {
+    registry: &'a BehaviorTreeRegistry<M>,
+    tokens: Vec<Token>,
+    index: usize,
+    end: (usize, usize),
+}
+
+impl<M> Parser<'_, M>
+where
+    M: 'static,
+{
+    fn peek(&self) -> Result<Token, BehaviorTreeDslError> {
+        self.tokens
+            .get(self.index)
+            .cloned()
+            .ok_or(BehaviorTreeDslError {
+                kind: BehaviorTreeDslErrorKind::UnexpectedEndOfInput,
+                line: self.end.0,
+                column: self.end.1,
+            })
+    }
+
+    fn next(&mut self) -> Result<Token, BehaviorTreeDslError> {
+        let token = self.peek()?;
+        self.index += 1;
+        Ok(token)
+    }
+
+    fn parse_node(&mut self, depth: usize) -> Result<BehaviorTree<M>, BehaviorTreeDslError> {
+        let open = self.next()?;
+        if open.kind != TokenKind::Open {
+            return Err(open.error(BehaviorTreeDslErrorKind::UnexpectedToken(
+                open.kind.to_string(),
+            )));
+        }
+        if depth > BEHAVIOR_TREE_DSL_MAX_DEPTH {
+            return Err(open.error(BehaviorTreeDslErrorKind::TooDeep));
+        }
+        let kind = self.next()?;
+        let Some(kind_name) = kind.atom().map(|name| name.to_owned()) else {
+            return Err(kind.error(BehaviorTreeDslErrorKind::UnexpectedToken(
+                kind.kind.to_string(),
+            )));
+        };
+        let mut parts = NodeParts {
+            properties: vec![],
+            names: vec![],
+            nodes: vec![],
+            close: kind.clone(),
+        };
+        loop {
+            let token = self.peek()?;
+            match &token.kind {
+                TokenKind::Close => {
+                    self.index += 1;
+                    parts.close = token;
+                    break;
+                }
+                TokenKind::Open => {
+                    let node = self.parse_node(depth + 1)?;
+                    parts.nodes.push((node, token));
+                }
+                TokenKind::Property(name) => {
+                    self.index += 1;
+                    let value = self.next()?;
+                    if value.atom().is_none() {
+                        return Err(value.error(BehaviorTreeDslErrorKind::UnexpectedToken(
+                            value.kind.to_string(),
+                        )));
+                    }
+                    if parts.properties.iter().any(|(n, _, _)| n == name) {
+                        return Err(token
+                            .error(BehaviorTreeDslErrorKind::DuplicateProperty(name.to_owned())));
+                    }
+                    parts
+                        .properties
+                        .push((name.to_owned(), token.clone(), value));
+                }
+                TokenKind::Atom(_) => {
+                    self.index += 1;
+                    parts.names.push(token);
+                }
+            }
+        }
+        self.build_node(&kind_name, &kind, parts)
+    }
+
+    fn build_node(
+        &self,
+        kind_name: &str,
+        kind: &Token,
+        mut parts: NodeParts<M>,
+    ) -> Result<BehaviorTree<M>, BehaviorTreeDslError> {
+        let result = match kind_name {
+            "sequence" => {
+                let condition = self.condition_property(&mut parts, "if")?;
+                let mode = match parts.take_property("mode") {
+                    Some(value) => parse_sequencer_mode(&value, "mode")?,
+                    None => Default::default(),
+                };
+                BehaviorTree::Sequence {
+                    condition,
+                    nodes: parts.take_nodes(),
+                    mode,
+                }
+            }
+            "selector" => BehaviorTree::Selector {
+                condition: self.condition_property(&mut parts, "if")?,
+                nodes: parts.take_nodes(),
+            },
+            "parallel" => {
+                let condition = self.condition_property(&mut parts, "if")?;
+                let mut policy = ParallelizerPolicy::default();
+                if let Some(value) = parts.take_property("success") {
+                    policy = policy.success(parse_success_policy(&value, "success")?);
+                }
+                if let Some(value) = parts.take_property("failure") {
+                    policy = policy.failure(parse_failure_policy(&value, "failure")?);
+                }
+                if let Some(value) = parts.take_property("cancel-remaining") {
+                    policy = policy.cancel_remaining(parse_value(&value, "cancel-remaining")?);
+                }
+                BehaviorTree::Parallel {
+                    condition,
+                    nodes: parts.take_nodes(),
+                    policy,
+                }
+            }
+            "state" => {
+                let name = parts.take_name()?;
+                let Some(task) = self.registry.make_task(name.atom().unwrap()) else {
+                    return Err(name.error(BehaviorTreeDslErrorKind::UnknownTask(
+                        name.atom().unwrap().to_owned(),
+                    )));
+                };
+                BehaviorTree::State {
+                    condition: self.condition_property(&mut parts, "if")?,
+                    task: Box::new(task),
+                }
+            }
+            "transaction" => BehaviorTree::transactional(parts.take_node()?),
+            "inverter" => BehaviorTree::inverter(parts.take_node()?),
+            "repeat" => {
+                // Repeating with count ignores `:until`, so it gets reported as unknown property.
+                let node = Box::new(parts.take_node()?);
+                match parts.take_property("count") {
+                    Some(value) => BehaviorTree::Repeat {
+                        count: Some(parse_value(&value, "count")?),
+                        node,
+                    },
+                    None if parts.find_property("until") => BehaviorTree::RepeatUntil {
+                        condition: self.condition_property(&mut parts, "until")?,
+                        node,
+                    },
+                    None => BehaviorTree::Repeat { count: None, node },
+                }
+            }
+            "retry" => {
+                let Some(value) = parts.take_property("count") else {
+                    return Err(kind.error(BehaviorTreeDslErrorKind::MissingProperty(
+                        "count".to_owned(),
+                    )));
+                };
+                let retries = parse_value(&value, "count")?;
+                if !parts.find_property("until") {
+                    return Err(kind.error(BehaviorTreeDslErrorKind::MissingProperty(
+                        "until".to_owned(),
+                    )));
+                }
+                let condition = self.condition_property(&mut parts, "until")?;
+                BehaviorTree::Retry {
+                    retries,
+                    condition,
+                    node: Box::new(parts.take_node()?),
+                }
+            }
+            "cooldown" | "timeout" => {
+                let Some(value) = parts.take_property("duration") else {
+                    return Err(kind.error(BehaviorTreeDslErrorKind::MissingProperty(
+                        "duration".to_owned(),
+                    )));
+                };
+                let duration = parse_value(&value, "duration")?;
+                let Some(clock) = self.registry.clock.clone() else {
+                    return Err(kind.error(BehaviorTreeDslErrorKind::MissingClock));
+                };
+                let node = Box::new(parts.take_node()?);
+                if kind_name == "cooldown" {
+                    BehaviorTree::Cooldown {
+                        duration,
+                        clock,
+                        node,
+                    }
+                } else {
+                    BehaviorTree::Timeout {
+                        duration,
+                        clock,
+                        node,
+                    }
+                }
+            }
+            "always-succeed" => BehaviorTree::always_succeed(parts.take_node()?),
+            "abort" => {
+                let Some(value) = parts.take_property("mode") else {
+                    return Err(
+                        kind.error(BehaviorTreeDslErrorKind::MissingProperty("mode".to_owned()))
+                    );
+                };
+                let mode = parse_abort_mode(&value, "mode")?;
+                BehaviorTree::abort(mode, parts.take_node()?)
+            }
+            "guard" => {
+                if !parts.find_property("if") {
+                    return Err(
+                        kind.error(BehaviorTreeDslErrorKind::MissingProperty("if".to_owned()))
+                    );
+                }
+                BehaviorTree::Guard {
+                    condition: self.condition_property(&mut parts, "if")?,
+                    node: Box::new(parts.take_node()?),
+                }
+            }
+            "subtree" => BehaviorTree::subtree(parts.take_name()?.atom().unwrap()),
+            _ => {
+                return Err(kind.error(BehaviorTreeDslErrorKind::UnknownNodeKind(
+                    kind_name.to_owned(),
+                )));
+            }
+        };
+        parts.finish()?;
+        Ok(result)
+    }
+
+    fn condition_property(
+        &self,
+        parts: &mut NodeParts<M>,
+        property: &str,
+    ) -> Result<Box<dyn Condition<M>>, BehaviorTreeDslError> {
+        let Some(value) = parts.take_property(property) else {
+            return Ok(Box::new(true));
+        };
+        match value.atom().unwrap() {
+            "true" => Ok(Box::new(true)),
+            "false" => Ok(Box::new(false)),
+            name => match self.registry.make_condition(name) {
+                Some(condition) => Ok(Box::new(condition)),
+                None => {
+                    Err(value.error(BehaviorTreeDslErrorKind::UnknownCondition(name.to_owned())))
+                }
+            },
+        }
+    }
+}
+
+struct NodeParts<M> {
+    /// Properties names, property tokens and value tokens.
+    properties: Vec<(String, Token, Token)>,
+    names: Vec<Token>,
+    nodes: Vec<(BehaviorTree<M>, Token)>,
+    close: Token,
+}
+
+impl<M> NodeParts<M> {
+    fn find_property(&self, name: &str) -> bool {
+        self.properties.iter().any(|(n, _, _)| n == name)
+    }
+
+    fn take_property(&mut self, name: &str) -> Option<Token> {
+        let index = self.properties.iter().position(|(n, _, _)| n == name)?;
+        Some(self.properties.remove(index).2)
+    }
+
+    fn take_name(&mut self) -> Result<Token, BehaviorTreeDslError> {
+        if self.names.is_empty() {
+            return Err(self.close.error(BehaviorTreeDslErrorKind::MissingName));
+        }
+        Ok(self.names.remove(0))
+    }
+
+    fn take_nodes(&mut self) -> Vec<BehaviorTree<M>> {
+        std::mem::take(&mut self.nodes)
+            .into_iter()
+            .map(|(node, _)| node)
+            .collect()
+    }
+
+    fn take_node(&mut self) -> Result<BehaviorTree<M>, BehaviorTreeDslError> {
+        if self.nodes.is_empty() {
+            return Err(self.close.error(BehaviorTreeDslErrorKind::MissingNode));
+        }
+        if let Some((_, token)) = self.nodes.get(1) {
+            return Err(token.error(BehaviorTreeDslErrorKind::TooManyNodes));
+        }
+        Ok(self.nodes.remove(0).0)
+    }
+
+    fn finish(self) -> Result<(), BehaviorTreeDslError> {
+        if let Some((name, token, _)) = self.properties.first() {
+            return Err(token.error(BehaviorTreeDslErrorKind::UnknownProperty(name.to_owned())));
+        }
+        if let Some(token) = self.names.first() {
+            return Err(token.error(BehaviorTreeDslErrorKind::UnexpectedToken(
+                token.kind.to_string(),
+            )));
+        }
+        if let Some((_, token)) = self.nodes.first() {
+            return Err(token.error(BehaviorTreeDslErrorKind::TooManyNodes));
+        }
+        Ok(())
+    }
+}
+
+fn invalid_value(value: &Token, property: &str) -> BehaviorTreeDslError {
+    value.error(BehaviorTreeDslErrorKind::InvalidValue {
+        property: property.to_owned(),
+        value: value.kind.to_string(),
+    })
+}
+
+fn parse_value<T>(value: &Token, property: &str) -> Result<T, BehaviorTreeDslError>
+where
+    T: std::str::FromStr,
+{
+    value
+        .atom()
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| invalid_value(value, property))
+}
+
+fn parse_sequencer_mode(
+    value: &Token,
+    property: &str,
+) -> Result<SequencerMode, BehaviorTreeDslError> {
+    match value.atom() {
+        Some("memory") => Ok(SequencerMode::Memory),
+        Some("reactive-restart") => Ok(SequencerMode::ReactiveRestart),
+        Some("reactive-abort") => Ok(SequencerMode::ReactiveAbort),
+        _ => Err(invalid_value(value, property)),
+    }
+}
+
+fn sequencer_mode_name(mode: SequencerMode) -> &'static str {
+    match mode {
+        SequencerMode::Memory => "memory",
+        SequencerMode::ReactiveRestart => "reactive-restart",
+        SequencerMode::ReactiveAbort => "reactive-abort",
+    }
+}
+
+fn parse_success_policy(
+    value: &Token,
+    property: &str,
+) -> Result<ParallelizerSuccessPolicy, BehaviorTreeDslError> {
+    match value.atom() {
+        Some("never") => Ok(ParallelizerSuccessPolicy::Never),
+        Some("require-one") => Ok(ParallelizerSuccessPolicy::RequireOne),
+        Some("require-all") => Ok(ParallelizerSuccessPolicy::RequireAll),
+        _ => Err(invalid_value(value, property)),
+    }
+}
+
+fn success_policy_name(policy: ParallelizerSuccessPolicy) -> &'static str {
+    match policy {
+        ParallelizerSuccessPolicy::Never => "never",
+        ParallelizerSuccessPolicy::RequireOne => "require-one",
+        ParallelizerSuccessPolicy::RequireAll => "require-all",
+    }
+}
+
+fn parse_failure_policy(
+    value: &Token,
+    property: &str,
+) -> Result<ParallelizerFailurePolicy, BehaviorTreeDslError> {
+    match value.atom() {
+        Some("never") => Ok(ParallelizerFailurePolicy::Never),
+        Some("fail-on-first") => Ok(ParallelizerFailurePolicy::FailOnFirst),
+        Some("fail-on-all") => Ok(ParallelizerFailurePolicy::FailOnAll),
+        _ => Err(invalid_value(value, property)),
+    }
+}
+
+fn failure_policy_name(policy: ParallelizerFailurePolicy) -> &'static str {
+    match policy {
+        ParallelizerFailurePolicy::Never => "never",
+        ParallelizerFailurePolicy::FailOnFirst => "fail-on-first",
+        ParallelizerFailurePolicy::FailOnAll => "fail-on-all",
+    }
+}
+
+fn parse_abort_mode(
+    value: &Token,
+    property: &str,
+) -> Result<SelectorAbortMode, BehaviorTreeDslError> {
+    match value.atom() {
+        Some("none") => Ok(SelectorAbortMode::None),
+        Some("itself") => Ok(SelectorAbortMode::Itself),
+        Some("lower-priority") => Ok(SelectorAbortMode::LowerPriority),
+        Some("both") => Ok(SelectorAbortMode::Both),
+        _ => Err(invalid_value(value, property)),
+    }
+}
+
+fn abort_mode_name(mode: SelectorAbortMode) -> &'static str {
+    match mode {
+        SelectorAbortMode::None => "none",
+        SelectorAbortMode::Itself => "itself",
+        SelectorAbortMode::LowerPriority => "lower-priority",
+        SelectorAbortMode::Both => "both",
+    }
+}
+
+fn condition_name<M>(condition: &dyn Condition<M>) -> &str {
+    condition.condition_name().unwrap_or(UNNAMED)
+}
+
+/// Writes `:if` property, skipping it when condition always succeeds.
+fn write_if<M>(condition: &dyn Condition<M>, output: &mut String) {
+    let name = condition_name(condition);
+    if name != "true" {
+        output.push_str(" :if ");
+        output.push_str(name);
+    }
+}
+
+fn write_node<M>(node: &BehaviorTree<M>, indent: usize, output: &mut String) {
+    output.push('(');
+    match node {
+        BehaviorTree::Sequence {
+            condition, mode, ..
+        } => {
+            output.push_str("sequence");
+            write_if(condition.as_ref(), output);
+            if *mode != SequencerMode::default() {
+                output.push_str(" :mode ");
+                output.push_str(sequencer_mode_name(*mode));
+            }
+        }
+        BehaviorTree::Selector { condition, .. } => {
+            output.push_str("selector");
+            write_if(condition.as_ref(), output);
+        }
+        BehaviorTree::Parallel {
+            condition, policy, ..
+        } => {
+            output.push_str("parallel");
+            write_if(condition.as_ref(), output);
+            let default = ParallelizerPolicy::default();
+            if policy.get_success() != default.get_success() {
+                output.push_str(" :success ");
+                output.push_str(success_policy_name(policy.get_success()));
+            }
+            if policy.get_failure() != default.get_failure() {
+                output.push_str(" :failure ");
+                output.push_str(failure_policy_name(policy.get_failure()));
+            }
+            if policy.get_cancel_remaining() != default.get_cancel_remaining() {
+                output.push_str(&format!(
+                    " :cancel-remaining {}",
+                    policy.get_cancel_remaining()
+                ));
+            }
+        }
+        BehaviorTree::State { condition, task } => {
+            output.push_str("state ");
+            output.push_str(task.task_name().unwrap_or(UNNAMED));
+            write_if(condition.as_ref(), output);
+        }
+        BehaviorTree::Transaction { .. } => output.push_str("transaction"),
+        BehaviorTree::Inverter { .. } => output.push_str("inverter"),
+        BehaviorTree::Repeat { count, .. } => {
+            output.push_str("repeat");
+            if let Some(count) = count {
+                output.push_str(&format!(" :count {}", count));
+            }
+        }
+        BehaviorTree::RepeatUntil { condition, .. } => {
+            output.push_str("repeat :until ");
+            output.push_str(condition_name(condition.as_ref()));
+        }
+        BehaviorTree::Retry {
+            retries, condition, ..
+        } => {
+            output.push_str(&format!("retry :count {} :until ", retries));
+            output.push_str(condition_name(condition.as_ref()));
+        }
+        BehaviorTree::Cooldown { duration, .. } => {
+            output.push_str(&format!("cooldown :duration {}", duration));
+        }
+        BehaviorTree::Timeout { duration, .. } => {
+            output.push_str(&format!("timeout :duration {}", duration));
+        }
+        BehaviorTree::AlwaysSucceed { .. } => output.push_str("always-succeed"),
+        BehaviorTree::Abort { mode, .. } => {
+            output.push_str("abort :mode ");
+            output.push_str(abort_mode_name(*mode));
+        }
+        BehaviorTree::Guard { condition, .. } => {
+            output.push_str("guard :if ");
+            output.push_str(condition_name(condition.as_ref()));
+        }
+        BehaviorTree::Subtree { name } => {
+            output.push_str("subtree ");
+            output.push_str(name);
+        }
+    }
+    for child in node.children() {
+        output.push('\n');
+        output.extend(std::iter::repeat_n(' ', indent + 2));
+        write_node(child, indent + 2, output);
+    }
+    output.push(')');
+}
@@ -1,6 +1,10 @@
 //! LOD builder.
 
-use crate::{condition::*, decision_makers::selector::*, task::*};
+use crate::{
+    Scalar, condition::*, consideration::*, decision_makers::selector::*, memory::blackboard::*,
+    task::*,
+};
+use std::sync::{Arc, Mutex};
 
 /// Wrapper around memory that holds additional information about current level of details.
 pub struct LodMemory<M = ()> {
@@ -71,7 +75,64 @@ where
 /// lod.update(&mut memory);
 /// assert_eq!(memory.memory.hunger, 4.0);
 /// ```
-pub struct Lod<M = ()>(Vec<Box<dyn Task<LodMemory<M>>>>);
+///
+/// # Handoff
+/// Levels added with [`Lod::level_handoff`] implement [`LodHandoff`], so when selector switches
+/// levels, old level exports [`LodSummary`] of what it was doing and new level imports it before
+/// it gets entered, so level transitions preserve intent of the agent.
+///
+/// ```
+/// use emergent::prelude::*;
+///
+/// // memory tells where agent is heading to.
+/// type Memory = LodMemory<Option<String>>;
+///
+/// #[derive(Default)]
+/// struct Walk {
+///     target: Option<String>,
+/// }
+///
+/// impl Task<Memory> for Walk {
+///     fn on_enter(&mut self, memory: &mut Memory) {
+///         memory.memory = self.target.clone();
+///     }
+/// }
+///
+/// impl LodHandoff<Option<String>> for Walk {
+///     fn export_summary(&self, _: &Memory, summary: &mut LodSummary) {
+///         if let Some(target) = &self.target {
+///             summary.data.set("target".to_owned(), target.to_owned());
+///         }
+///     }
+///
+///     fn import_summary(&mut self, _: &mut Memory, summary: &LodSummary) {
+///         self.target = summary.data.get::<String>("target").cloned();
+///     }
+/// }
+///
+/// let mut lod = Lod::default()
+///     .level_handoff(Walk::default())
+///     .level_handoff(Walk {
+///         target: Some("tavern".to_owned()),
+///     })
+///     .build();
+///
+/// let mut memory = LodMemory { lod_level: 1, memory: None };
+/// assert!(lod.process(&mut memory));
+/// assert_eq!(memory.memory.as_deref(), Some("tavern"));
+/// // background level continues walking to the same place.
+/// memory.lod_level = 0;
+/// memory.memory = None;
+/// assert!(lod.process(&mut memory));
+/// assert_eq!(memory.memory.as_deref(), Some("tavern"));
+/// assert_eq!(lod.active_index(), Some(0));
+/// ```
+pub struct Lod<M = ()>(Vec<LodLevel<M>>);
+
+enum LodLevel<M> {
+    Task(Box<dyn Task<LodMemory<M>>>),
+    Handoff(Box<dyn LodHandoff<M>>),
+}
 
 impl<M> Default for Lod<M> {
     fn default() -> Self {
@@ -82,7 +143,7 @@ impl<M> Default for Lod<M> {
 impl<M> Lod<M> {
     /// Constructs new LOD with list of tasks as LOD levels.
     pub fn new(levels: Vec<Box<dyn Task<LodMemory<M>>>>) -> Self {
-        Self(levels)
+        Self(levels.into_iter().map(LodLevel::Task).collect())
     }
 
     /// Adds new level with task.
@@ -90,13 +151,28 @@ impl<M> Lod<M> {
     where
         T: Task<LodMemory<M>> + 'static,
     {
-        self.0.push(Box::new(task));
+        self.0.push(LodLevel::Task(Box::new(task)));
         self
     }
 
     /// Adds new level with task.
     pub fn level_raw(mut self, task: Box<dyn Task<LodMemory<M>>>) -> Self {
-        self.0.push(task);
+        self.0.push(LodLevel::Task(task));
+        self
+    }
+
+    /// Adds new level with task that exports and imports state summary on level change.
+    pub fn level_handoff<T>(mut self, task: T) -> Self
+    where
+        T: LodHandoff<M> + 'static,
+    {
+        self.0.push(LodLevel::Handoff(Box::new(task)));
+        self
+    }
+
+    /// Adds new level with task that exports and imports state summary on level change.
+    pub fn level_handoff_raw(mut self, task: Box<dyn LodHandoff<M>>) -> Self {
+        self.0.push(LodLevel::Handoff(task));
         self
     }
 
@@ -105,16 +181,21 @@ impl<M> Lod<M> {
     where
         M: 'static,
     {
+        let summary = Arc::new(Mutex::new(None));
         Selector::new(
             self.0
                 .into_iter()
                 .enumerate()
-                .map(|(index, task)| {
+                .map(|(index, level)| {
                     SelectorState::new_raw(
                         Box::new(ClosureCondition::new(move |memory: &LodMemory<M>| {
                             memory.lod_level == index
                         })),
-                        task,
+                        Box::new(LodLevelTask {
+                            index,
+                            level,
+                            summary: summary.clone(),
+                        }),
                     )
                 })
                 .collect(),
@@ -127,3 +208,348 @@ impl<M> std::fmt::Debug for Lod<M> {
         f.debug_struct("Lod").finish()
     }
 }
+
+/// Abstract summary of what agent was doing at given level of details, passed between levels.
+///
+/// Data is stored in [`Blackboard`] so levels can agree on named properties (for example current
+/// state or plan) without knowing about each other.
+#[derive(Default)]
+pub struct LodSummary {
+    /// Level that exported this summary.
+    pub from_level: usize,
+    /// Level that imports this summary.
+    pub to_level: usize,
+    /// Summarized state written by exporting level and read by importing level.
+    pub data: Blackboard,
+}
+
+impl std::fmt::Debug for LodSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LodSummary")
+            .field("from_level", &self.from_level)
+            .field("to_level", &self.to_level)
+            .field("data", &self.data.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+/// LOD level task that can hand off its state to other levels.
+///
+/// When [`Lod`] switches levels, old level gets [`LodHandoff::export_summary`] called right before
+/// it gets stopped and new level gets [`LodHandoff::import_summary`] called right before it gets
+/// entered. Summary is not passed if any of the levels was added without handoff.
+pub trait LodHandoff<M = ()>: Task<LodMemory<M>> {
+    /// Writes summary of current state of this level.
+    #[allow(unused_variables)]
+    fn export_summary(&self, memory: &LodMemory<M>, summary: &mut LodSummary) {}
+
+    /// Reads summary of state exported by previous level.
+    #[allow(unused_variables)]
+    fn import_summary(&mut self, memory: &mut LodMemory<M>, summary: &LodSummary) {}
+}
+
+struct LodLevelTask<M> {
+    index: usize,
+    level: LodLevel<M>,
+    summary: Arc<Mutex<Option<LodSummary>>>,
+}
+
+impl<M> LodLevelTask<M> {
+    fn task(&self) -> &dyn Task<LodMemory<M>> {
+        match &self.level {
+            LodLevel::Task(task) => task.as_ref(),
+            LodLevel::Handoff(task) => task.as_ref(),
+        }
+    }
+
+    fn task_mut(&mut self) -> &mut dyn Task<LodMemory<M>> {
+        match &mut self.level {
+            LodLevel::Task(task) => task.as_mut(),
+            LodLevel::Handoff(task) => task.as_mut(),
+        }
+    }
+}
+
+impl<M> Task<LodMemory<M>> for LodLevelTask<M> {
+    fn is_locked(&self, memory: &LodMemory<M>) -> bool {
+        self.task().is_locked(memory)
+    }
+
+    fn on_enter(&mut self, memory: &mut LodMemory<M>) {
+        let mut summary = None;
+        if let Ok(mut current) = self.summary.lock() {
+            summary = current.take();
+        }
+        if let LodLevel::Handoff(task) = &mut self.level
+            && let Some(mut summary) = summary
+            && summary.from_level != self.index
+        {
+            summary.to_level = self.index;
+            task.import_summary(memory, &summary);
+        }
+        self.task_mut().on_enter(memory);
+    }
+
+    fn on_exit(&mut self, memory: &mut LodMemory<M>) {
+        self.task_mut().on_exit(memory);
+    }
+
+    fn on_stop(&mut self, memory: &mut LodMemory<M>, reason: TaskStopReason) {
+        let mut summary = None;
+        if let LodLevel::Handoff(task) = &self.level
+            && reason == TaskStopReason::Replaced
+        {
+            let mut result = LodSummary {
+                from_level: self.index,
+                to_level: memory.lod_level,
+                data: Default::default(),
+            };
+            task.export_summary(memory, &mut result);
+            summary = Some(result);
+        }
+        if let Ok(mut current) = self.summary.lock() {
+            *current = summary;
+        }
+        self.task_mut().on_stop(memory, reason);
+    }
+
+    fn on_update(&mut self, memory: &mut LodMemory<M>) {
+        self.task_mut().on_update(memory);
+    }
+
+    fn on_process(&mut self, memory: &mut LodMemory<M>) -> bool {
+        self.task_mut().on_process(memory)
+    }
+
+    fn task_name(&self) -> Option<&str> {
+        self.task().task_name()
+    }
+}
+
+/// Task that computes level of details from importance of the agent and runs wrapped LOD task.
+///
+/// Importance is scored by [`Consideration`] and level of details is the number of thresholds
+/// that importance has reached, so with thresholds `[0.3, 0.7]` importance `0.5` gives level `1`.
+/// Thresholds are expected to be sorted in ascending order.
+///
+/// Hysteresis prevents level from flickering when importance oscillates around threshold: level
+/// goes up only when importance reaches `threshold + hysteresis` and goes down only when
+/// importance drops below `threshold - hysteresis`.
+///
+/// Each level can have its own update frequency, so for example background agents can process and
+/// update their decision making only every Nth tick. Tick is counted on every
+/// [`Task::on_update`] call and level change always processes new level immediately.
+///
+/// # Example
+/// ```
+/// use emergent::prelude::*;
+///
+/// struct Memory {
+///     distance: Scalar,
+///     updates: usize,
+/// }
+///
+/// let lod = Lod::default()
+///     .level(ClosureTask::default().update(|m: &mut LodMemory<Memory>| m.memory.updates += 1))
+///     .level(ClosureTask::default().update(|m: &mut LodMemory<Memory>| m.memory.updates += 10))
+///     .build();
+/// let mut controller = LodController::new(
+///     ClosureConsideration::new(|m: &Memory| 1.0 - m.distance / 100.0),
+///     lod,
+/// )
+/// .threshold(0.5)
+/// .hysteresis(0.1)
+/// // background agents update only every 4th tick.
+/// .frequency(0, 4);
+///
+/// let mut memory = LodMemory {
+///     lod_level: 0,
+///     memory: Memory { distance: 90.0, updates: 0 },
+/// };
+/// for _ in 0..8 {
+///     controller.on_process(&mut memory);
+///     controller.on_update(&mut memory);
+/// }
+/// assert_eq!(memory.lod_level, 0);
+/// assert_eq!(memory.memory.updates, 2);
+///
+/// // importance 0.55 is within hysteresis range so level does not change yet.
+/// memory.memory.distance = 45.0;
+/// controller.on_process(&mut memory);
+/// assert_eq!(memory.lod_level, 0);
+///
+/// memory.memory.distance = 30.0;
+/// controller.on_process(&mut memory);
+/// controller.on_update(&mut memory);
+/// assert_eq!(memory.lod_level, 1);
+/// assert_eq!(memory.memory.updates, 12);
+/// ```
+pub struct LodController<M = ()> {
+    importance: Box<dyn Consideration<M>>,
+    thresholds: Vec<Scalar>,
+    hysteresis: Scalar,
+    frequencies: Vec<usize>,
+    task: Box<dyn Task<LodMemory<M>>>,
+    tick: usize,
+}
+
+impl<M> LodController<M> {
+    /// Constructs new LOD controller with importance consideration and LOD task (usually selector
+    /// built by [`Lod`]).
+    pub fn new<C, T>(importance: C, task: T) -> Self
+    where
+        C: Consideration<M> + 'static,
+        T: Task<LodMemory<M>> + 'static,
+    {
+        Self::new_raw(Box::new(importance), Box::new(task))
+    }
+
+    /// Constructs new LOD controller with raw importance consideration and LOD task.
+    pub fn new_raw(
+        importance: Box<dyn Consideration<M>>,
+        task: Box<dyn Task<LodMemory<M>>>,
+    ) -> Self {
+        Self {
+            importance,
+            thresholds: vec![],
+            hysteresis: 0.0,
+            frequencies: vec![],
+            task,
+            tick: 0,
+        }
+    }
+
+    /// Adds importance threshold that has to be reached to go to next level.
+    pub fn threshold(mut self, value: Scalar) -> Self {
+        self.thresholds.push(value);
+        self
+    }
+
+    /// Sets importance thresholds for all levels above level `0`.
+    pub fn thresholds(mut self, values: Vec<Scalar>) -> Self {
+        self.thresholds = values;
+        self
+    }
+
+    /// Sets hysteresis applied around thresholds.
+    pub fn hysteresis(mut self, value: Scalar) -> Self {
+        self.hysteresis = value;
+        self
+    }
+
+    /// Sets update frequency of given level (level task is processed and updated every `every`
+    /// ticks). Levels without frequency set are processed and updated on every tick.
+    pub fn frequency(mut self, level: usize, every: usize) -> Self {
+        if self.frequencies.len() <= level {
+            self.frequencies.resize(level + 1, 1);
+        }
+        self.frequencies[level] = every.max(1);
+        self
+    }
+
+    /// Returns importance thresholds.
+    pub fn get_thresholds(&self) -> &[Scalar] {
+        &self.thresholds
+    }
+
+    /// Returns hysteresis.
+    pub fn get_hysteresis(&self) -> Scalar {
+        self.hysteresis
+    }
+
+    /// Returns update frequency of given level.
+    pub fn get_frequency(&self, level: usize) -> usize {
+        self.frequencies.get(level).copied().unwrap_or(1)
+    }
+
+    /// Returns number of ticks since current level got active.
+    pub fn tick(&self) -> usize {
+        self.tick
+    }
+
+    /// Returns wrapped LOD task.
+    pub fn task(&self) -> &dyn Task<LodMemory<M>> {
+        self.task.as_ref()
+    }
+
+    /// Tells if given level should be processed and updated in current tick.
+    pub fn is_due(&self, level: usize) -> bool {
+        self.tick.is_multiple_of(self.get_frequency(level))
+    }
+
+    /// Computes level of details for given memory, starting from `current` level to apply
+    /// hysteresis.
+    pub fn evaluate(&self, memory: &M, current: usize) -> usize {
+        let importance = self.importance.score(memory);
+        let mut level = current.min(self.thresholds.len());
+        while level < self.thresholds.len()
+            && importance >= self.thresholds[level] + self.hysteresis
+        {
+            level += 1;
+        }
+        while level > 0 && importance < self.thresholds[level - 1] - self.hysteresis {
+            level -= 1;
+        }
+        level
+    }
+
+    /// Computes level of details and writes it into memory.
+    ///
+    /// Returns `true` if level has changed.
+    pub fn update_level(&mut self, memory: &mut LodMemory<M>) -> bool {
+        let level = self.evaluate(&memory.memory, memory.lod_level);
+        if level != memory.lod_level {
+            memory.lod_level = level;
+            self.tick = 0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl<M> Task<LodMemory<M>> for LodController<M> {
+    fn is_locked(&self, memory: &LodMemory<M>) -> bool {
+        self.task.is_locked(memory)
+    }
+
+    fn on_enter(&mut self, memory: &mut LodMemory<M>) {
+        self.update_level(memory);
+        self.tick = 0;
+        self.task.on_enter(memory);
+    }
+
+    fn on_exit(&mut self, memory: &mut LodMemory<M>) {
+        self.task.on_exit(memory);
+    }
+
+    fn on_stop(&mut self, memory: &mut LodMemory<M>, reason: TaskStopReason) {
+        self.task.on_stop(memory, reason);
+    }
+
+    fn on_update(&mut self, memory: &mut LodMemory<M>) {
+        if self.is_due(memory.lod_level) {
+            self.task.on_update(memory);
+        }
+        self.tick = self.tick.wrapping_add(1);
+    }
+
+    fn on_process(&mut self, memory: &mut LodMemory<M>) -> bool {
+        if self.update_level(memory) || self.is_due(memory.lod_level) {
+            self.task.on_process(memory)
+        } else {
+            false
+        }
+    }
+}
+
+impl<M> std::fmt::Debug for LodController<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LodController")
+            .field("thresholds", &self.thresholds)
+            .field("hysteresis", &self.hysteresis)
+            .field("frequencies", &self.frequencies)
+            .field("tick", &self.tick)
+            .finish()
+    }
+}
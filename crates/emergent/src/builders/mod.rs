@@ -1,4 +1,5 @@
 //! Helpers used to simplify building commonly used AI techniques.
 
 pub mod behavior_tree;
+pub mod behavior_tree_dsl;
 pub mod lod;
@@ -5,6 +5,7 @@
 //! See [`crate::combinators`] for more info about combinators (operations on sets of conditions).
 
 use crate::{Scalar, consideration::*};
+use std::sync::Arc;
 
 /// Condition represent the simplest question about the state of the world via provided memory.
 ///
@@ -36,12 +37,99 @@ use crate::{Scalar, consideration::*};
 pub trait Condition<M = ()>: Send + Sync {
     /// Tells if given condition is met based on the state of the memory provided.
     fn validate(&self, memory: &M) -> bool;
+
+    /// Returns name that identifies this condition, if it has one.
+    ///
+    /// Used to write behavior trees back to text (see [`crate::builders::behavior_tree_dsl`]).
+    fn condition_name(&self) -> Option<&str> {
+        None
+    }
 }
 
 impl<M> Condition<M> for bool {
     fn validate(&self, _: &M) -> bool {
         *self
     }
+
+    fn condition_name(&self) -> Option<&str> {
+        Some(if *self { "true" } else { "false" })
+    }
+}
+
+/// Shared conditions are useful when the same condition has to be checked by multiple owners,
+/// e.g. by both node condition and its task in behavior tree (see [`crate::task::GuardTask`]).
+impl<M, C> Condition<M> for Arc<C>
+where
+    C: Condition<M> + ?Sized,
+{
+    fn validate(&self, memory: &M) -> bool {
+        self.as_ref().validate(memory)
+    }
+
+    fn condition_name(&self) -> Option<&str> {
+        self.as_ref().condition_name()
+    }
+}
+
+/// Condition that wraps another condition and gives it a name.
+///
+/// # Example
+/// ```
+/// use emergent::prelude::*;
+///
+/// let condition = NamedCondition::new("always", true);
+/// assert!(condition.validate(&()));
+/// assert_eq!(condition.condition_name(), Some("always"));
+/// ```
+pub struct NamedCondition<M = ()> {
+    name: String,
+    condition: Box<dyn Condition<M>>,
+}
+
+impl<M> NamedCondition<M> {
+    /// Constructs new named condition.
+    pub fn new<C>(name: impl ToString, condition: C) -> Self
+    where
+        C: Condition<M> + 'static,
+    {
+        Self::new_raw(name, Box::new(condition))
+    }
+
+    /// Constructs new named condition from raw condition.
+    pub fn new_raw(name: impl ToString, condition: Box<dyn Condition<M>>) -> Self {
+        Self {
+            name: name.to_string(),
+            condition,
+        }
+    }
+
+    /// Returns name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns wrapped condition.
+    pub fn condition(&self) -> &dyn Condition<M> {
+        self.condition.as_ref()
+    }
+}
+
+impl<M> Condition<M> for NamedCondition<M> {
+    fn validate(&self, memory: &M) -> bool {
+        self.condition.validate(memory)
+    }
+
+    fn condition_name(&self) -> Option<&str> {
+        Some(&self.name)
+    }
+}
+
+impl<M> std::fmt::Debug for NamedCondition<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NamedCondition")
+            .field("name", &self.name)
+            .finish()
+    }
 }
 
 /// Condition that wraps a closure.
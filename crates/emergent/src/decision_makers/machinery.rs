@@ -1,6 +1,8 @@
 //! Machinery (a.k.a. Finite State Machine) decision maker.
 
-use crate::{DefaultKey, condition::*, decision_makers::*, task::*};
+use crate::{
+    DefaultKey, Scalar, condition::*, consideration::*, decision_makers::*, random::Random, task::*,
+};
 use std::{collections::HashMap, hash::Hash};
 
 /// Machinery error.
@@ -88,10 +90,110 @@ where
     }
 }
 
+/// Defines how machinery state picks change to perform.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum MachineryTransitionMode {
+    /// Performs first change with valid condition.
+    #[default]
+    FirstMatch,
+    /// Rolls one of changes with valid condition, with probability proportional to its weight
+    /// (see [`MachineryState::weight`] and [`MachineryTransitionTable`]).
+    Random,
+}
+
+/// Table of transition weights between states, tunable by designers.
+///
+/// Weight from table multiplies weight of the change scored by its state (see
+/// [`MachineryState::score`]). Changes without entry in the table have weight multiplier of 1.
+///
+/// # Example
+/// ```
+/// use emergent::prelude::*;
+///
+/// let table = MachineryTransitionTable::default()
+///     .weight("idle", "graze", 3.0)
+///     .weight("idle", "look_around", 1.0);
+/// assert_eq!(table.get(&"idle", &"graze"), Some(3.0));
+/// assert_eq!(table.get(&"graze", &"idle"), None);
+/// ```
+pub struct MachineryTransitionTable<K = DefaultKey>(HashMap<K, HashMap<K, Scalar>>)
+where
+    K: Clone + Hash + Eq;
+
+impl<K> Default for MachineryTransitionTable<K>
+where
+    K: Clone + Hash + Eq,
+{
+    fn default() -> Self {
+        Self(Default::default())
+    }
+}
+
+impl<K> Clone for MachineryTransitionTable<K>
+where
+    K: Clone + Hash + Eq,
+{
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<K> MachineryTransitionTable<K>
+where
+    K: Clone + Hash + Eq,
+{
+    /// Sets weight of transition between states.
+    pub fn weight(mut self, from: K, to: K, weight: Scalar) -> Self {
+        self.set(from, to, weight);
+        self
+    }
+
+    /// Sets weight of transition between states.
+    pub fn set(&mut self, from: K, to: K, weight: Scalar) {
+        self.0.entry(from).or_default().insert(to, weight);
+    }
+
+    /// Removes weight of transition between states.
+    pub fn remove(&mut self, from: &K, to: &K) -> Option<Scalar> {
+        let row = self.0.get_mut(from)?;
+        let result = row.remove(to);
+        if row.is_empty() {
+            self.0.remove(from);
+        }
+        result
+    }
+
+    /// Returns weight of transition between states.
+    pub fn get(&self, from: &K, to: &K) -> Option<Scalar> {
+        self.0.get(from)?.get(to).copied()
+    }
+
+    /// Returns weights of transitions from given state.
+    pub fn row(&self, from: &K) -> impl Iterator<Item = (&K, Scalar)> {
+        self.0
+            .get(from)
+            .into_iter()
+            .flat_map(|row| row.iter().map(|(to, weight)| (to, *weight)))
+    }
+}
+
+impl<K> std::fmt::Debug for MachineryTransitionTable<K>
+where
+    K: Clone + Hash + Eq + std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("MachineryTransitionTable")
+            .field(&self.0)
+            .finish()
+    }
+}
+
 /// Defines machinery state with task to run and changes that can happen for this state.
 pub struct MachineryState<M = (), K = DefaultKey> {
     task: Box<dyn Task<M>>,
     changes: Vec<MachineryChange<M, K>>,
+    weights: Vec<(K, Box<dyn Consideration<M>>)>,
+    transition_mode: MachineryTransitionMode,
 }
 
 impl<M, K> MachineryState<M, K> {
@@ -100,18 +202,12 @@ impl<M, K> MachineryState<M, K> {
     where
         T: Task<M> + 'static,
     {
-        Self {
-            task: Box::new(task),
-            changes: vec![],
-        }
+        Self::task_raw(Box::new(task))
     }
 
     /// Construct new state with task only.
     pub fn task_raw(task: Box<dyn Task<M>>) -> Self {
-        Self {
-            task,
-            changes: vec![],
-        }
+        Self::new_raw(task, vec![])
     }
 
     /// Constructs new state with task and list of changes.
@@ -119,15 +215,17 @@ impl<M, K> MachineryState<M, K> {
     where
         T: Task<M> + 'static,
     {
-        Self {
-            task: Box::new(task),
-            changes,
-        }
+        Self::new_raw(Box::new(task), changes)
     }
 
     /// Constructs new state with task and list of changes.
     pub fn new_raw(task: Box<dyn Task<M>>, changes: Vec<MachineryChange<M, K>>) -> Self {
-        Self { task, changes }
+        Self {
+            task,
+            changes,
+            weights: vec![],
+            transition_mode: Default::default(),
+        }
     }
 
     /// Add state change.
@@ -135,6 +233,94 @@ impl<M, K> MachineryState<M, K> {
         self.changes.push(change);
         self
     }
+
+    /// Sets weight of changes to given state, used when this state picks changes randomly
+    /// (see [`MachineryTransitionMode::Random`]).
+    pub fn weight<C>(self, to: K, consideration: C) -> Self
+    where
+        C: Consideration<M> + 'static,
+        K: PartialEq,
+    {
+        self.weight_raw(to, Box::new(consideration))
+    }
+
+    /// Sets weight of changes to given state, used when this state picks changes randomly
+    /// (see [`MachineryTransitionMode::Random`]).
+    pub fn weight_raw(mut self, to: K, consideration: Box<dyn Consideration<M>>) -> Self
+    where
+        K: PartialEq,
+    {
+        self.weights.retain(|(id, _)| id != &to);
+        self.weights.push((to, consideration));
+        self
+    }
+
+    /// Scores weight of changes to given state (defaults to 1).
+    pub fn score(&self, to: &K, memory: &M) -> Scalar
+    where
+        K: PartialEq,
+    {
+        self.weights
+            .iter()
+            .find(|(id, _)| id == to)
+            .map(|(_, weight)| weight.score(memory))
+            .unwrap_or(1.0)
+    }
+
+    /// Sets how this state picks change to perform.
+    pub fn transition_mode(mut self, mode: MachineryTransitionMode) -> Self {
+        self.transition_mode = mode;
+        self
+    }
+
+    /// Returns how this state picks change to perform.
+    pub fn get_transition_mode(&self) -> MachineryTransitionMode {
+        self.transition_mode
+    }
+
+    /// Returns probabilities of changes to happen from this state in given memory state.
+    ///
+    /// Probabilities of changes to the same state are summed up. Empty list means no change will
+    /// happen.
+    pub fn transition_probabilities(
+        &self,
+        id: &K,
+        memory: &M,
+        table: &MachineryTransitionTable<K>,
+    ) -> Vec<(K, Scalar)>
+    where
+        K: Clone + Hash + Eq,
+    {
+        let mut result = Vec::<(K, Scalar)>::new();
+        match self.transition_mode {
+            MachineryTransitionMode::FirstMatch => {
+                if let Some(change) = self.changes.iter().find(|c| c.validate(memory)) {
+                    result.push((change.to.clone(), 1.0));
+                }
+            }
+            MachineryTransitionMode::Random => {
+                for change in &self.changes {
+                    if !change.validate(memory) {
+                        continue;
+                    }
+                    let weight =
+                        self.score(&change.to, memory) * table.get(id, &change.to).unwrap_or(1.0);
+                    if weight <= 0.0 {
+                        continue;
+                    }
+                    match result.iter_mut().find(|(to, _)| to == &change.to) {
+                        Some((_, total)) => *total += weight,
+                        None => result.push((change.to.clone(), weight)),
+                    }
+                }
+                let total = result.iter().map(|(_, weight)| *weight).sum::<Scalar>();
+                for (_, weight) in &mut result {
+                    *weight /= total;
+                }
+            }
+        }
+        result
+    }
 }
 
 impl<M, K> std::fmt::Debug for MachineryState<M, K>
@@ -144,6 +330,7 @@ where
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("MachineryState")
             .field("changes", &self.changes)
+            .field("transition_mode", &self.transition_mode)
             .finish()
     }
 }
@@ -239,6 +426,41 @@ where
 /// assert!(machinery.process(&mut memory));
 /// assert_eq!(machinery.active_state(), Some(&Action::None));
 /// ```
+///
+/// # Probabilistic transitions
+/// States with [`MachineryTransitionMode::Random`] roll one of changes with valid condition, with
+/// probability proportional to change weight (scored by [`Consideration`] set with
+/// [`MachineryState::weight`]) multiplied by weight from [`MachineryTransitionTable`]. Rolls are seeded, so runs are reproducible - useful for
+/// ambient behaviors like idle animations or wildlife.
+///
+/// ```
+/// use emergent::prelude::*;
+///
+/// let wander = |to: &'static str| MachineryChange::new(to, true);
+/// let mut machinery = MachineryBuilder::default()
+///     .state(
+///         "idle",
+///         MachineryState::task(NoTask)
+///             .change(wander("graze"))
+///             .change(wander("look_around"))
+///             .transition_mode(MachineryTransitionMode::Random),
+///     )
+///     .state("graze", MachineryState::task(NoTask).change(wander("idle")))
+///     .state("look_around", MachineryState::task(NoTask).change(wander("idle")))
+///     .build()
+///     .transition_table(MachineryTransitionTable::default().weight("idle", "graze", 3.0))
+///     .seed(42);
+///
+/// machinery.change_active_state(Some("idle"), &mut (), true);
+/// assert!(machinery.process(&mut ()));
+/// assert_ne!(machinery.active_state(), Some(&"idle"));
+///
+/// // idle half of the time, grazing three times more often than looking around.
+/// let distribution = machinery.estimate_stationary_distribution(&(), 100);
+/// assert!((distribution["idle"] - 0.5).abs() < 1.0e-3);
+/// assert!((distribution["graze"] - 0.375).abs() < 1.0e-3);
+/// assert!((distribution["look_around"] - 0.125).abs() < 1.0e-3);
+/// ```
 pub struct Machinery<M = (), K = DefaultKey>
 where
     K: Clone + Hash + Eq,
@@ -246,6 +468,8 @@ where
     states: HashMap<K, MachineryState<M, K>>,
     active_state: Option<K>,
     initial_state_decision_maker: Option<Box<dyn DecisionMaker<M, K>>>,
+    transition_table: MachineryTransitionTable<K>,
+    random: Random,
 }
 
 impl<M, K> Machinery<M, K>
@@ -258,9 +482,33 @@ where
             states,
             active_state: None,
             initial_state_decision_maker: None,
+            transition_table: Default::default(),
+            random: Default::default(),
         }
     }
 
+    /// Sets table of transition weights used by states that pick changes randomly.
+    pub fn transition_table(mut self, table: MachineryTransitionTable<K>) -> Self {
+        self.transition_table = table;
+        self
+    }
+
+    /// Sets seed of random rolls performed by states that pick changes randomly.
+    pub fn seed(mut self, value: u64) -> Self {
+        self.random = Random::new(value);
+        self
+    }
+
+    /// Returns table of transition weights.
+    pub fn get_transition_table(&self) -> &MachineryTransitionTable<K> {
+        &self.transition_table
+    }
+
+    /// Returns mutable table of transition weights, so they can be tuned at runtime.
+    pub fn get_transition_table_mut(&mut self) -> &mut MachineryTransitionTable<K> {
+        &mut self.transition_table
+    }
+
     /// Assigns decision maker that will set initial state when machinery gets activated.
     ///
     /// This is useful when we want to use machinery in hierarchy.
@@ -332,19 +580,26 @@ where
     /// Performs decision making.
     pub fn process(&mut self, memory: &mut M) -> bool {
         if let Some(id) = &self.active_state
-            && let Some(state) = self.states.get_mut(id)
+            && let Some(state) = self.states.get(id)
         {
-            let id = state
-                .changes
-                .iter()
-                .find_map(|c| {
-                    if c.validate(memory) {
-                        Some(&c.to)
-                    } else {
-                        None
-                    }
-                })
-                .cloned();
+            let id = match state.transition_mode {
+                MachineryTransitionMode::FirstMatch => state
+                    .changes
+                    .iter()
+                    .find_map(|c| {
+                        if c.validate(memory) {
+                            Some(&c.to)
+                        } else {
+                            None
+                        }
+                    })
+                    .cloned(),
+                MachineryTransitionMode::Random => {
+                    let probabilities =
+                        state.transition_probabilities(id, memory, &self.transition_table);
+                    Self::roll(&probabilities, &mut self.random)
+                }
+            };
             if let Some(id) = id
                 && let Ok(true) = self.change_active_state(Some(id), memory, false)
             {
@@ -357,6 +612,78 @@ where
         false
     }
 
+    fn roll(probabilities: &[(K, Scalar)], random: &mut Random) -> Option<K> {
+        if probabilities.is_empty() {
+            return None;
+        }
+        let mut value = random.next_scalar();
+        for (id, probability) in probabilities {
+            if value < *probability {
+                return Some(id.clone());
+            }
+            value -= probability;
+        }
+        probabilities.last().map(|(id, _)| id.clone())
+    }
+
+    /// Estimates stationary distribution of states - long-run fraction of time machinery spends in
+    /// each state, assuming memory does not change.
+    ///
+    /// Only conditions and weights are evaluated, tasks are not run. States without valid changes
+    /// stay where they are. Estimation is performed by given number of iterations of lazy Markov
+    /// chain (which converges also for periodic chains), starting from active state, or from
+    /// uniform distribution if there is no active state.
+    pub fn estimate_stationary_distribution(
+        &self,
+        memory: &M,
+        iterations: usize,
+    ) -> HashMap<K, Scalar> {
+        let ids = self.states.keys().cloned().collect::<Vec<_>>();
+        if ids.is_empty() {
+            return Default::default();
+        }
+        let indices = ids
+            .iter()
+            .enumerate()
+            .map(|(index, id)| (id.clone(), index))
+            .collect::<HashMap<_, _>>();
+        let transitions = ids
+            .iter()
+            .map(|id| {
+                self.states[id]
+                    .transition_probabilities(id, memory, &self.transition_table)
+                    .into_iter()
+                    .filter_map(|(to, probability)| Some((*indices.get(&to)?, probability)))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let mut distribution = match self.active_state.as_ref().and_then(|id| indices.get(id)) {
+            Some(index) => {
+                let mut result = vec![0.0; ids.len()];
+                result[*index] = 1.0;
+                result
+            }
+            None => vec![1.0 / ids.len() as Scalar; ids.len()],
+        };
+        for _ in 0..iterations {
+            let mut next = vec![0.0; ids.len()];
+            for (from, probability) in distribution.iter().enumerate() {
+                let half = probability * 0.5;
+                next[from] += half;
+                let moved = transitions[from]
+                    .iter()
+                    .map(|(to, weight)| {
+                        next[*to] += half * weight;
+                        *weight
+                    })
+                    .sum::<Scalar>();
+                next[from] += half * (1.0 - moved);
+            }
+            distribution = next;
+        }
+        ids.into_iter().zip(distribution).collect()
+    }
+
     /// Updates active state.
     pub fn update(&mut self, memory: &mut M) {
         if let Some(id) = &self.active_state {
@@ -421,6 +748,7 @@ where
         f.debug_struct("Machinery")
             .field("states", &self.states)
             .field("active_state", &self.active_state)
+            .field("transition_table", &self.transition_table)
             .finish()
     }
 }
@@ -0,0 +1,536 @@
+//! Monte Carlo Tree Search decision maker.
+
+use crate::{DefaultKey, Scalar, compare_scores, decision_makers::*, random::Random, task::*};
+use std::{collections::HashMap, hash::Hash, time::Duration};
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+/// MCTS error.
+pub enum MctsError<K = DefaultKey> {
+    /// There is no state with given ID found in MCTS.
+    StateDoesNotExists(K),
+}
+
+impl<K> Clone for MctsError<K>
+where
+    K: Clone,
+{
+    fn clone(&self) -> Self {
+        match self {
+            Self::StateDoesNotExists(key) => Self::StateDoesNotExists(key.clone()),
+        }
+    }
+}
+
+impl<K> PartialEq for MctsError<K>
+where
+    K: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::StateDoesNotExists(a), Self::StateDoesNotExists(b)) => a == b,
+        }
+    }
+}
+
+impl<K> Eq for MctsError<K> where K: Eq {}
+
+impl<K> std::fmt::Debug for MctsError<K>
+where
+    K: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::StateDoesNotExists(key) => {
+                write!(f, "StateDoesNotExists({:?})", key)
+            }
+        }
+    }
+}
+
+/// Simulation model used by [`Mcts`] to look ahead.
+///
+/// Model operates on copies of the memory, so simulation never affects actual memory.
+pub trait MctsModel<M = (), K = DefaultKey>: Send + Sync {
+    /// Returns list of actions that can be performed in given memory state.
+    fn actions(&self, memory: &M) -> Vec<K>;
+
+    /// Applies action to simulated memory.
+    fn apply(&self, memory: &mut M, action: &K);
+
+    /// Tells if memory state ends the simulation.
+    ///
+    /// By default simulation ends when there are no actions to perform.
+    fn is_terminal(&self, memory: &M) -> bool {
+        self.actions(memory).is_empty()
+    }
+
+    /// Scores memory state from the point of view of the agent that makes decision.
+    ///
+    /// Rewards are best kept in `[0; 1]` range, so default exploration constant works well.
+    fn reward(&self, memory: &M) -> Scalar;
+
+    /// Tells if it's opponent who chooses next action in given memory state.
+    ///
+    /// Opponent is assumed to choose actions that minimize agent reward.
+    #[allow(unused_variables)]
+    fn is_opponent_turn(&self, memory: &M) -> bool {
+        false
+    }
+}
+
+/// Statistics of single root action gathered during last search.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MctsActionStatistics<K = DefaultKey> {
+    /// Root action ID.
+    pub action: K,
+    /// Number of simulations that started with this action.
+    pub visits: usize,
+    /// Average reward of simulations that started with this action.
+    pub value: Scalar,
+}
+
+struct MctsNode<K> {
+    action: Option<K>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    untried: Vec<K>,
+    opponent_turn: bool,
+    visits: usize,
+    value: Scalar,
+}
+
+/// Monte Carlo Tree Search agent.
+///
+/// MCTS contains states (tasks) assigned to actions of [`MctsModel`]. During decision making it
+/// runs simulations on copies of memory, building tree of possible futures (also including
+/// opponent responses) and balancing between exploration of new and exploitation of promising
+/// actions using UCT formula. After search, state of the most visited root action gets activated.
+///
+/// Search is limited by number of iterations and optionally by time budget. Random choices are
+/// seeded, so decisions are reproducible.
+///
+/// # Example
+/// ```
+/// use emergent::prelude::*;
+///
+/// // Nim game: players take 1 or 2 sticks in turns and one who takes the last stick wins.
+/// #[derive(Clone)]
+/// struct Memory {
+///     sticks: usize,
+///     my_turn: bool,
+/// }
+///
+/// struct Nim;
+///
+/// impl MctsModel<Memory, usize> for Nim {
+///     fn actions(&self, memory: &Memory) -> Vec<usize> {
+///         (1..=memory.sticks.min(2)).collect()
+///     }
+///
+///     fn apply(&self, memory: &mut Memory, action: &usize) {
+///         memory.sticks -= action;
+///         memory.my_turn = !memory.my_turn;
+///     }
+///
+///     fn reward(&self, memory: &Memory) -> Scalar {
+///         // when there are no sticks left, player who took the last one just passed the turn.
+///         if memory.my_turn { 0.0 } else { 1.0 }
+///     }
+///
+///     fn is_opponent_turn(&self, memory: &Memory) -> bool {
+///         !memory.my_turn
+///     }
+/// }
+///
+/// let take = |count| ClosureTask::default().enter(move |m: &mut Memory| m.sticks -= count);
+/// let mut mcts = Mcts::new(Nim)
+///     .state(1, take(1))
+///     .state(2, take(2))
+///     .iterations(500)
+///     .seed(42);
+///
+/// // taking 1 stick out of 4 leaves opponent with losing position.
+/// let mut memory = Memory { sticks: 4, my_turn: true };
+/// assert!(mcts.process(&mut memory));
+/// assert_eq!(mcts.active_state(), Some(&1));
+/// assert_eq!(memory.sticks, 3);
+/// ```
+pub struct Mcts<M = (), K = DefaultKey>
+where
+    K: Clone + Hash + Eq,
+{
+    model: Box<dyn MctsModel<M, K>>,
+    states: HashMap<K, Box<dyn Task<M>>>,
+    active_state: Option<K>,
+    iterations: usize,
+    time_limit: Option<Duration>,
+    exploration: Scalar,
+    max_depth: usize,
+    random: Random,
+    statistics: Vec<MctsActionStatistics<K>>,
+}
+
+impl<M, K> Mcts<M, K>
+where
+    K: Clone + Hash + Eq,
+{
+    /// Constructs new MCTS with simulation model.
+    pub fn new<T>(model: T) -> Self
+    where
+        T: MctsModel<M, K> + 'static,
+    {
+        Self::new_raw(Box::new(model))
+    }
+
+    /// Constructs new MCTS with raw simulation model.
+    pub fn new_raw(model: Box<dyn MctsModel<M, K>>) -> Self {
+        Self {
+            model,
+            states: Default::default(),
+            active_state: None,
+            iterations: 1000,
+            time_limit: None,
+            exploration: std::f64::consts::SQRT_2 as Scalar,
+            max_depth: 100,
+            random: Default::default(),
+            statistics: vec![],
+        }
+    }
+
+    /// Adds state that gets activated when given action is selected.
+    pub fn state<T>(mut self, id: K, task: T) -> Self
+    where
+        T: Task<M> + 'static,
+    {
+        self.states.insert(id, Box::new(task));
+        self
+    }
+
+    /// Adds state that gets activated when given action is selected.
+    pub fn state_raw(mut self, id: K, task: Box<dyn Task<M>>) -> Self {
+        self.states.insert(id, task);
+        self
+    }
+
+    /// Sets maximum number of simulations performed per search (at least one is always performed).
+    pub fn iterations(mut self, value: usize) -> Self {
+        self.iterations = value.max(1);
+        self
+    }
+
+    /// Sets time budget of single search.
+    ///
+    /// Not supported on `wasm32` targets, where only iterations budget applies.
+    pub fn time_limit(mut self, value: Duration) -> Self {
+        self.time_limit = Some(value);
+        self
+    }
+
+    /// Sets UCT exploration constant (defaults to `sqrt(2)`).
+    pub fn exploration(mut self, value: Scalar) -> Self {
+        self.exploration = value;
+        self
+    }
+
+    /// Sets maximum number of actions performed in single simulation.
+    pub fn max_depth(mut self, value: usize) -> Self {
+        self.max_depth = value;
+        self
+    }
+
+    /// Sets seed of random choices.
+    pub fn seed(mut self, value: u64) -> Self {
+        self.random = Random::new(value);
+        self
+    }
+
+    /// Returns iterations budget.
+    pub fn get_iterations(&self) -> usize {
+        self.iterations
+    }
+
+    /// Returns time budget.
+    pub fn get_time_limit(&self) -> Option<Duration> {
+        self.time_limit
+    }
+
+    /// Returns UCT exploration constant.
+    pub fn get_exploration(&self) -> Scalar {
+        self.exploration
+    }
+
+    /// Returns maximum simulation depth.
+    pub fn get_max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Returns currently active state ID.
+    pub fn active_state(&self) -> Option<&K> {
+        self.active_state.as_ref()
+    }
+
+    /// Returns statistics of root actions gathered during last search.
+    pub fn statistics(&self) -> &[MctsActionStatistics<K>] {
+        &self.statistics
+    }
+
+    /// Change active state.
+    ///
+    /// If currently active state is locked then state change will fail, unless we force it to change.
+    pub fn change_active_state(
+        &mut self,
+        id: Option<K>,
+        memory: &mut M,
+        forced: bool,
+    ) -> Result<bool, MctsError<K>> {
+        if id == self.active_state {
+            return Ok(false);
+        }
+        if let Some(id) = &id
+            && !self.states.contains_key(id)
+        {
+            return Err(MctsError::StateDoesNotExists(id.clone()));
+        }
+        if let Some(id) = &self.active_state {
+            let task = self.states.get_mut(id).unwrap();
+            if !forced && task.is_locked(memory) {
+                return Ok(false);
+            }
+            task.on_stop(memory, TaskStopReason::Replaced);
+        }
+        if let Some(id) = &id {
+            self.states.get_mut(id).unwrap().on_enter(memory);
+        }
+        self.active_state = id;
+        Ok(true)
+    }
+
+    /// Searches for the best action in given memory state, without changing active state.
+    ///
+    /// Only actions that have states assigned are considered at the root of search.
+    pub fn search(&mut self, memory: &M) -> Option<K>
+    where
+        M: Clone,
+    {
+        self.statistics.clear();
+        let actions = self
+            .model
+            .actions(memory)
+            .into_iter()
+            .filter(|action| self.states.contains_key(action))
+            .collect::<Vec<_>>();
+        if actions.is_empty() {
+            return None;
+        }
+        let mut nodes = vec![MctsNode {
+            action: None,
+            parent: None,
+            children: vec![],
+            untried: actions,
+            opponent_turn: self.model.is_opponent_turn(memory),
+            visits: 0,
+            value: 0.0,
+        }];
+        #[cfg(not(target_arch = "wasm32"))]
+        let timer = Instant::now();
+        for _ in 0..self.iterations {
+            self.iterate(&mut nodes, memory.clone());
+            #[cfg(not(target_arch = "wasm32"))]
+            if let Some(limit) = self.time_limit
+                && timer.elapsed() >= limit
+            {
+                break;
+            }
+        }
+        self.statistics = nodes[0]
+            .children
+            .iter()
+            .map(|index| {
+                let node = &nodes[*index];
+                MctsActionStatistics {
+                    action: node.action.clone().unwrap(),
+                    visits: node.visits,
+                    value: node.value / node.visits.max(1) as Scalar,
+                }
+            })
+            .collect();
+        self.statistics
+            .iter()
+            .max_by(|a, b| {
+                a.visits
+                    .cmp(&b.visits)
+                    .then(compare_scores(a.value, b.value))
+            })
+            .map(|statistics| statistics.action.clone())
+    }
+
+    fn iterate(&mut self, nodes: &mut Vec<MctsNode<K>>, mut memory: M) {
+        // selection.
+        let mut index = 0;
+        while nodes[index].untried.is_empty() && !nodes[index].children.is_empty() {
+            index = self.select_child(nodes, index);
+            self.model
+                .apply(&mut memory, nodes[index].action.as_ref().unwrap());
+        }
+        // expansion.
+        if !nodes[index].untried.is_empty() {
+            let untried = &mut nodes[index].untried;
+            let action = untried.swap_remove(self.random.next_index(untried.len()));
+            self.model.apply(&mut memory, &action);
+            let untried = if self.model.is_terminal(&memory) {
+                vec![]
+            } else {
+                self.model.actions(&memory)
+            };
+            let child = nodes.len();
+            nodes.push(MctsNode {
+                action: Some(action),
+                parent: Some(index),
+                children: vec![],
+                untried,
+                opponent_turn: self.model.is_opponent_turn(&memory),
+                visits: 0,
+                value: 0.0,
+            });
+            nodes[index].children.push(child);
+            index = child;
+        }
+        // simulation.
+        for _ in 0..self.max_depth {
+            if self.model.is_terminal(&memory) {
+                break;
+            }
+            let actions = self.model.actions(&memory);
+            if actions.is_empty() {
+                break;
+            }
+            let action = &actions[self.random.next_index(actions.len())];
+            self.model.apply(&mut memory, action);
+        }
+        // backpropagation.
+        let reward = self.model.reward(&memory);
+        let mut current = Some(index);
+        while let Some(index) = current {
+            let node = &mut nodes[index];
+            node.visits += 1;
+            node.value += reward;
+            current = node.parent;
+        }
+    }
+
+    fn select_child(&self, nodes: &[MctsNode<K>], index: usize) -> usize {
+        let parent = &nodes[index];
+        let log_visits = (parent.visits.max(1) as Scalar).ln();
+        parent
+            .children
+            .iter()
+            .map(|child| {
+                let node = &nodes[*child];
+                let visits = node.visits.max(1) as Scalar;
+                let mut value = node.value / visits;
+                if parent.opponent_turn {
+                    value = -value;
+                }
+                (
+                    *child,
+                    value + self.exploration * (log_visits / visits).sqrt(),
+                )
+            })
+            .max_by(|(_, a), (_, b)| compare_scores(*a, *b))
+            .map(|(child, _)| child)
+            .unwrap()
+    }
+
+    /// Performs decision making.
+    pub fn process(&mut self, memory: &mut M) -> bool
+    where
+        M: Clone,
+    {
+        if let Some(id) = &self.active_state
+            && self.states.get(id).unwrap().is_locked(memory)
+        {
+            return self.states.get_mut(id).unwrap().on_process(memory);
+        }
+        if let Some(id) = self.search(memory)
+            && let Ok(true) = self.change_active_state(Some(id), memory, false)
+        {
+            return true;
+        }
+        if let Some(id) = &self.active_state {
+            return self.states.get_mut(id).unwrap().on_process(memory);
+        }
+        false
+    }
+
+    /// Update currently active state.
+    pub fn update(&mut self, memory: &mut M) {
+        if let Some(id) = &self.active_state {
+            self.states.get_mut(id).unwrap().on_update(memory);
+        }
+    }
+}
+
+impl<M, K> DecisionMaker<M, K> for Mcts<M, K>
+where
+    M: Clone,
+    K: Clone + Hash + Eq + Send + Sync,
+{
+    fn decide(&mut self, memory: &mut M) -> Option<K> {
+        self.process(memory);
+        self.active_state().cloned()
+    }
+
+    fn change_mind(&mut self, id: Option<K>, memory: &mut M) -> bool {
+        matches!(self.change_active_state(id, memory, true), Ok(true))
+    }
+}
+
+impl<M, K> Task<M> for Mcts<M, K>
+where
+    M: Clone,
+    K: Clone + Hash + Eq + Send + Sync,
+{
+    fn is_locked(&self, memory: &M) -> bool {
+        if let Some(id) = &self.active_state
+            && let Some(task) = self.states.get(id)
+        {
+            return task.is_locked(memory);
+        }
+        false
+    }
+
+    fn on_enter(&mut self, memory: &mut M) {
+        let _ = self.change_active_state(None, memory, true);
+        self.process(memory);
+    }
+
+    fn on_exit(&mut self, memory: &mut M) {
+        let _ = self.change_active_state(None, memory, true);
+    }
+
+    fn on_update(&mut self, memory: &mut M) {
+        self.update(memory);
+    }
+
+    fn on_process(&mut self, memory: &mut M) -> bool {
+        self.process(memory)
+    }
+}
+
+impl<M, K> std::fmt::Debug for Mcts<M, K>
+where
+    K: Clone + Hash + Eq + std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Mcts")
+            .field("states", &self.states.keys().collect::<Vec<_>>())
+            .field("active_state", &self.active_state)
+            .field("iterations", &self.iterations)
+            .field("time_limit", &self.time_limit)
+            .field("exploration", &self.exploration)
+            .field("max_depth", &self.max_depth)
+            .finish()
+    }
+}
@@ -10,6 +10,8 @@
 //! - [`Planner`](struct@self::planner::Planner) - Goal Oriented Action Planning agent (finds the
 //!   best path through all possible actions for goal selected by another decision maker assigned
 //!   into this planner).
+//! - [`Mcts`](struct@self::mcts::Mcts) - Monte Carlo Tree Search agent (simulates possible futures,
+//!   including responses of opponents, and selects action that leads to the best outcome).
 //! - [`Sequencer`](struct@self::sequencer::Sequencer) - Goes through states (ones that are possible
 //!   to run) in a sequence.
 //! - [`Selector`](struct@self::selector::Selector) - Selects only one state from list of possible
@@ -69,6 +71,7 @@
 //!     - Eat
 
 pub mod machinery;
+pub mod mcts;
 pub mod parallelizer;
 pub mod planner;
 pub mod reasoner;
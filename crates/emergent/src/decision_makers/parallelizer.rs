@@ -33,12 +33,168 @@ impl<M> std::fmt::Debug for ParallelizerState<M> {
     }
 }
 
+/// Tells when parallelizer succeeds.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum ParallelizerSuccessPolicy {
+    /// Parallelizer never succeeds because of completed states.
+    #[default]
+    Never,
+    /// Parallelizer succeeds when any state completes.
+    RequireOne,
+    /// Parallelizer succeeds when all states complete.
+    RequireAll,
+}
+
+/// Tells when parallelizer fails.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum ParallelizerFailurePolicy {
+    /// Parallelizer never fails because of failed states.
+    #[default]
+    Never,
+    /// Parallelizer fails when any state fails.
+    FailOnFirst,
+    /// Parallelizer fails when all states fail.
+    FailOnAll,
+}
+
+/// Parallelizer completion policy.
+///
+/// Default policy never resolves, which means parallelizer keeps running its states again and
+/// again for as long as their conditions are met. Any other policy makes parallelizer run each
+/// state once: state completes when its task gets unlocked and fails when its condition is not met
+/// (either when it's about to start or while it runs). Failure policy is checked before success
+/// policy, and when all states are done without resolving any of policies, parallelizer succeeds
+/// only if it has no success policy.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct ParallelizerPolicy {
+    /// Tells when parallelizer succeeds.
+    success: ParallelizerSuccessPolicy,
+    /// Tells when parallelizer fails.
+    failure: ParallelizerFailurePolicy,
+    /// Tells if states still running when policy resolves get cancelled, otherwise they are left
+    /// to finish their work.
+    cancel_remaining: bool,
+}
+
+impl ParallelizerPolicy {
+    /// Constructs policy that succeeds when all states complete and fails on first failed state.
+    pub fn require_all() -> Self {
+        Self {
+            success: ParallelizerSuccessPolicy::RequireAll,
+            failure: ParallelizerFailurePolicy::FailOnFirst,
+            cancel_remaining: true,
+        }
+    }
+
+    /// Constructs policy that succeeds when any state completes and fails when all states fail.
+    pub fn require_one() -> Self {
+        Self {
+            success: ParallelizerSuccessPolicy::RequireOne,
+            failure: ParallelizerFailurePolicy::FailOnAll,
+            cancel_remaining: true,
+        }
+    }
+
+    /// Sets success policy.
+    pub fn success(mut self, policy: ParallelizerSuccessPolicy) -> Self {
+        self.success = policy;
+        self
+    }
+
+    /// Sets failure policy.
+    pub fn failure(mut self, policy: ParallelizerFailurePolicy) -> Self {
+        self.failure = policy;
+        self
+    }
+
+    /// Sets if states still running when policy resolves get cancelled.
+    pub fn cancel_remaining(mut self, value: bool) -> Self {
+        self.cancel_remaining = value;
+        self
+    }
+
+    /// Returns success policy.
+    pub fn get_success(&self) -> ParallelizerSuccessPolicy {
+        self.success
+    }
+
+    /// Returns failure policy.
+    pub fn get_failure(&self) -> ParallelizerFailurePolicy {
+        self.failure
+    }
+
+    /// Tells if states still running when policy resolves get cancelled.
+    pub fn get_cancel_remaining(&self) -> bool {
+        self.cancel_remaining
+    }
+
+    /// Tells if this policy can ever resolve.
+    pub fn is_resolving(&self) -> bool {
+        self.success != ParallelizerSuccessPolicy::Never
+            || self.failure != ParallelizerFailurePolicy::Never
+    }
+
+    /// Resolves outcome based on number of all, completed and failed states and number of states
+    /// that are still running or about to run.
+    fn resolve(
+        &self,
+        total: usize,
+        succeeded: usize,
+        failed: usize,
+        pending: usize,
+    ) -> Option<ParallelizerOutcome> {
+        let failure = match self.failure {
+            ParallelizerFailurePolicy::Never => false,
+            ParallelizerFailurePolicy::FailOnFirst => failed > 0,
+            ParallelizerFailurePolicy::FailOnAll => total > 0 && failed == total,
+        };
+        if failure {
+            return Some(ParallelizerOutcome::Failed);
+        }
+        let success = match self.success {
+            ParallelizerSuccessPolicy::Never => false,
+            ParallelizerSuccessPolicy::RequireOne => succeeded > 0,
+            ParallelizerSuccessPolicy::RequireAll => succeeded == total,
+        };
+        if success {
+            return Some(ParallelizerOutcome::Succeeded);
+        }
+        if pending == 0 {
+            if self.success == ParallelizerSuccessPolicy::Never {
+                return Some(ParallelizerOutcome::Succeeded);
+            }
+            return Some(ParallelizerOutcome::Failed);
+        }
+        None
+    }
+}
+
+/// Outcome of parallelizer with resolving policy.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ParallelizerOutcome {
+    /// Success policy got resolved, or all states are done and there is no success policy.
+    Succeeded,
+    /// Failure policy got resolved, or all states are done without resolving success policy.
+    Failed,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum ParallelizerStateStatus {
+    Idle,
+    Running,
+    Succeeded,
+    Failed,
+}
+
 /// Parallelizer runs all its states at the same time.
 ///
 /// Note that at any time you ask it to make a decision it goes through non-active states and tries
 /// to run them so instead of starting all possible states at once, it will ensure that at any time
 /// of decision making all possible states will run.
 ///
+/// Parallelizer can also be given a completion policy (see [`ParallelizerPolicy`]), so it can
+/// succeed or fail based on how its states did, and unlock once policy resolves.
+///
 /// # Example
 /// ```
 /// use emergent::prelude::*;
@@ -58,21 +214,83 @@ impl<M> std::fmt::Debug for ParallelizerState<M> {
 /// assert_eq!(memory.a, true);
 /// assert_eq!(memory.b, true);
 /// ```
+///
+/// Move while aiming, ending when movement finishes:
+/// ```
+/// use emergent::prelude::*;
+///
+/// struct Memory {
+///     distance: usize,
+///     aiming: bool,
+/// }
+///
+/// let mut parallelizer = Parallelizer::new(vec![
+///     ParallelizerState::new(
+///         true,
+///         ClosureTask::default()
+///             .locked(|m: &Memory| m.distance > 0)
+///             .update(|m: &mut Memory| m.distance -= 1),
+///     ),
+///     ParallelizerState::new(
+///         true,
+///         ClosureTask::default()
+///             .locked(|_: &Memory| true)
+///             .enter(|m: &mut Memory| m.aiming = true)
+///             .exit(|m: &mut Memory| m.aiming = false),
+///     ),
+/// ])
+/// .policy(ParallelizerPolicy::require_one());
+///
+/// let mut memory = Memory { distance: 2, aiming: false };
+/// parallelizer.on_enter(&mut memory);
+/// assert!(memory.aiming);
+/// parallelizer.on_update(&mut memory);
+/// parallelizer.on_update(&mut memory);
+/// assert!(!parallelizer.is_locked(&memory));
+/// assert!(parallelizer.process(&mut memory));
+/// assert_eq!(parallelizer.outcome(), Some(ParallelizerOutcome::Succeeded));
+/// assert!(!memory.aiming);
+/// ```
 pub struct Parallelizer<M = ()> {
-    states: Vec<(ParallelizerState<M>, bool)>,
+    states: Vec<(ParallelizerState<M>, ParallelizerStateStatus)>,
+    policy: ParallelizerPolicy,
+    outcome: Option<ParallelizerOutcome>,
 }
 
 impl<M> Parallelizer<M> {
     /// Constructs new parallelizer with states.
     pub fn new(states: Vec<ParallelizerState<M>>) -> Self {
         Self {
-            states: states.into_iter().map(|state| (state, false)).collect(),
+            states: states
+                .into_iter()
+                .map(|state| (state, ParallelizerStateStatus::Idle))
+                .collect(),
+            policy: Default::default(),
+            outcome: None,
         }
     }
 
+    /// Sets completion policy.
+    pub fn policy(mut self, policy: ParallelizerPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Returns completion policy.
+    pub fn get_policy(&self) -> ParallelizerPolicy {
+        self.policy
+    }
+
+    /// Returns outcome of completion policy, if it has resolved.
+    pub fn outcome(&self) -> Option<ParallelizerOutcome> {
+        self.outcome
+    }
+
     /// Tells if any of states is active/running.
     pub fn is_active(&self) -> bool {
-        self.states.iter().any(|(_, active)| *active)
+        self.states
+            .iter()
+            .any(|(_, status)| *status == ParallelizerStateStatus::Running)
     }
 
     /// Stops all active/running states.
@@ -84,43 +302,157 @@ impl<M> Parallelizer<M> {
 
     fn reset_with_reason(&mut self, memory: &mut M, forced: bool, reason: TaskStopReason) -> bool {
         let mut result = false;
-        for (state, active) in &mut self.states {
-            if *active && (forced || !state.task.is_locked(memory)) {
-                state.task.on_stop(memory, reason);
-                *active = false;
-                result = true;
+        for (state, status) in &mut self.states {
+            if *status == ParallelizerStateStatus::Running {
+                if forced || !state.task.is_locked(memory) {
+                    state.task.on_stop(memory, reason);
+                    *status = ParallelizerStateStatus::Idle;
+                    result = true;
+                }
+            } else {
+                *status = ParallelizerStateStatus::Idle;
             }
         }
+        if !self.is_active() {
+            self.outcome = None;
+        }
         result
     }
 
     /// Perform decision making.
     pub fn process(&mut self, memory: &mut M) -> bool {
+        if self.policy.is_resolving() {
+            return self.process_resolving(memory);
+        }
         let mut result = false;
-        for (state, active) in &mut self.states {
-            if *active {
+        for (state, status) in &mut self.states {
+            if *status == ParallelizerStateStatus::Running {
                 if state.task.is_locked(memory) && state.condition.validate(memory) {
                     if state.task.on_process(memory) {
                         result = true;
                     }
                 } else {
                     state.task.on_stop(memory, TaskStopReason::Replaced);
-                    *active = false;
+                    *status = ParallelizerStateStatus::Idle;
                     result = true;
                 }
             } else if state.condition.validate(memory) {
                 state.task.on_enter(memory);
-                *active = true;
+                *status = ParallelizerStateStatus::Running;
                 result = true;
             }
         }
         result
     }
 
+    fn process_resolving(&mut self, memory: &mut M) -> bool {
+        let mut result = false;
+        if self.outcome.is_some() {
+            // states left running after policy resolved only get to finish their work.
+            for (state, status) in &mut self.states {
+                if *status == ParallelizerStateStatus::Running {
+                    if state.task.is_locked(memory) {
+                        if state.task.on_process(memory) {
+                            result = true;
+                        }
+                    } else {
+                        state.task.on_stop(memory, TaskStopReason::Completed);
+                        *status = ParallelizerStateStatus::Succeeded;
+                        result = true;
+                    }
+                }
+            }
+            return result;
+        }
+        for (state, status) in &mut self.states {
+            match status {
+                ParallelizerStateStatus::Idle => {
+                    if state.condition.validate(memory) {
+                        state.task.on_enter(memory);
+                        *status = ParallelizerStateStatus::Running;
+                    } else {
+                        *status = ParallelizerStateStatus::Failed;
+                    }
+                    result = true;
+                }
+                ParallelizerStateStatus::Running => {
+                    if !state.condition.validate(memory) {
+                        state.task.on_stop(memory, TaskStopReason::Cancelled);
+                        *status = ParallelizerStateStatus::Failed;
+                        result = true;
+                    } else if !state.task.is_locked(memory) {
+                        state.task.on_stop(memory, TaskStopReason::Completed);
+                        *status = ParallelizerStateStatus::Succeeded;
+                        result = true;
+                    } else if state.task.on_process(memory) {
+                        result = true;
+                    }
+                }
+                _ => {}
+            }
+        }
+        let outcome = self.resolve(self.states.iter().map(|(_, status)| *status));
+        if outcome.is_some() {
+            self.outcome = outcome;
+            if self.policy.cancel_remaining {
+                for (state, status) in &mut self.states {
+                    if *status == ParallelizerStateStatus::Running {
+                        state.task.on_stop(memory, TaskStopReason::Cancelled);
+                        *status = ParallelizerStateStatus::Idle;
+                    }
+                }
+            }
+            result = true;
+        }
+        result
+    }
+
+    fn resolve(
+        &self,
+        statuses: impl Iterator<Item = ParallelizerStateStatus>,
+    ) -> Option<ParallelizerOutcome> {
+        let mut total = 0;
+        let mut succeeded = 0;
+        let mut failed = 0;
+        let mut pending = 0;
+        for status in statuses {
+            total += 1;
+            match status {
+                ParallelizerStateStatus::Idle | ParallelizerStateStatus::Running => pending += 1,
+                ParallelizerStateStatus::Succeeded => succeeded += 1,
+                ParallelizerStateStatus::Failed => failed += 1,
+            }
+        }
+        self.policy.resolve(total, succeeded, failed, pending)
+    }
+
+    /// Predicts statuses states would have after next decision making.
+    fn predict(&self, memory: &M) -> impl Iterator<Item = ParallelizerStateStatus> {
+        self.states.iter().map(|(state, status)| match status {
+            ParallelizerStateStatus::Idle => {
+                if state.condition.validate(memory) {
+                    ParallelizerStateStatus::Running
+                } else {
+                    ParallelizerStateStatus::Failed
+                }
+            }
+            ParallelizerStateStatus::Running => {
+                if !state.condition.validate(memory) {
+                    ParallelizerStateStatus::Failed
+                } else if !state.task.is_locked(memory) {
+                    ParallelizerStateStatus::Succeeded
+                } else {
+                    ParallelizerStateStatus::Running
+                }
+            }
+            status => *status,
+        })
+    }
+
     /// Update active/running states.
     pub fn update(&mut self, memory: &mut M) {
-        for (state, active) in &mut self.states {
-            if *active {
+        for (state, status) in &mut self.states {
+            if *status == ParallelizerStateStatus::Running {
                 state.task.on_update(memory);
             }
         }
@@ -143,9 +475,14 @@ where
 
 impl<M> Task<M> for Parallelizer<M> {
     fn is_locked(&self, memory: &M) -> bool {
-        self.states
-            .iter()
-            .any(|(state, active)| *active && state.task.is_locked(memory))
+        let locked = self.states.iter().any(|(state, status)| {
+            *status == ParallelizerStateStatus::Running && state.task.is_locked(memory)
+        });
+        if !self.policy.is_resolving() {
+            return locked;
+        }
+        let unresolved = self.outcome.is_none() && self.resolve(self.predict(memory)).is_none();
+        unresolved || (!self.policy.cancel_remaining && locked)
     }
 
     fn on_enter(&mut self, memory: &mut M) {
@@ -158,6 +495,15 @@ impl<M> Task<M> for Parallelizer<M> {
     }
 
     fn on_stop(&mut self, memory: &mut M, reason: TaskStopReason) {
+        if self.policy.is_resolving() && self.policy.cancel_remaining {
+            // states that did not finish their work when policy resolved get cancelled.
+            for (state, status) in &mut self.states {
+                if *status == ParallelizerStateStatus::Running && state.task.is_locked(memory) {
+                    state.task.on_stop(memory, TaskStopReason::Cancelled);
+                    *status = ParallelizerStateStatus::Idle;
+                }
+            }
+        }
         self.reset_with_reason(memory, true, reason);
     }
 
@@ -174,6 +520,8 @@ impl<M> std::fmt::Debug for Parallelizer<M> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Parallelizer")
             .field("states", &self.states)
+            .field("policy", &self.policy)
+            .field("outcome", &self.outcome)
             .finish()
     }
 }
@@ -2,10 +2,19 @@
 
 use crate::{DefaultKey, Scalar, condition::*, consideration::*, decision_makers::*, task::*};
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{HashMap, HashSet, VecDeque},
     hash::Hash,
+    sync::Arc,
+    time::Duration,
 };
 
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+/// Shared function that reads world state version from memory, used by planner to replan whenever
+/// the world changes (see [`Planner::replan_on_world_change`]).
+pub type PlannerWorldVersion<M = ()> = Arc<dyn Fn(&M) -> u64 + Send + Sync>;
+
 /// Planner action.
 pub enum PlannerError<CK = DefaultKey, AK = DefaultKey> {
     /// There is no condition with given ID found in planner.
@@ -14,6 +23,10 @@ pub enum PlannerError<CK = DefaultKey, AK = DefaultKey> {
     ConditionIsNeverUsed(CK),
     /// There is no action with given ID found in planner.
     ActionDoesNotExists(AK),
+    /// There is already an action with given ID in planner.
+    ActionAlreadyExists(AK),
+    /// Condition with given ID is still used by some planner action.
+    ConditionIsInUse(CK),
 }
 
 impl<CK, AK> Clone for PlannerError<CK, AK>
@@ -26,6 +39,8 @@ where
             Self::ConditionDoesNotExists(key) => Self::ConditionDoesNotExists(key.clone()),
             Self::ConditionIsNeverUsed(key) => Self::ConditionIsNeverUsed(key.clone()),
             Self::ActionDoesNotExists(key) => Self::ActionDoesNotExists(key.clone()),
+            Self::ActionAlreadyExists(key) => Self::ActionAlreadyExists(key.clone()),
+            Self::ConditionIsInUse(key) => Self::ConditionIsInUse(key.clone()),
         }
     }
 }
@@ -40,6 +55,8 @@ where
             (Self::ConditionDoesNotExists(a), Self::ConditionDoesNotExists(b)) => a == b,
             (Self::ConditionIsNeverUsed(a), Self::ConditionIsNeverUsed(b)) => a == b,
             (Self::ActionDoesNotExists(a), Self::ActionDoesNotExists(b)) => a == b,
+            (Self::ActionAlreadyExists(a), Self::ActionAlreadyExists(b)) => a == b,
+            (Self::ConditionIsInUse(a), Self::ConditionIsInUse(b)) => a == b,
             _ => false,
         }
     }
@@ -68,6 +85,230 @@ where
             Self::ActionDoesNotExists(key) => {
                 write!(f, "ActionDoesNotExists({:?})", key)
             }
+            Self::ActionAlreadyExists(key) => {
+                write!(f, "ActionAlreadyExists({:?})", key)
+            }
+            Self::ConditionIsInUse(key) => {
+                write!(f, "ConditionIsInUse({:?})", key)
+            }
+        }
+    }
+}
+
+/// Reason why candidate goal was not picked by the planner.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PlannerGoalRejection {
+    /// Goal utility score was not positive.
+    Undesired,
+    /// There is no action with goal ID found in planner.
+    ActionDoesNotExists,
+    /// Goal action is disabled.
+    Disabled,
+    /// There is no action that could start a plan in current state.
+    NoStartAction,
+    /// There is no path between start action and goal action.
+    Unreachable,
+}
+
+/// Reason why planner has thrown away its running plan and searched for new one.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PlannerReplanReason {
+    /// Preconditions of currently running action became false.
+    ///
+    /// Running action gets stopped with [`TaskStopReason::Cancelled`] before replanning.
+    InvalidStep,
+    /// Currently running action runs for too long.
+    ///
+    /// Running action gets stopped with [`TaskStopReason::Cancelled`] before replanning.
+    StepTimeout,
+    /// World state version has changed since plan was made.
+    ///
+    /// Running action gets stopped with [`TaskStopReason::Replaced`] only if new plan differs.
+    WorldChanged,
+    /// Plan is older than replanning period.
+    ///
+    /// Running action gets stopped with [`TaskStopReason::Replaced`] only if new plan differs.
+    Periodic,
+}
+
+impl PlannerReplanReason {
+    /// Returns reason with which running action gets stopped when replanning.
+    pub fn stop_reason(self) -> TaskStopReason {
+        match self {
+            Self::InvalidStep | Self::StepTimeout => TaskStopReason::Cancelled,
+            Self::WorldChanged | Self::Periodic => TaskStopReason::Replaced,
+        }
+    }
+}
+
+/// State of plan search towards single goal, kept between processing calls when planning budget
+/// is limited.
+struct PlannerSearch<AK> {
+    goal_action: AK,
+    scores: HashMap<AK, Scalar>,
+    gscores: HashMap<AK, Scalar>,
+    open: Vec<(Scalar, AK)>,
+    came_from: HashMap<AK, AK>,
+    explored: Vec<AK>,
+}
+
+type PlannerSearchResult<CK, AK> = Result<Vec<AK>, Box<PlanFailure<CK, AK>>>;
+
+/// Values of all planner conditions and world version, used to tell if memory has changed.
+#[derive(PartialEq)]
+struct PlannerWorldState {
+    conditions: Vec<bool>,
+    version: Option<u64>,
+}
+
+/// Goal selection that is still in progress.
+struct PlannerPendingSearch<AK> {
+    ranking: Vec<(AK, bool)>,
+    world: PlannerWorldState,
+    candidates: VecDeque<(AK, bool)>,
+    start_action: Option<AK>,
+    replanning: bool,
+    current: Option<(bool, PlannerSearch<AK>)>,
+}
+
+/// Finished goal selection, which stays valid for as long as goals ranking, world state and pursued
+/// goal stay the same.
+struct PlannerSettledSearch<AK> {
+    ranking: Vec<(AK, bool)>,
+    world: PlannerWorldState,
+    active_goal: Option<AK>,
+}
+
+/// Limits amount of work done by single plan search call.
+struct PlannerBudget {
+    expansions: Option<usize>,
+    #[cfg(not(target_arch = "wasm32"))]
+    deadline: Option<Instant>,
+    used: usize,
+}
+
+impl PlannerBudget {
+    fn unlimited() -> Self {
+        Self {
+            expansions: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            deadline: None,
+            used: 0,
+        }
+    }
+
+    // at least one node gets expanded per call, so search always makes progress.
+    fn is_exhausted(&self) -> bool {
+        if self.used == 0 {
+            return false;
+        }
+        if self.expansions.is_some_and(|limit| self.used >= limit) {
+            return true;
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            return true;
+        }
+        false
+    }
+}
+
+#[derive(Default)]
+struct PlannerReplanState {
+    world_version: Option<u64>,
+    planned_at: Option<Scalar>,
+    step_started_at: Option<Scalar>,
+    step_valid: bool,
+}
+
+/// Result of searching for a plan (see [`Planner::find_plan`]).
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlanOutcome<CK = DefaultKey, AK = DefaultKey>
+where
+    CK: Hash + Eq,
+    AK: Hash + Eq,
+{
+    /// Plan did not change because given goal is already pursued.
+    Unchanged,
+    /// Running plan got cleared because there is no goal to pursue.
+    Cleared,
+    /// New plan has started.
+    Started,
+    /// There is no plan that leads towards given goal.
+    Failed(PlanFailure<CK, AK>),
+}
+
+impl<CK, AK> PlanOutcome<CK, AK>
+where
+    CK: Hash + Eq,
+    AK: Hash + Eq,
+{
+    /// Tells if running plan has changed.
+    pub fn has_changed(&self) -> bool {
+        matches!(self, Self::Cleared | Self::Started)
+    }
+
+    /// Returns failure explanation if plan could not be found.
+    pub fn failure(&self) -> Option<&PlanFailure<CK, AK>> {
+        match self {
+            Self::Failed(failure) => Some(failure),
+            _ => None,
+        }
+    }
+}
+
+/// Reason why planner could not find a plan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlanFailureReason<AK = DefaultKey> {
+    /// Currently running action with given ID is locked and planning wasn't forced.
+    Locked(AK),
+    /// Goal action is disabled.
+    Disabled,
+    /// There is no action that could start a plan in current state.
+    NoStartAction,
+    /// There is no path between start action and goal action.
+    Unreachable,
+}
+
+/// Explanation of why planner could not find a plan towards goal action.
+///
+/// Useful for debugging and designer tooling to tell for example that sword can't be crafted
+/// because there is no action that provides coal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlanFailure<CK = DefaultKey, AK = DefaultKey>
+where
+    CK: Hash + Eq,
+    AK: Hash + Eq,
+{
+    /// Reason of the failure.
+    pub reason: PlanFailureReason<AK>,
+    /// Action IDs explored during search, in order of exploration.
+    pub explored: Vec<AK>,
+    /// Path from start action to explored action that got the closest to goal action - the one
+    /// which postconditions provide most of goal action preconditions.
+    pub best_path: Vec<AK>,
+    /// Cost scores of actions evaluated during search.
+    pub costs: HashMap<AK, Scalar>,
+    /// Goal action preconditions that are neither met in current state nor provided by any of
+    /// explored actions.
+    pub missing_conditions: HashSet<CK>,
+}
+
+impl<CK, AK> PlanFailure<CK, AK>
+where
+    CK: Hash + Eq,
+    AK: Hash + Eq,
+{
+    fn new(reason: PlanFailureReason<AK>) -> Self {
+        Self {
+            reason,
+            explored: vec![],
+            best_path: vec![],
+            costs: Default::default(),
+            missing_conditions: Default::default(),
         }
     }
 }
@@ -81,6 +322,7 @@ where
     postconditions: HashSet<K>,
     cost: Box<dyn Consideration<M>>,
     task: Box<dyn Task<M>>,
+    enabled: bool,
 }
 
 impl<M, K> PlannerAction<M, K>
@@ -98,6 +340,7 @@ where
             postconditions: Default::default(),
             cost: Box::new(cost),
             task: Box::new(task),
+            enabled: true,
         }
     }
 
@@ -108,6 +351,7 @@ where
             postconditions: Default::default(),
             cost,
             task,
+            enabled: true,
         }
     }
 
@@ -123,6 +367,17 @@ where
         self
     }
 
+    /// Tells if action can be used by the planner. Disabled actions are never planned.
+    pub fn enabled(mut self, mode: bool) -> Self {
+        self.enabled = mode;
+        self
+    }
+
+    /// Tells if action can be used by the planner.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
     /// Constructs new planner action with set of preconditions, post conditions, cost of the action
     /// and action task.
     pub fn new<C, T>(
@@ -140,6 +395,7 @@ where
             postconditions,
             cost: Box::new(cost),
             task: Box::new(task),
+            enabled: true,
         }
     }
 
@@ -156,7 +412,28 @@ where
             postconditions,
             cost,
             task,
+            enabled: true,
+        }
+    }
+
+    fn uses_condition(&self, id: &K) -> bool {
+        self.preconditions.contains(id) || self.postconditions.contains(id)
+    }
+
+    fn connection_weight(&self, other: &Self, exact_conditions_match: bool) -> Option<Scalar> {
+        let count = self
+            .postconditions
+            .intersection(&other.preconditions)
+            .count();
+        let limit = self.postconditions.len().min(other.postconditions.len());
+        if exact_conditions_match {
+            if count == limit {
+                return Some(1.0);
+            }
+        } else if count > 0 {
+            return Some(limit as Scalar / count as Scalar);
         }
+        None
     }
 
     fn score_preconditions(
@@ -199,10 +476,204 @@ where
         f.debug_struct("PlannerAction")
             .field("precondition", &self.preconditions)
             .field("postconditions", &self.postconditions)
+            .field("enabled", &self.enabled)
+            .finish()
+    }
+}
+
+/// Planner action bound to parameter, produced by [`PlannerActionGenerator`].
+pub struct PlannerActionInstance<M = (), CK = DefaultKey, AK = DefaultKey>
+where
+    CK: Clone + Hash + Eq,
+{
+    /// ID of bound action.
+    pub id: AK,
+    /// Bound action.
+    pub action: PlannerAction<M, CK>,
+    /// Conditions specific to bound parameter, used by bound action.
+    pub conditions: Vec<(CK, Box<dyn Condition<M>>)>,
+}
+
+impl<M, CK, AK> PlannerActionInstance<M, CK, AK>
+where
+    CK: Clone + Hash + Eq,
+{
+    /// Constructs new instance of bound action.
+    pub fn new(id: AK, action: PlannerAction<M, CK>) -> Self {
+        Self {
+            id,
+            action,
+            conditions: vec![],
+        }
+    }
+
+    /// Add condition specific to bound parameter.
+    pub fn condition<C>(self, id: CK, condition: C) -> Self
+    where
+        C: Condition<M> + 'static,
+    {
+        self.condition_raw(id, Box::new(condition))
+    }
+
+    /// Add condition specific to bound parameter.
+    pub fn condition_raw(mut self, id: CK, condition: Box<dyn Condition<M>>) -> Self {
+        self.conditions.push((id, condition));
+        self
+    }
+}
+
+impl<M, CK, AK> std::fmt::Debug for PlannerActionInstance<M, CK, AK>
+where
+    CK: Clone + Hash + Eq + std::fmt::Debug,
+    AK: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PlannerActionInstance")
+            .field("id", &self.id)
+            .field("action", &self.action)
+            .field(
+                "conditions",
+                &self.conditions.iter().map(|(id, _)| id).collect::<Vec<_>>(),
+            )
             .finish()
     }
 }
 
+/// Generates planner actions bound to parameters, whenever planner is about to plan.
+pub trait PlannerActionGenerator<M = (), CK = DefaultKey, AK = DefaultKey>: Send + Sync
+where
+    CK: Clone + Hash + Eq,
+{
+    /// Returns actions bound to parameters that are valid for given memory state.
+    fn generate(&self, memory: &M) -> Vec<PlannerActionInstance<M, CK, AK>>;
+
+    /// Tells if action with given ID can be generated for any memory state.
+    ///
+    /// Used by [`PlannerBuilder::build`] to validate goals that point to generated actions, by
+    /// default generator does not produce any goal.
+    #[allow(unused_variables)]
+    fn can_generate(&self, id: &AK) -> bool {
+        false
+    }
+}
+
+/// Parameterized planner action template.
+///
+/// Candidate generator returns list of parameters for current memory state (for example all known
+/// ore nodes), then each parameter gets bound into planner action that has its own ID, conditions,
+/// cost and task - that way bound parameter is carried through to the task that executes it.
+///
+/// # Example
+/// ```
+/// use emergent::prelude::*;
+///
+/// struct Memory {
+///     ores: Vec<usize>,
+///     location: usize,
+///     target: Option<usize>,
+/// }
+///
+/// let goto = PlannerActionTemplate::new(
+///     |m: &Memory| m.ores.clone(),
+///     |ore| {
+///         PlannerActionInstance::new(
+///             format!("goto({})", ore),
+///             PlannerAction::task(
+///                 ore as Scalar,
+///                 ClosureTask::default().enter(move |m: &mut Memory| m.target = Some(ore)),
+///             )
+///             .postcondition(format!("at({})", ore)),
+///         )
+///         .condition(
+///             format!("at({})", ore),
+///             ClosureCondition::new(move |m: &Memory| m.location == ore),
+///         )
+///     },
+/// );
+///
+/// let mut planner = PlannerBuilder::new(SingleDecisionMaker::new("goto(3)".to_owned()))
+///     .template(goto.generates(|id: &String| id.starts_with("goto(")))
+///     .goal("goto(3)".to_owned(), 1.0)
+///     .build()
+///     .unwrap();
+///
+/// let mut memory = Memory {
+///     ores: vec![3],
+///     location: 0,
+///     target: None,
+/// };
+/// assert!(planner.process(&mut memory));
+/// assert_eq!(planner.active_action().map(|id| id.as_str()), Some("goto(3)"));
+/// assert_eq!(memory.target, Some(3));
+/// ```
+#[allow(clippy::type_complexity)]
+pub struct PlannerActionTemplate<M = (), CK = DefaultKey, AK = DefaultKey, P = ()>
+where
+    CK: Clone + Hash + Eq,
+{
+    candidates: Box<dyn Fn(&M) -> Vec<P> + Send + Sync>,
+    bind: Box<dyn Fn(P) -> PlannerActionInstance<M, CK, AK> + Send + Sync>,
+    generates: Option<Box<dyn Fn(&AK) -> bool + Send + Sync>>,
+}
+
+impl<M, CK, AK, P> PlannerActionTemplate<M, CK, AK, P>
+where
+    CK: Clone + Hash + Eq,
+{
+    /// Constructs new template with candidate parameters generator and parameter binding function.
+    pub fn new<G, B>(candidates: G, bind: B) -> Self
+    where
+        G: Fn(&M) -> Vec<P> + Send + Sync + 'static,
+        B: Fn(P) -> PlannerActionInstance<M, CK, AK> + Send + Sync + 'static,
+    {
+        Self {
+            candidates: Box::new(candidates),
+            bind: Box::new(bind),
+            generates: None,
+        }
+    }
+
+    /// Sets function that tells if action with given ID can be bound by this template, which
+    /// allows to use template actions as planner builder goals.
+    ///
+    /// See [`PlannerActionGenerator::can_generate`].
+    pub fn generates<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&AK) -> bool + Send + Sync + 'static,
+    {
+        self.generates = Some(Box::new(f));
+        self
+    }
+}
+
+impl<M, CK, AK, P> PlannerActionGenerator<M, CK, AK> for PlannerActionTemplate<M, CK, AK, P>
+where
+    CK: Clone + Hash + Eq,
+{
+    fn generate(&self, memory: &M) -> Vec<PlannerActionInstance<M, CK, AK>> {
+        (self.candidates)(memory)
+            .into_iter()
+            .map(|parameter| (self.bind)(parameter))
+            .collect()
+    }
+
+    fn can_generate(&self, id: &AK) -> bool {
+        self.generates
+            .as_ref()
+            .map(|generates| generates(id))
+            .unwrap_or_default()
+    }
+}
+
+impl<M, CK, AK, P> std::fmt::Debug for PlannerActionTemplate<M, CK, AK, P>
+where
+    CK: Clone + Hash + Eq,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PlannerActionTemplate").finish()
+    }
+}
+
 /// Planner (a.k.a. Goal Oriented Action Planner)
 ///
 /// Planners are used to plan long term lists of actions that will lead to desired end goal.
@@ -233,10 +704,53 @@ where
 /// __So to sum things up: planner is just a pathfinding performed on set of actions connected by
 /// facts about the state of the world.__
 ///
-/// _It's worth noting that one disadvantage of a planner is that when new plan gets calculated it
-/// won't change until either goal selector don't change its mind or user forces to find new plan.
-/// That means plan can't change during already running plan execution, it can change only when
-/// goal changes._
+/// _It's worth noting that by default, when new plan gets calculated it won't change until either
+/// goal selector don't change its mind or user forces to find new plan. That means plan can't
+/// change during already running plan execution, it can change only when goal changes - unless
+/// replanning triggers are enabled._
+///
+/// Parameterized actions
+/// ---
+/// Instead of registering one action per target (for example one "go to" action per location),
+/// planner can hold action templates (see [`PlannerActionTemplate`]) that get bound to parameters
+/// generated from memory right before planning, so plan can contain actions bound to them.
+/// Actions that fail to bind do not stop planning - it continues with ones bound successfully,
+/// while error is reported by [`Planner::template_error`].
+///
+/// Incremental planning
+/// ---
+/// For big action sets plan search can be spread across multiple processing calls with
+/// [`Planner::planning_budget`] and/or [`Planner::planning_time_limit`]. While search is in
+/// progress, currently running action keeps running, or fallback task runs if there is no running
+/// plan (see [`Planner::fallback_task`]). Search in progress starts over whenever the most important
+/// candidate goal changes, any planner condition or world state version changes, or replanning gets
+/// triggered, so plans are never made for outdated goals or world state.
+///
+/// Goals that could not be planned for are not searched again until goals ranking, any planner
+/// condition or world state version changes, so unreachable goals don't cost full search on every
+/// processing. Memory changes that affect planning (including action templates) but not planner
+/// conditions should be reported with [`Planner::replan_on_world_change`].
+///
+/// Replanning
+/// ---
+/// Planner can throw away running plan and search for new one when:
+/// - preconditions of running action become false ([`Planner::replan_on_invalid_step`]),
+/// - world state version changes ([`Planner::replan_on_world_change`]),
+/// - plan gets older than given period ([`Planner::replan_every`]),
+/// - running action runs for too long ([`Planner::step_timeout`]).
+///
+/// Invalid action and timeout interrupt running action with [`TaskStopReason::Cancelled`], while
+/// world change and periodic replanning respect locked actions and stop running action with
+/// [`TaskStopReason::Replaced`] only when new plan differs from running one. Reason of the last
+/// replanning can be read with [`Planner::replan_reason`].
+///
+/// Multiple goals
+/// ---
+/// Besides goal selector decision, planner can also hold a list of candidate goals, each scored by
+/// its own utility consideration. On every processing candidates are ranked (goal selector decision
+/// first, then goals with positive utility from the most useful one) and planner picks the first
+/// goal that it can find a plan for, falling back to the next ones otherwise. Goals that were
+/// skipped along with the reason can be read with [`Planner::rejected_goals`].
 ///
 /// # Example
 /// ```
@@ -316,7 +830,26 @@ where
     conditions: HashMap<CK, Box<dyn Condition<M>>>,
     actions: HashMap<AK, PlannerAction<M, CK>>,
     connections: Vec<(AK, AK, Scalar)>,
+    exact_conditions_match: bool,
     goal_selector: Box<dyn DecisionMaker<M, AK>>,
+    templates: Vec<Box<dyn PlannerActionGenerator<M, CK, AK>>>,
+    template_actions: HashSet<AK>,
+    template_conditions: HashSet<CK>,
+    goals: Vec<(AK, Box<dyn Consideration<M>>)>,
+    rejected_goals: Vec<(AK, PlannerGoalRejection)>,
+    template_error: Option<PlannerError<CK, AK>>,
+    replan_on_invalid_step: bool,
+    world_version: Option<PlannerWorldVersion<M>>,
+    replan_period: Option<(Scalar, TaskClock<M>)>,
+    step_timeout: Option<(Scalar, TaskClock<M>)>,
+    replan_state: PlannerReplanState,
+    replan_reason: Option<PlannerReplanReason>,
+    planning_budget: Option<usize>,
+    planning_time_limit: Option<Duration>,
+    search: Option<PlannerPendingSearch<AK>>,
+    settled: Option<PlannerSettledSearch<AK>>,
+    fallback: Option<Box<dyn Task<M>>>,
+    fallback_active: bool,
     plan: Option<(usize, Vec<AK>)>,
 }
 
@@ -416,16 +949,8 @@ where
             .iter()
             .flat_map(|(ak, av)| {
                 actions.iter().filter_map(move |(bk, bv)| {
-                    let count = av.postconditions.intersection(&bv.preconditions).count();
-                    let limit = av.postconditions.len().min(bv.postconditions.len());
-                    if exact_conditions_match {
-                        if count == limit {
-                            return Some((ak.clone(), bk.clone(), 1.0));
-                        }
-                    } else if count > 0 {
-                        return Some((ak.clone(), bk.clone(), limit as Scalar / count as Scalar));
-                    }
-                    None
+                    av.connection_weight(bv, exact_conditions_match)
+                        .map(|weight| (ak.clone(), bk.clone(), weight))
                 })
             })
             .collect();
@@ -433,105 +958,1133 @@ where
             conditions,
             actions,
             connections,
+            exact_conditions_match,
             goal_selector,
+            templates: vec![],
+            template_actions: Default::default(),
+            template_conditions: Default::default(),
+            goals: vec![],
+            rejected_goals: vec![],
+            template_error: None,
+            replan_on_invalid_step: false,
+            world_version: None,
+            replan_period: None,
+            step_timeout: None,
+            replan_state: Default::default(),
+            replan_reason: None,
+            planning_budget: None,
+            planning_time_limit: None,
+            search: None,
+            settled: None,
+            fallback: None,
+            fallback_active: false,
             plan: None,
         }
     }
 
-    /// Returns slice of currently running plan action IDs.
-    pub fn active_plan(&self) -> Option<&[AK]> {
-        self.plan.as_ref().map(|(start, plan)| &plan[(*start)..])
+    /// Add candidate goal action scored by its utility.
+    ///
+    /// # Example
+    /// ```
+    /// use emergent::prelude::*;
+    ///
+    /// let mut planner = PlannerBuilder::new(NoDecisionMaker::default())
+    ///     .action("idle", PlannerAction::task(1.0, NoTask).postcondition("rested"))
+    ///     .action("fly", PlannerAction::task(1.0, NoTask).precondition("wings"))
+    ///     .action("walk", PlannerAction::task(1.0, NoTask).precondition("rested"))
+    ///     .condition("rested", true)
+    ///     .condition("wings", false)
+    ///     .build()
+    ///     .unwrap()
+    ///     .goal("fly", 1.0)
+    ///     .goal("walk", 0.5);
+    ///
+    /// assert!(planner.process(&mut ()));
+    /// assert_eq!(planner.active_goal(), Some(&"walk"));
+    /// assert_eq!(
+    ///     planner.rejected_goals(),
+    ///     &[("fly", PlannerGoalRejection::Unreachable)],
+    /// );
+    /// ```
+    pub fn goal<C>(self, id: AK, utility: C) -> Self
+    where
+        C: Consideration<M> + 'static,
+    {
+        self.goal_raw(id, Box::new(utility))
     }
 
-    /// Tells currently running action ID.
-    pub fn active_action(&self) -> Option<&AK> {
-        match self.active_plan() {
-            Some(plan) => plan.first(),
-            None => None,
-        }
+    /// Add candidate goal action scored by its utility.
+    pub fn goal_raw(mut self, id: AK, utility: Box<dyn Consideration<M>>) -> Self {
+        self.goals.push((id, utility));
+        self
     }
 
-    /// Tells current goal action ID.
-    pub fn active_goal(&self) -> Option<&AK> {
-        match &self.plan {
-            Some((_, plan)) => plan.last(),
-            None => None,
-        }
+    /// Add parameterized action template (or any other bound actions generator).
+    ///
+    /// See [`PlannerActionTemplate`].
+    pub fn template<T>(self, template: T) -> Self
+    where
+        T: PlannerActionGenerator<M, CK, AK> + 'static,
+    {
+        self.template_raw(Box::new(template))
     }
 
-    /// Tells transition between two actions in running plan - current and next action IDs.
-    pub fn active_transition(&self) -> (Option<&AK>, Option<&AK>) {
-        match self.active_plan() {
-            Some(plan) => {
-                let mut iter = plan.iter();
-                let prev = iter.next();
-                let next = iter.next();
-                (prev, next)
-            }
-            None => (None, None),
-        }
+    /// Add parameterized action template (or any other bound actions generator).
+    pub fn template_raw(mut self, template: Box<dyn PlannerActionGenerator<M, CK, AK>>) -> Self {
+        self.templates.push(template);
+        self
     }
 
-    /// Find best possible plan towards goal action.
+    /// Tells if action with given ID was bound from action template.
+    pub fn is_template_action(&self, id: &AK) -> bool {
+        self.template_actions.contains(id)
+    }
+
+    /// Replaces actions bound from templates with ones generated for current memory state.
     ///
-    /// By default plan won't change if currently running action is locked, unless we force it.
-    pub fn find_plan(
-        &mut self,
-        goal_action: Option<AK>,
-        memory: &mut M,
-        forced: bool,
-    ) -> Result<bool, PlannerError<CK, AK>> {
-        if self.active_action() == goal_action.as_ref() {
-            return Ok(false);
+    /// Error is also kept until next refresh (see [`Self::template_error`]), since planner
+    /// refreshes templates on its own whenever it is about to plan.
+    ///
+    /// Planner does that on its own before every planning, but it might be useful for inspection.
+    /// Bound actions that are part of running plan are kept until that plan ends.
+    pub fn refresh_templates(&mut self, memory: &M) -> Result<(), PlannerError<CK, AK>> {
+        if self.templates.is_empty() {
+            return Ok(());
         }
-        let active_action = self.active_action().cloned();
-        if let Some(id) = &active_action
-            && !forced
-            && self.actions.get_mut(id).unwrap().task.is_locked(memory)
-        {
-            return Ok(false);
+        self.search = None;
+        self.settled = None;
+        let instances = self
+            .templates
+            .iter()
+            .flat_map(|template| template.generate(memory))
+            .collect::<Vec<_>>();
+        let kept = self
+            .active_plan()
+            .map(|plan| {
+                plan.iter()
+                    .filter(|id| self.template_actions.contains(*id))
+                    .cloned()
+                    .collect::<HashSet<_>>()
+            })
+            .unwrap_or_default();
+        for id in std::mem::take(&mut self.template_actions) {
+            if kept.contains(&id) {
+                self.template_actions.insert(id);
+            } else {
+                self.actions.remove(&id);
+                self.connections
+                    .retain(|(from, to, _)| from != &id && to != &id);
+            }
         }
-        let goal_action = match goal_action {
-            Some(id) => id,
-            None => {
-                if let Some(id) = &active_action {
-                    self.actions
-                        .get_mut(id)
-                        .unwrap()
-                        .task
-                        .on_stop(memory, TaskStopReason::Cancelled);
+        let mut actions = Vec::with_capacity(instances.len());
+        for instance in instances {
+            for (id, condition) in instance.conditions {
+                if !self.conditions.contains_key(&id) || self.template_conditions.contains(&id) {
+                    self.conditions.insert(id.clone(), condition);
+                    self.template_conditions.insert(id);
                 }
-                self.plan = None;
-                self.goal_selector.change_mind(None, memory);
-                return Ok(true);
             }
-        };
-        if !self.actions.contains_key(&goal_action) {
-            return Err(PlannerError::ActionDoesNotExists(goal_action));
+            actions.push((instance.id, instance.action));
         }
-        let start_action = match self.find_start_action(memory) {
-            Some(id) => id,
-            None => return Ok(false),
-        };
-        if let Some(id) = &active_action {
-            self.actions
-                .get_mut(id)
+        let mut result = Ok(());
+        for (id, action) in actions {
+            if kept.contains(&id) {
+                continue;
+            }
+            match self.add_action(id.clone(), action) {
+                Ok(()) => {
+                    self.template_actions.insert(id);
+                }
+                Err(error) => {
+                    if result.is_ok() {
+                        result = Err(error);
+                    }
+                }
+            }
+        }
+        let unused = self
+            .template_conditions
+            .iter()
+            .filter(|id| {
+                !self
+                    .actions
+                    .values()
+                    .any(|action| action.uses_condition(id))
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        for id in unused {
+            self.conditions.remove(&id);
+            self.template_conditions.remove(&id);
+        }
+        self.template_error = result.clone().err();
+        result
+    }
+
+    /// Returns error reported by last templates refresh, for example when template bound action
+    /// with ID that is already used by another action.
+    pub fn template_error(&self) -> Option<&PlannerError<CK, AK>> {
+        self.template_error.as_ref()
+    }
+
+    /// Returns iterator over candidate goal action IDs.
+    pub fn goals(&self) -> impl Iterator<Item = &AK> {
+        self.goals.iter().map(|(id, _)| id)
+    }
+
+    /// Returns candidate goals rejected during last processing along with rejection reasons.
+    pub fn rejected_goals(&self) -> &[(AK, PlannerGoalRejection)] {
+        &self.rejected_goals
+    }
+
+    /// Limits number of actions explored by plan search during single processing.
+    ///
+    /// Search that did not finish continues on next processing, while currently running action
+    /// (or fallback task if there is none) keeps running. Explicit [`Self::find_plan`] calls are
+    /// never limited.
+    ///
+    /// # Example
+    /// ```
+    /// use emergent::prelude::*;
+    ///
+    /// let mut planner = PlannerBuilder::new(SingleDecisionMaker::new("c"))
+    ///     .action("a", PlannerAction::task(1.0, NoTask).postcondition("a"))
+    ///     .action(
+    ///         "b",
+    ///         PlannerAction::task(1.0, NoTask)
+    ///             .precondition("a")
+    ///             .postcondition("b"),
+    ///     )
+    ///     .action("c", PlannerAction::task(1.0, NoTask).precondition("b"))
+    ///     .condition("a", false)
+    ///     .condition("b", false)
+    ///     .build()
+    ///     .unwrap()
+    ///     .planning_budget(1)
+    ///     .fallback_task(ClosureTask::default().enter(|m: &mut bool| *m = true));
+    ///
+    /// let mut waiting = false;
+    /// assert!(!planner.process(&mut waiting));
+    /// assert!(planner.is_planning());
+    /// assert!(waiting);
+    /// assert!(!planner.process(&mut waiting));
+    /// assert!(planner.process(&mut waiting));
+    /// assert!(!planner.is_planning());
+    /// assert_eq!(planner.active_plan(), Some(["a", "b", "c"].as_slice()));
+    /// ```
+    pub fn planning_budget(mut self, expansions: usize) -> Self {
+        self.planning_budget = Some(expansions.max(1));
+        self
+    }
+
+    /// Limits time spent on plan search during single processing.
+    ///
+    /// See [`Self::planning_budget`]. Not supported on `wasm32` targets, where only expansions
+    /// budget applies.
+    pub fn planning_time_limit(mut self, value: Duration) -> Self {
+        self.planning_time_limit = Some(value);
+        self
+    }
+
+    /// Set task that runs while plan search is in progress and there is no running plan.
+    pub fn fallback_task<T>(self, task: T) -> Self
+    where
+        T: Task<M> + 'static,
+    {
+        self.fallback_task_raw(Box::new(task))
+    }
+
+    /// Set task that runs while plan search is in progress and there is no running plan.
+    pub fn fallback_task_raw(mut self, task: Box<dyn Task<M>>) -> Self {
+        self.fallback = Some(task);
+        self
+    }
+
+    /// Returns number of actions explored by plan search during single processing.
+    pub fn get_planning_budget(&self) -> Option<usize> {
+        self.planning_budget
+    }
+
+    /// Returns time limit of plan search during single processing.
+    pub fn get_planning_time_limit(&self) -> Option<Duration> {
+        self.planning_time_limit
+    }
+
+    /// Tells if plan search is still in progress.
+    pub fn is_planning(&self) -> bool {
+        self.search.is_some()
+    }
+
+    /// Returns goal action ID that plan search currently searches path to.
+    pub fn planning_goal(&self) -> Option<&AK> {
+        self.search
+            .as_ref()
+            .and_then(|search| search.current.as_ref())
+            .map(|(_, search)| &search.goal_action)
+    }
+
+    /// Tells if fallback task is running.
+    pub fn is_fallback_active(&self) -> bool {
+        self.fallback_active
+    }
+
+    /// Tells if planner should replan when preconditions of running action become false.
+    ///
+    /// Unlike other replanning triggers, this one interrupts running action even if it is locked.
+    pub fn replan_on_invalid_step(mut self, mode: bool) -> Self {
+        self.replan_on_invalid_step = mode;
+        self
+    }
+
+    /// Makes planner replan whenever world state version read from memory changes.
+    ///
+    /// # Example
+    /// ```
+    /// use emergent::prelude::*;
+    /// use std::sync::Arc;
+    ///
+    /// struct Memory {
+    ///     version: u64,
+    ///     stops: Vec<TaskStopReason>,
+    /// }
+    ///
+    /// let task = || ClosureTask::default().stop(|m: &mut Memory, r| m.stops.push(r));
+    /// let mut planner = PlannerBuilder::new(SingleDecisionMaker::new("eat"))
+    ///     .action("cook", PlannerAction::task(1.0, task()).postcondition("food"))
+    ///     .action("eat", PlannerAction::task(1.0, task()).precondition("food"))
+    ///     .condition("food", false)
+    ///     .build()
+    ///     .unwrap()
+    ///     .replan_on_world_change(Arc::new(|m: &Memory| m.version));
+    ///
+    /// let mut memory = Memory { version: 0, stops: vec![] };
+    /// assert!(planner.process(&mut memory));
+    /// assert_eq!(planner.active_plan(), Some(["cook", "eat"].as_slice()));
+    ///
+    /// memory.version += 1;
+    /// assert!(!planner.process(&mut memory));
+    /// assert_eq!(planner.replan_reason(), Some(PlannerReplanReason::WorldChanged));
+    /// // new plan is the same as before, so running action keeps running.
+    /// assert!(memory.stops.is_empty());
+    /// ```
+    pub fn replan_on_world_change(mut self, version: PlannerWorldVersion<M>) -> Self {
+        self.world_version = Some(version);
+        self
+    }
+
+    /// Makes planner replan every time given duration passes since plan was made.
+    pub fn replan_every(mut self, duration: Scalar, clock: TaskClock<M>) -> Self {
+        self.replan_period = Some((duration, clock));
+        self
+    }
+
+    /// Makes planner replan when currently running action runs longer than given duration.
+    ///
+    /// Unlike other replanning triggers, this one interrupts running action even if it is locked.
+    pub fn step_timeout(mut self, duration: Scalar, clock: TaskClock<M>) -> Self {
+        self.step_timeout = Some((duration, clock));
+        self
+    }
+
+    /// Tells if planner replans when preconditions of running action become false.
+    pub fn get_replan_on_invalid_step(&self) -> bool {
+        self.replan_on_invalid_step
+    }
+
+    /// Returns replanning period duration.
+    pub fn get_replan_period(&self) -> Option<Scalar> {
+        self.replan_period.as_ref().map(|(duration, _)| *duration)
+    }
+
+    /// Returns running action timeout duration.
+    pub fn get_step_timeout(&self) -> Option<Scalar> {
+        self.step_timeout.as_ref().map(|(duration, _)| *duration)
+    }
+
+    /// Returns reason of replanning performed during last processing.
+    pub fn replan_reason(&self) -> Option<PlannerReplanReason> {
+        self.replan_reason
+    }
+
+    /// Tells if connections between actions are made only if preconditions and postcondition are
+    /// exactly the same.
+    pub fn get_exact_conditions_match(&self) -> bool {
+        self.exact_conditions_match
+    }
+
+    /// Tells if there is condition with given ID.
+    pub fn has_condition(&self, id: &CK) -> bool {
+        self.conditions.contains_key(id)
+    }
+
+    /// Tells if there is action with given ID.
+    pub fn has_action(&self, id: &AK) -> bool {
+        self.actions.contains_key(id)
+    }
+
+    /// Tells if action with given ID can be used by the planner.
+    pub fn is_action_enabled(&self, id: &AK) -> Option<bool> {
+        self.actions.get(id).map(|action| action.enabled)
+    }
+
+    /// Add condition (fact about the world state), returns replaced condition if there was one.
+    pub fn add_condition<C>(&mut self, id: CK, condition: C) -> Option<Box<dyn Condition<M>>>
+    where
+        C: Condition<M> + 'static,
+    {
+        self.add_condition_raw(id, Box::new(condition))
+    }
+
+    /// Add condition (fact about the world state), returns replaced condition if there was one.
+    pub fn add_condition_raw(
+        &mut self,
+        id: CK,
+        condition: Box<dyn Condition<M>>,
+    ) -> Option<Box<dyn Condition<M>>> {
+        self.conditions.insert(id, condition)
+    }
+
+    /// Remove condition that is not used by any of the actions.
+    pub fn remove_condition(
+        &mut self,
+        id: &CK,
+    ) -> Result<Box<dyn Condition<M>>, PlannerError<CK, AK>> {
+        if !self.conditions.contains_key(id) {
+            return Err(PlannerError::ConditionDoesNotExists(id.clone()));
+        }
+        if self
+            .actions
+            .values()
+            .any(|action| action.uses_condition(id))
+        {
+            return Err(PlannerError::ConditionIsInUse(id.clone()));
+        }
+        self.template_conditions.remove(id);
+        Ok(self.conditions.remove(id).unwrap())
+    }
+
+    /// Add planner action and connect it with other actions.
+    ///
+    /// # Example
+    /// ```
+    /// use emergent::prelude::*;
+    ///
+    /// let mut planner = PlannerBuilder::new(SingleDecisionMaker::new("attack"))
+    ///     .action("punch", PlannerAction::task(1.0, NoTask).postcondition("attack"))
+    ///     .action("attack", PlannerAction::task(1.0, NoTask).precondition("attack"))
+    ///     .condition("attack", false)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// planner.add_condition("has_sword", true);
+    /// planner
+    ///     .add_action(
+    ///         "slash",
+    ///         PlannerAction::task(0.1, NoTask)
+    ///             .precondition("has_sword")
+    ///             .postcondition("attack"),
+    ///     )
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     planner.add_action("slash", PlannerAction::task(0.1, NoTask)),
+    ///     Err(PlannerError::ActionAlreadyExists("slash")),
+    /// );
+    /// assert_eq!(
+    ///     planner.remove_condition(&"has_sword").err(),
+    ///     Some(PlannerError::ConditionIsInUse("has_sword")),
+    /// );
+    ///
+    /// planner.set_action_enabled(&"punch", false, &mut ()).unwrap();
+    /// assert!(planner.process(&mut ()));
+    /// assert_eq!(planner.active_plan(), Some(["slash", "attack"].as_slice()));
+    /// ```
+    pub fn add_action(
+        &mut self,
+        id: AK,
+        action: PlannerAction<M, CK>,
+    ) -> Result<(), PlannerError<CK, AK>> {
+        if self.actions.contains_key(&id) {
+            return Err(PlannerError::ActionAlreadyExists(id));
+        }
+        self.search = None;
+        self.settled = None;
+        if let Some(cid) = action
+            .preconditions
+            .iter()
+            .chain(action.postconditions.iter())
+            .find(|cid| !self.conditions.contains_key(cid))
+        {
+            return Err(PlannerError::ConditionDoesNotExists(cid.clone()));
+        }
+        for (oid, other) in &self.actions {
+            if let Some(weight) = action.connection_weight(other, self.exact_conditions_match) {
+                self.connections.push((id.clone(), oid.clone(), weight));
+            }
+            if let Some(weight) = other.connection_weight(&action, self.exact_conditions_match) {
+                self.connections.push((oid.clone(), id.clone(), weight));
+            }
+        }
+        if let Some(weight) = action.connection_weight(&action, self.exact_conditions_match) {
+            self.connections.push((id.clone(), id.clone(), weight));
+        }
+        self.actions.insert(id, action);
+        Ok(())
+    }
+
+    /// Remove planner action along with its connections.
+    ///
+    /// If action is part of running plan, that plan gets cancelled.
+    pub fn remove_action(
+        &mut self,
+        id: &AK,
+        memory: &mut M,
+    ) -> Result<PlannerAction<M, CK>, PlannerError<CK, AK>> {
+        if !self.actions.contains_key(id) {
+            return Err(PlannerError::ActionDoesNotExists(id.clone()));
+        }
+        self.cancel_plan_using(id, memory);
+        self.search = None;
+        self.settled = None;
+        self.connections
+            .retain(|(from, to, _)| from != id && to != id);
+        self.template_actions.remove(id);
+        Ok(self.actions.remove(id).unwrap())
+    }
+
+    /// Enable or disable planner action.
+    ///
+    /// If disabled action is part of running plan, that plan gets cancelled.
+    pub fn set_action_enabled(
+        &mut self,
+        id: &AK,
+        enabled: bool,
+        memory: &mut M,
+    ) -> Result<(), PlannerError<CK, AK>> {
+        if !self.actions.contains_key(id) {
+            return Err(PlannerError::ActionDoesNotExists(id.clone()));
+        }
+        if !enabled {
+            self.cancel_plan_using(id, memory);
+        }
+        self.search = None;
+        self.settled = None;
+        self.actions.get_mut(id).unwrap().enabled = enabled;
+        Ok(())
+    }
+
+    /// Returns slice of currently running plan action IDs.
+    pub fn active_plan(&self) -> Option<&[AK]> {
+        self.plan.as_ref().map(|(start, plan)| &plan[(*start)..])
+    }
+
+    /// Tells currently running action ID.
+    pub fn active_action(&self) -> Option<&AK> {
+        match self.active_plan() {
+            Some(plan) => plan.first(),
+            None => None,
+        }
+    }
+
+    /// Tells current goal action ID.
+    pub fn active_goal(&self) -> Option<&AK> {
+        match &self.plan {
+            Some((_, plan)) => plan.last(),
+            None => None,
+        }
+    }
+
+    /// Tells transition between two actions in running plan - current and next action IDs.
+    pub fn active_transition(&self) -> (Option<&AK>, Option<&AK>) {
+        match self.active_plan() {
+            Some(plan) => {
+                let mut iter = plan.iter();
+                let prev = iter.next();
+                let next = iter.next();
+                (prev, next)
+            }
+            None => (None, None),
+        }
+    }
+
+    /// Find best possible plan towards goal action.
+    ///
+    /// By default plan won't change if currently running action is locked, unless we force it.
+    /// When plan can't be found, returned outcome explains why.
+    ///
+    /// # Example
+    /// ```
+    /// use emergent::prelude::*;
+    ///
+    /// let mut planner = PlannerBuilder::new(NoDecisionMaker::default())
+    ///     .action("mine", PlannerAction::task(1.0, NoTask).postcondition("iron"))
+    ///     .action("craft_sword", PlannerAction::task(1.0, NoTask).precondition("coal"))
+    ///     .condition("iron", false)
+    ///     .condition("coal", false)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let outcome = planner.find_plan(Some("craft_sword"), &mut (), false).unwrap();
+    /// let failure = outcome.failure().unwrap();
+    /// assert_eq!(failure.reason, PlanFailureReason::Unreachable);
+    /// assert_eq!(failure.explored, vec!["mine"]);
+    /// assert_eq!(failure.best_path, vec!["mine"]);
+    /// assert!(failure.missing_conditions.contains("coal"));
+    /// ```
+    pub fn find_plan(
+        &mut self,
+        goal_action: Option<AK>,
+        memory: &mut M,
+        forced: bool,
+    ) -> Result<PlanOutcome<CK, AK>, PlannerError<CK, AK>> {
+        let result = self.find_plan_now(goal_action, memory, forced);
+        self.sync_fallback(memory);
+        result
+    }
+
+    fn find_plan_now(
+        &mut self,
+        goal_action: Option<AK>,
+        memory: &mut M,
+        forced: bool,
+    ) -> Result<PlanOutcome<CK, AK>, PlannerError<CK, AK>> {
+        if self.active_action() == goal_action.as_ref()
+            && (goal_action.is_some() || self.search.is_none())
+        {
+            return Ok(PlanOutcome::Unchanged);
+        }
+        let active_action = self.active_action().cloned();
+        if let Some(id) = active_action
+            .as_ref()
+            .filter(|id| !forced && self.actions[*id].task.is_locked(memory))
+        {
+            return Ok(PlanOutcome::Failed(PlanFailure::new(
+                PlanFailureReason::Locked(id.clone()),
+            )));
+        }
+        self.search = None;
+        self.settled = None;
+        let goal_action = match goal_action {
+            Some(id) => id,
+            None => {
+                if let Some(id) = &active_action {
+                    self.actions
+                        .get_mut(id)
+                        .unwrap()
+                        .task
+                        .on_stop(memory, TaskStopReason::Cancelled);
+                }
+                self.plan = None;
+                self.goal_selector.change_mind(None, memory);
+                return Ok(PlanOutcome::Cleared);
+            }
+        };
+        // refresh error is kept for `template_error` and planning continues with actions that
+        // were bound successfully.
+        let _ = self.refresh_templates(memory);
+        if !self.actions.contains_key(&goal_action) {
+            return Err(PlannerError::ActionDoesNotExists(goal_action));
+        }
+        if !self.actions[&goal_action].enabled {
+            return Ok(PlanOutcome::Failed(PlanFailure::new(
+                PlanFailureReason::Disabled,
+            )));
+        }
+        let start_action = match self.find_start_action(memory) {
+            Some(id) => id,
+            None => {
+                return Ok(PlanOutcome::Failed(PlanFailure::new(
+                    PlanFailureReason::NoStartAction,
+                )));
+            }
+        };
+        if let Some(id) = &active_action {
+            self.actions
+                .get_mut(id)
                 .unwrap()
                 .task
                 .on_stop(memory, TaskStopReason::Cancelled);
             self.plan = None;
         }
+        match self.search_plan(&start_action, &goal_action, memory) {
+            Ok(path) => {
+                self.start_plan(path, true, TaskStopReason::Cancelled, memory);
+                Ok(PlanOutcome::Started)
+            }
+            Err(failure) => Ok(PlanOutcome::Failed(*failure)),
+        }
+    }
+
+    /// Perform decision making.
+    pub fn process(&mut self, memory: &mut M) -> bool {
+        self.replan_reason = None;
+        let new_id = self.goal_selector.decide(memory);
+        let (candidates, undesired) = self.rank_goals(new_id, memory);
+        let trigger = self.replan_trigger(memory);
+        // pending search is valid only for goals ranking and memory from when it has started.
+        if let Some(pending) = &self.search
+            && (trigger.is_some()
+                || pending.ranking.first() != candidates.first()
+                || pending.world != self.world_state(memory))
+        {
+            self.search = None;
+        }
+        let changed = if self.search.is_some() {
+            self.continue_search(memory)
+        } else if trigger.is_none() && self.is_settled(&candidates, memory) {
+            // goals that were rejected last time would get rejected again.
+            false
+        } else {
+            self.rejected_goals.clear();
+            self.rejected_goals.extend(
+                undesired
+                    .into_iter()
+                    .map(|id| (id, PlannerGoalRejection::Undesired)),
+            );
+            if let Some(reason) = trigger {
+                self.replan(candidates, reason, memory)
+            } else {
+                candidates.first().map(|(id, _)| id) != self.active_goal()
+                    && self.select_goal(candidates, false, memory)
+            }
+        };
+        if changed {
+            self.sync_fallback(memory);
+            return true;
+        }
+        match self.active_transition() {
+            (Some(prev), Some(next)) => {
+                let prev_passing = self
+                    .actions
+                    .get(prev)
+                    .unwrap()
+                    .validate_postconditions(&self.conditions, memory);
+                let next_passing = self
+                    .actions
+                    .get(next)
+                    .unwrap()
+                    .validate_preconditions(&self.conditions, memory);
+                if prev_passing && next_passing {
+                    let prev = prev.clone();
+                    let next = next.clone();
+                    self.actions
+                        .get_mut(&prev)
+                        .unwrap()
+                        .task
+                        .on_stop(memory, TaskStopReason::Completed);
+                    self.actions.get_mut(&next).unwrap().task.on_enter(memory);
+                    self.plan.as_mut().unwrap().0 += 1;
+                    self.reset_step(memory);
+                }
+            }
+            (Some(prev), None) => {
+                let prev_passing = self
+                    .actions
+                    .get(prev)
+                    .unwrap()
+                    .validate_postconditions(&self.conditions, memory);
+                if prev_passing {
+                    let prev = prev.clone();
+                    self.actions
+                        .get_mut(&prev)
+                        .unwrap()
+                        .task
+                        .on_stop(memory, TaskStopReason::Completed);
+                    self.plan = None;
+                }
+            }
+            _ => {}
+        }
+        self.sync_fallback(memory);
+        if let Some(id) = self.active_action().cloned() {
+            return self.actions.get_mut(&id).unwrap().task.on_process(memory);
+        }
+        if self.fallback_active {
+            return self.fallback.as_mut().unwrap().on_process(memory);
+        }
+        false
+    }
+
+    /// Update currently active state.
+    pub fn update(&mut self, memory: &mut M) {
+        if let Some(id) = self.active_action().cloned() {
+            self.actions.get_mut(&id).unwrap().task.on_update(memory);
+        } else if self.fallback_active {
+            self.fallback.as_mut().unwrap().on_update(memory);
+        }
+    }
+
+    /// Returns candidate goals in order of importance: goal selector decision first, then
+    /// candidate goals with positive utility, starting from the most useful one. Goals without
+    /// positive utility are returned separately.
+    fn rank_goals(&self, selected: Option<AK>, memory: &M) -> (Vec<(AK, bool)>, Vec<AK>) {
+        let mut scored = Vec::with_capacity(self.goals.len());
+        let mut undesired = vec![];
+        for (id, utility) in &self.goals {
+            let score = utility.score(memory);
+            if score > 0.0 {
+                scored.push((id, score));
+            } else {
+                undesired.push(id.clone());
+            }
+        }
+        scored.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
+        let mut result = Vec::with_capacity(scored.len() + 1);
+        if let Some(id) = selected {
+            result.push((id, true));
+        }
+        for (id, _) in scored {
+            if !result.iter().any(|(other, _)| other == id) {
+                result.push((id.clone(), false));
+            }
+        }
+        (result, undesired)
+    }
+
+    /// Tells if last goal selection still holds for given goals ranking and memory.
+    fn is_settled(&self, candidates: &[(AK, bool)], memory: &M) -> bool {
+        self.settled.as_ref().is_some_and(|settled| {
+            settled.ranking == candidates
+                && settled.active_goal.as_ref() == self.active_goal()
+                && settled.world == self.world_state(memory)
+        })
+    }
+
+    fn world_state(&self, memory: &M) -> PlannerWorldState {
+        PlannerWorldState {
+            // conditions are never modified while world state is in use (modifications drop
+            // pending search), so iteration order stays the same.
+            conditions: self
+                .conditions
+                .values()
+                .map(|condition| condition.validate(memory))
+                .collect(),
+            version: self.world_version.as_ref().map(|f| f(memory)),
+        }
+    }
+
+    /// Starts planning towards candidate goals in given order, stopping at the first one that either
+    /// is already pursued or can be planned for. Returns true if new plan has started.
+    ///
+    /// When replanning, currently pursued goal gets planned again and running plan is kept only if
+    /// new plan turns out to be the same.
+    fn select_goal(
+        &mut self,
+        candidates: Vec<(AK, bool)>,
+        replanning: bool,
+        memory: &mut M,
+    ) -> bool {
+        if candidates.is_empty() {
+            return self
+                .find_plan_now(None, memory, replanning)
+                .is_ok_and(|outcome| outcome.has_changed());
+        }
+        if !replanning
+            && let Some(id) = self.active_action()
+            && self.actions.get(id).unwrap().task.is_locked(memory)
+        {
+            return false;
+        }
+        // refresh error is kept for `template_error` and planning continues with actions that
+        // were bound successfully.
+        let _ = self.refresh_templates(memory);
+        self.settled = None;
+        self.search = Some(PlannerPendingSearch {
+            ranking: candidates.clone(),
+            world: self.world_state(memory),
+            candidates: candidates.into(),
+            start_action: self.find_start_action(memory),
+            replanning,
+            current: None,
+        });
+        self.continue_search(memory)
+    }
+
+    /// Continues goal selection within planning budget. Returns true if new plan has started.
+    fn continue_search(&mut self, memory: &mut M) -> bool {
+        let Some(mut pending) = self.search.take() else {
+            return false;
+        };
+        let mut budget = self.budget();
+        loop {
+            let (selected, search) = match &mut pending.current {
+                Some((selected, search)) => (*selected, search),
+                None => {
+                    let Some((id, selected)) = pending.candidates.pop_front() else {
+                        break;
+                    };
+                    if !pending.replanning && self.active_goal() == Some(&id) {
+                        self.settle(pending);
+                        return false;
+                    }
+                    let Some(action) = self.actions.get(&id) else {
+                        self.rejected_goals
+                            .push((id, PlannerGoalRejection::ActionDoesNotExists));
+                        continue;
+                    };
+                    if !action.enabled {
+                        self.rejected_goals
+                            .push((id, PlannerGoalRejection::Disabled));
+                        continue;
+                    }
+                    let Some(start_action) = &pending.start_action else {
+                        self.rejected_goals
+                            .push((id, PlannerGoalRejection::NoStartAction));
+                        continue;
+                    };
+                    let search = self.begin_search(start_action.clone(), id, memory);
+                    pending.current = Some((selected, search));
+                    continue;
+                }
+            };
+            match self.expand_search(search, memory, &mut budget) {
+                None => {
+                    self.search = Some(pending);
+                    return false;
+                }
+                Some(Ok(path)) => {
+                    if pending.replanning && self.active_plan() == Some(path.as_slice()) {
+                        self.reset_plan(memory);
+                        self.settle(pending);
+                        return false;
+                    }
+                    let reason = if pending.replanning {
+                        TaskStopReason::Replaced
+                    } else {
+                        TaskStopReason::Cancelled
+                    };
+                    self.start_plan(path, selected, reason, memory);
+                    self.settle(pending);
+                    return true;
+                }
+                Some(Err(_)) => {
+                    let (_, search) = pending.current.take().unwrap();
+                    self.rejected_goals
+                        .push((search.goal_action, PlannerGoalRejection::Unreachable));
+                }
+            }
+        }
+        if let Some(id) = self.active_action().cloned() {
+            self.actions
+                .get_mut(&id)
+                .unwrap()
+                .task
+                .on_stop(memory, TaskStopReason::Cancelled);
+            self.plan = None;
+        }
+        self.settle(pending);
+        false
+    }
+
+    /// Remembers finished goal selection.
+    fn settle(&mut self, pending: PlannerPendingSearch<AK>) {
+        self.settled = Some(PlannerSettledSearch {
+            ranking: pending.ranking,
+            world: pending.world,
+            active_goal: self.active_goal().cloned(),
+        });
+    }
+
+    fn budget(&self) -> PlannerBudget {
+        PlannerBudget {
+            expansions: self.planning_budget,
+            #[cfg(not(target_arch = "wasm32"))]
+            deadline: self.planning_time_limit.map(|limit| Instant::now() + limit),
+            used: 0,
+        }
+    }
+
+    /// Runs fallback task only while plan search is in progress and there is no running plan.
+    fn sync_fallback(&mut self, memory: &mut M) {
+        let Some(task) = self.fallback.as_mut() else {
+            return;
+        };
+        let needed = self.search.is_some() && self.plan.is_none();
+        if needed && !self.fallback_active {
+            task.on_enter(memory);
+            self.fallback_active = true;
+        } else if !needed && self.fallback_active {
+            let reason = if self.plan.is_some() {
+                TaskStopReason::Replaced
+            } else {
+                TaskStopReason::Cancelled
+            };
+            task.on_stop(memory, reason);
+            self.fallback_active = false;
+        }
+    }
+
+    /// Stops currently running action if needed and searches for new plan.
+    fn replan(
+        &mut self,
+        candidates: Vec<(AK, bool)>,
+        reason: PlannerReplanReason,
+        memory: &mut M,
+    ) -> bool {
+        let id = self.active_action().cloned().unwrap();
+        let task = &mut self.actions.get_mut(&id).unwrap().task;
+        match reason.stop_reason() {
+            TaskStopReason::Replaced => {
+                if task.is_locked(memory) {
+                    return false;
+                }
+                // plan keeps running while new one is searched, so the same change must not
+                // trigger replanning again.
+                self.replan_state.world_version = self.world_version.as_ref().map(|f| f(memory));
+                self.replan_state.planned_at = self.replan_period.as_ref().map(|(_, f)| f(memory));
+            }
+            reason => {
+                task.on_stop(memory, reason);
+                self.plan = None;
+            }
+        }
+        self.replan_reason = Some(reason);
+        self.select_goal(candidates, true, memory)
+    }
+
+    /// Stops currently running action and starts given plan.
+    fn start_plan(
+        &mut self,
+        path: Vec<AK>,
+        notify_goal_selector: bool,
+        reason: TaskStopReason,
+        memory: &mut M,
+    ) {
+        if let Some(id) = self.active_action().cloned() {
+            self.actions
+                .get_mut(&id)
+                .unwrap()
+                .task
+                .on_stop(memory, reason);
+        }
+        if self.fallback_active {
+            self.fallback
+                .as_mut()
+                .unwrap()
+                .on_stop(memory, TaskStopReason::Replaced);
+            self.fallback_active = false;
+        }
+        let goal_action = path.last().cloned();
+        self.actions
+            .get_mut(path.first().unwrap())
+            .unwrap()
+            .task
+            .on_enter(memory);
+        self.plan = Some((0, path));
+        self.reset_plan(memory);
+        if notify_goal_selector {
+            self.goal_selector.change_mind(goal_action, memory);
+        }
+    }
+
+    fn reset_plan(&mut self, memory: &M) {
+        self.replan_state.world_version = self.world_version.as_ref().map(|f| f(memory));
+        self.replan_state.planned_at = self.replan_period.as_ref().map(|(_, f)| f(memory));
+        self.reset_step(memory);
+    }
+
+    fn reset_step(&mut self, memory: &M) {
+        self.replan_state.step_started_at = self.step_timeout.as_ref().map(|(_, f)| f(memory));
+        self.replan_state.step_valid = match self.active_action() {
+            Some(id) => {
+                let action = &self.actions[id];
+                action.validate_preconditions(&self.conditions, memory)
+                    || action.validate_postconditions(&self.conditions, memory)
+            }
+            None => false,
+        };
+    }
+
+    /// Returns first replanning trigger that fired for currently running action.
+    fn replan_trigger(&mut self, memory: &M) -> Option<PlannerReplanReason> {
+        let action = &self.actions[self.active_action()?];
+        if let Some((duration, clock)) = &self.step_timeout
+            && let Some(started_at) = self.replan_state.step_started_at
+            && clock(memory) - started_at >= *duration
+        {
+            return Some(PlannerReplanReason::StepTimeout);
+        }
+        if self.replan_on_invalid_step {
+            // action that already achieved its postconditions is done, not invalid.
+            let valid = action.validate_preconditions(&self.conditions, memory)
+                || action.validate_postconditions(&self.conditions, memory);
+            if std::mem::replace(&mut self.replan_state.step_valid, valid) && !valid {
+                return Some(PlannerReplanReason::InvalidStep);
+            }
+        }
+        if let Some(version) = &self.world_version
+            && self.replan_state.world_version != Some(version(memory))
+        {
+            return Some(PlannerReplanReason::WorldChanged);
+        }
+        if let Some((duration, clock)) = &self.replan_period
+            && let Some(planned_at) = self.replan_state.planned_at
+            && clock(memory) - planned_at >= *duration
+        {
+            return Some(PlannerReplanReason::Periodic);
+        }
+        None
+    }
+
+    fn search_plan(
+        &self,
+        start_action: &AK,
+        goal_action: &AK,
+        memory: &M,
+    ) -> PlannerSearchResult<CK, AK> {
+        let mut search = self.begin_search(start_action.clone(), goal_action.clone(), memory);
+        self.expand_search(&mut search, memory, &mut PlannerBudget::unlimited())
+            .unwrap()
+    }
+
+    fn begin_search(&self, start_action: AK, goal_action: AK, memory: &M) -> PlannerSearch<AK> {
+        let score = self.actions[&start_action].cost.score(memory);
         let mut scores = HashMap::with_capacity(self.actions.len());
-        scores.insert(
-            start_action.clone(),
-            self.actions[&start_action].cost.score(memory),
-        );
+        scores.insert(start_action.clone(), score);
         let mut gscores = HashMap::with_capacity(self.actions.len());
-        gscores.insert(start_action.clone(), scores[&start_action]);
+        gscores.insert(start_action.clone(), score);
         let mut open = Vec::with_capacity(self.actions.len());
-        open.push((gscores[&start_action], start_action.clone()));
-        let mut came_from = HashMap::<AK, AK>::with_capacity(self.actions.len());
+        open.push((score, start_action));
+        PlannerSearch {
+            goal_action,
+            scores,
+            gscores,
+            open,
+            came_from: HashMap::with_capacity(self.actions.len()),
+            explored: Vec::with_capacity(self.actions.len()),
+        }
+    }
+
+    /// Expands search nodes until either path to goal is found, there is no path to goal or
+    /// budget gets exhausted (then returns `None`).
+    fn expand_search(
+        &self,
+        search: &mut PlannerSearch<AK>,
+        memory: &M,
+        budget: &mut PlannerBudget,
+    ) -> Option<PlannerSearchResult<CK, AK>> {
+        let path_to = |came_from: &HashMap<AK, AK>, id: &AK| {
+            let mut path = vec![id.clone()];
+            let mut current = id.clone();
+            while let Some(id) = came_from.get(&current) {
+                path.push(id.clone());
+                current = id.clone();
+            }
+            path.reverse();
+            path
+        };
+        let PlannerSearch {
+            goal_action,
+            scores,
+            gscores,
+            open,
+            came_from,
+            explored,
+        } = search;
         while !open.is_empty() {
+            if budget.is_exhausted() {
+                return None;
+            }
+            budget.used += 1;
             let index = open
                 .iter()
                 .enumerate()
@@ -539,28 +2092,13 @@ where
                 .map(|(i, _)| i)
                 .unwrap();
             let (total_score, id) = open.swap_remove(index);
-            if id == goal_action {
-                let mut path = Vec::with_capacity(came_from.len());
-                path.push(id.clone());
-                let mut current = id;
-                while let Some(id) = came_from.remove(&current) {
-                    path.push(id.clone());
-                    current = id;
-                }
-                path.reverse();
-                self.actions
-                    .get_mut(&start_action)
-                    .unwrap()
-                    .task
-                    .on_enter(memory);
-                self.plan = Some((0, path));
-                self.goal_selector.change_mind(Some(goal_action), memory);
-                return Ok(true);
+            if &id == goal_action {
+                return Some(Ok(path_to(came_from, &id)));
             }
             for (nid, weight) in self
                 .connections
                 .iter()
-                .filter(|(from, _, _)| from == &id)
+                .filter(|(from, to, _)| from == &id && self.actions[to].enabled)
                 .map(|(_, to, weight)| (to, weight))
             {
                 let gscore = gscores.get(nid).copied().unwrap_or(Scalar::INFINITY);
@@ -577,75 +2115,61 @@ where
                     }
                 }
             }
-        }
-        Ok(false)
-    }
-
-    /// Perform decision making.
-    pub fn process(&mut self, memory: &mut M) -> bool {
-        let new_id = self.goal_selector.decide(memory);
-        if new_id.as_ref() == self.active_goal() {
-            match self.active_transition() {
-                (Some(prev), Some(next)) => {
-                    let prev_passing = self
-                        .actions
-                        .get(prev)
-                        .unwrap()
-                        .validate_postconditions(&self.conditions, memory);
-                    let next_passing = self
-                        .actions
-                        .get(next)
-                        .unwrap()
-                        .validate_preconditions(&self.conditions, memory);
-                    if prev_passing && next_passing {
-                        let prev = prev.clone();
-                        let next = next.clone();
-                        self.actions
-                            .get_mut(&prev)
-                            .unwrap()
-                            .task
-                            .on_stop(memory, TaskStopReason::Completed);
-                        self.actions.get_mut(&next).unwrap().task.on_enter(memory);
-                        self.plan.as_mut().unwrap().0 += 1;
-                    }
-                }
-                (Some(prev), None) => {
-                    let prev_passing = self
-                        .actions
-                        .get(prev)
-                        .unwrap()
-                        .validate_postconditions(&self.conditions, memory);
-                    if prev_passing {
-                        let prev = prev.clone();
-                        self.actions
-                            .get_mut(&prev)
-                            .unwrap()
-                            .task
-                            .on_stop(memory, TaskStopReason::Completed);
-                        self.plan = None;
-                    }
-                }
-                _ => {}
+            if !explored.contains(&id) {
+                explored.push(id);
             }
-        } else if let Ok(true) = self.find_plan(new_id, memory, false) {
-            return true;
         }
-        if let Some(id) = self.active_action().cloned() {
-            return self.actions.get_mut(&id).unwrap().task.on_process(memory);
-        }
-        false
+        let goal_preconditions = &self.actions[&*goal_action].preconditions;
+        let best_path = explored
+            .iter()
+            .map(|id| {
+                let provided = self.actions[id]
+                    .postconditions
+                    .intersection(goal_preconditions)
+                    .count();
+                (id, provided, gscores[id])
+            })
+            .max_by(|(_, a, ascore), (_, b, bscore)| {
+                a.cmp(b).then_with(|| bscore.total_cmp(ascore))
+            })
+            .map(|(id, _, _)| path_to(came_from, id))
+            .unwrap_or_default();
+        let missing_conditions = goal_preconditions
+            .iter()
+            .filter(|id| {
+                !self.conditions[*id].validate(memory)
+                    && !explored
+                        .iter()
+                        .any(|aid| self.actions[aid].postconditions.contains(*id))
+            })
+            .cloned()
+            .collect();
+        Some(Err(Box::new(PlanFailure {
+            reason: PlanFailureReason::Unreachable,
+            explored: std::mem::take(explored),
+            best_path,
+            costs: std::mem::take(scores),
+            missing_conditions,
+        })))
     }
 
-    /// Update currently active state.
-    pub fn update(&mut self, memory: &mut M) {
-        if let Some(id) = self.active_action().cloned() {
-            self.actions.get_mut(&id).unwrap().task.on_update(memory);
+    fn cancel_plan_using(&mut self, id: &AK, memory: &mut M) {
+        if self.active_plan().is_some_and(|plan| plan.contains(id)) {
+            if let Some(active) = self.active_action().cloned() {
+                self.actions
+                    .get_mut(&active)
+                    .unwrap()
+                    .task
+                    .on_stop(memory, TaskStopReason::Cancelled);
+            }
+            self.plan = None;
         }
     }
 
     fn find_start_action(&self, memory: &M) -> Option<AK> {
         self.actions
             .iter()
+            .filter(|(_, action)| action.enabled)
             .map(|(id, action)| {
                 (
                     id,
@@ -671,7 +2195,8 @@ where
     }
 
     fn change_mind(&mut self, id: Option<AK>, memory: &mut M) -> bool {
-        matches!(self.find_plan(id, memory, true), Ok(true))
+        self.find_plan(id, memory, true)
+            .is_ok_and(|outcome| outcome.has_changed())
     }
 }
 
@@ -686,6 +2211,9 @@ where
         {
             return action.task.is_locked(memory);
         }
+        if self.fallback_active {
+            return self.fallback.as_ref().unwrap().is_locked(memory);
+        }
         false
     }
 
@@ -728,6 +2256,15 @@ where
             .field("conditions", &self.conditions.keys().collect::<Vec<_>>())
             .field("actions", &self.actions)
             .field("connections", &self.connections)
+            .field("exact_conditions_match", &self.exact_conditions_match)
+            .field("template_actions", &self.template_actions)
+            .field("goals", &self.goals().collect::<Vec<_>>())
+            .field("rejected_goals", &self.rejected_goals)
+            .field("template_error", &self.template_error)
+            .field("planning_budget", &self.planning_budget)
+            .field("planning_time_limit", &self.planning_time_limit)
+            .field("planning_goal", &self.planning_goal())
+            .field("fallback_active", &self.fallback_active)
             .field("plan", &self.plan)
             .finish()
     }
@@ -744,6 +2281,8 @@ where
     pub conditions: HashMap<CK, Box<dyn Condition<M>>>,
     pub actions: HashMap<AK, PlannerAction<M, CK>>,
     pub goal_selector: Box<dyn DecisionMaker<M, AK>>,
+    templates: Vec<Box<dyn PlannerActionGenerator<M, CK, AK>>>,
+    goals: Vec<(AK, Box<dyn Consideration<M>>)>,
     pub exact_conditions_match: bool,
 }
 
@@ -761,6 +2300,8 @@ where
             conditions: Default::default(),
             actions: Default::default(),
             goal_selector: Box::new(goal_selector),
+            templates: vec![],
+            goals: vec![],
             exact_conditions_match: false,
         }
     }
@@ -787,14 +2328,47 @@ where
         self
     }
 
+    /// Add parameterized action template (or any other bound actions generator).
+    pub fn template<T>(mut self, template: T) -> Self
+    where
+        T: PlannerActionGenerator<M, CK, AK> + 'static,
+    {
+        self.templates.push(Box::new(template));
+        self
+    }
+
+    /// Add candidate goal action scored by its utility.
+    pub fn goal<C>(mut self, id: AK, utility: C) -> Self
+    where
+        C: Consideration<M> + 'static,
+    {
+        self.goals.push((id, Box::new(utility)));
+        self
+    }
+
     /// Consumes and builds planner.
+    ///
+    /// Every goal has to point to either existing action or action that some template can
+    /// generate (see [`PlannerActionGenerator::can_generate`]).
     pub fn build(self) -> Result<Planner<M, CK, AK>, PlannerError<CK, AK>> {
-        Planner::new_raw(
+        if let Some((id, _)) = self.goals.iter().find(|(id, _)| {
+            !self.actions.contains_key(id)
+                && !self
+                    .templates
+                    .iter()
+                    .any(|template| template.can_generate(id))
+        }) {
+            return Err(PlannerError::ActionDoesNotExists(id.clone()));
+        }
+        let mut result = Planner::new_raw(
             self.conditions,
             self.actions,
             self.goal_selector,
             self.exact_conditions_match,
-        )
+        )?;
+        result.templates = self.templates;
+        result.goals = self.goals;
+        Ok(result)
     }
 }
 
@@ -807,6 +2381,10 @@ where
         f.debug_struct("PlannerBuilder")
             .field("conditions", &self.conditions.keys().collect::<Vec<_>>())
             .field("actions", &self.actions)
+            .field(
+                "goals",
+                &self.goals.iter().map(|(id, _)| id).collect::<Vec<_>>(),
+            )
             .field("exact_conditions_match", &self.exact_conditions_match)
             .finish()
     }
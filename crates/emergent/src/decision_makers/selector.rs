@@ -2,10 +2,41 @@
 
 use crate::{condition::*, decision_makers::*, task::*};
 
+/// Tells which running states can be aborted by state condition changes.
+///
+/// Aborts happen even when running state is locked, so there is no need to overload
+/// [`Task::is_locked`] to make higher priority states preempt lower priority ones.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum SelectorAbortMode {
+    /// State condition changes don't abort anything, states change only when running state is not
+    /// locked.
+    #[default]
+    None,
+    /// Running state gets aborted as soon as its condition is not met anymore.
+    Itself,
+    /// Running lower priority state gets aborted as soon as this state condition is met.
+    LowerPriority,
+    /// Both [`Self::Itself`] and [`Self::LowerPriority`].
+    Both,
+}
+
+impl SelectorAbortMode {
+    /// Tells if state aborts itself when its condition is not met anymore.
+    pub fn aborts_itself(self) -> bool {
+        matches!(self, Self::Itself | Self::Both)
+    }
+
+    /// Tells if state aborts running lower priority states when its condition is met.
+    pub fn aborts_lower_priority(self) -> bool {
+        matches!(self, Self::LowerPriority | Self::Both)
+    }
+}
+
 /// Defines selector state with task and condition.
 pub struct SelectorState<M = ()> {
     condition: Box<dyn Condition<M>>,
     task: Box<dyn Task<M>>,
+    abort_mode: SelectorAbortMode,
 }
 
 impl<M> SelectorState<M> {
@@ -15,21 +46,35 @@ impl<M> SelectorState<M> {
         C: Condition<M> + 'static,
         T: Task<M> + 'static,
     {
-        Self {
-            condition: Box::new(condition),
-            task: Box::new(task),
-        }
+        Self::new_raw(Box::new(condition), Box::new(task))
     }
 
     /// Constructs new state with condition and task.
     pub fn new_raw(condition: Box<dyn Condition<M>>, task: Box<dyn Task<M>>) -> Self {
-        Self { condition, task }
+        Self {
+            condition,
+            task,
+            abort_mode: SelectorAbortMode::default(),
+        }
+    }
+
+    /// Sets abort mode.
+    pub fn abort_mode(mut self, mode: SelectorAbortMode) -> Self {
+        self.abort_mode = mode;
+        self
+    }
+
+    /// Returns abort mode.
+    pub fn get_abort_mode(&self) -> SelectorAbortMode {
+        self.abort_mode
     }
 }
 
 impl<M> std::fmt::Debug for SelectorState<M> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("SelectorState").finish()
+        f.debug_struct("SelectorState")
+            .field("abort_mode", &self.abort_mode)
+            .finish()
     }
 }
 
@@ -67,6 +112,32 @@ impl<M> std::fmt::Debug for SelectorState<M> {
 /// assert!(selector.process(&mut memory));
 /// assert_eq!(memory, false);
 /// ```
+///
+/// Higher priority state can abort locked lower priority state (see [`SelectorAbortMode`]):
+/// ```
+/// use emergent::prelude::*;
+///
+/// struct Memory {
+///     danger: bool,
+///     fleeing: bool,
+/// }
+///
+/// let mut selector = Selector::new(vec![
+///     SelectorState::new(
+///         ClosureCondition::new(|m: &Memory| m.danger),
+///         ClosureTask::default().enter(|m: &mut Memory| m.fleeing = true),
+///     )
+///     .abort_mode(SelectorAbortMode::LowerPriority),
+///     SelectorState::new(true, ClosureTask::default().locked(|_: &Memory| true)),
+/// ]);
+/// let mut memory = Memory { danger: false, fleeing: false };
+/// assert!(selector.process(&mut memory));
+/// assert_eq!(selector.active_index(), Some(1));
+/// memory.danger = true;
+/// assert!(selector.process(&mut memory));
+/// assert_eq!(selector.active_index(), Some(0));
+/// assert!(memory.fleeing);
+/// ```
 pub struct Selector<M = ()> {
     states: Vec<SelectorState<M>>,
     active_index: Option<usize>,
@@ -136,7 +207,15 @@ impl<M> Selector<M> {
             .states
             .iter()
             .position(|state| state.condition.validate(memory));
-        if self.change_active_index(index, memory) {
+        let mut aborted = false;
+        if let Some(active_index) = self.active_index
+            && let Some(reason) = self.abort_reason(index, active_index, memory)
+        {
+            self.states[active_index].task.on_stop(memory, reason);
+            self.active_index = None;
+            aborted = true;
+        }
+        if self.change_active_index(index, memory) || aborted {
             return true;
         }
         if let Some(index) = self.active_index {
@@ -145,6 +224,31 @@ impl<M> Selector<M> {
         false
     }
 
+    /// Returns reason of aborting active state when state at `index` is the first valid one.
+    fn abort_reason(
+        &self,
+        index: Option<usize>,
+        active_index: usize,
+        memory: &M,
+    ) -> Option<TaskStopReason> {
+        if index == Some(active_index) {
+            return None;
+        }
+        let aborts_lower_priority = index
+            .map(|index| {
+                index < active_index && self.states[index].abort_mode.aborts_lower_priority()
+            })
+            .unwrap_or_default();
+        let state = &self.states[active_index];
+        if aborts_lower_priority {
+            Some(TaskStopReason::Replaced)
+        } else if state.abort_mode.aborts_itself() && !state.condition.validate(memory) {
+            Some(TaskStopReason::Cancelled)
+        } else {
+            None
+        }
+    }
+
     /// Update currently active state.
     pub fn update(&mut self, memory: &mut M) {
         if let Some(index) = self.active_index {
@@ -168,11 +272,18 @@ where
 }
 
 impl<M> Task<M> for Selector<M> {
+    /// Selector is locked when its active state is locked and will not get aborted by next
+    /// [`Selector::process`] call.
     fn is_locked(&self, memory: &M) -> bool {
-        if let Some(index) = self.active_index
-            && let Some(state) = self.states.get(index)
+        if let Some(active_index) = self.active_index
+            && let Some(state) = self.states.get(active_index)
         {
-            return state.task.is_locked(memory);
+            let index = self
+                .states
+                .iter()
+                .position(|state| state.condition.validate(memory));
+            return self.abort_reason(index, active_index, memory).is_none()
+                && state.task.is_locked(memory);
         }
         false
     }
@@ -33,11 +33,28 @@ impl<M> std::fmt::Debug for SequencerState<M> {
     }
 }
 
+/// Tells if sequencer re-validates conditions of states it has already passed.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum SequencerMode {
+    /// Sequencer remembers passed states and never checks their conditions again.
+    #[default]
+    Memory,
+    /// Sequencer checks conditions of passed states on every decision making and when any of them
+    /// is not met anymore, it cancels active state and starts over from the first state (sequence
+    /// stays inactive until condition of the first state is met).
+    ReactiveRestart,
+    /// Sequencer checks conditions of passed states on every decision making and when any of them
+    /// is not met anymore, it cancels active state and stops the sequence until it gets reset (see
+    /// [`Sequencer::reset`]) or entered again.
+    ReactiveAbort,
+}
+
 /// Sequencer runs states one by one.
 ///
-/// Sequencer has two properties that change its behavior:
+/// Sequencer has three properties that change its behavior:
 /// - Looping (see [`Self::is_looped`])
 /// - Continuity (see [`Self::does_continue`])
+/// - Mode (see [`Self::get_mode`])
 ///
 /// # Example
 /// ```
@@ -62,11 +79,48 @@ impl<M> std::fmt::Debug for SequencerState<M> {
 /// assert!(sequencer.process(&mut ()));
 /// assert_eq!(sequencer.active_index(), Some(0));
 /// ```
+///
+/// Reactive sequencer reacts to preconditions of passed states:
+/// ```
+/// use emergent::prelude::*;
+///
+/// let mut sequencer = Sequencer::new(
+///     vec![
+///         SequencerState::new(ClosureCondition::new(|m: &bool| *m), NoTask::default()),
+///         SequencerState::new(
+///             true,
+///             ClosureTask::default().locked(|_: &bool| true),
+///         ),
+///     ],
+///     false,
+///     false,
+/// )
+/// .mode(SequencerMode::ReactiveAbort);
+///
+/// let mut memory = true;
+/// assert!(sequencer.process(&mut memory));
+/// assert!(sequencer.process(&mut memory));
+/// assert_eq!(sequencer.active_index(), Some(1));
+/// assert!(sequencer.is_locked(&memory));
+/// memory = false;
+/// assert!(!sequencer.is_locked(&memory));
+/// assert!(sequencer.process(&mut memory));
+/// assert_eq!(sequencer.active_index(), None);
+/// assert!(sequencer.is_aborted());
+/// memory = true;
+/// assert!(!sequencer.process(&mut memory));
+/// assert!(sequencer.reset(&mut memory, true));
+/// assert!(sequencer.process(&mut memory));
+/// assert_eq!(sequencer.active_index(), Some(0));
+/// ```
 pub struct Sequencer<M = ()> {
     states: Vec<SequencerState<M>>,
     active_index: Option<usize>,
     looped: bool,
     continuity: bool,
+    mode: SequencerMode,
+    passed: Vec<usize>,
+    aborted: bool,
 }
 
 impl<M> Sequencer<M> {
@@ -80,9 +134,30 @@ impl<M> Sequencer<M> {
             active_index: None,
             looped,
             continuity,
+            mode: SequencerMode::default(),
+            passed: vec![],
+            aborted: false,
         }
     }
 
+    /// Sets mode.
+    pub fn mode(mut self, mode: SequencerMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Returns mode.
+    ///
+    /// See [`SequencerMode`].
+    pub fn get_mode(&self) -> SequencerMode {
+        self.mode
+    }
+
+    /// Tells if sequence got stopped by [`SequencerMode::ReactiveAbort`] and waits for reset.
+    pub fn is_aborted(&self) -> bool {
+        self.aborted
+    }
+
     /// Returns currently active state index.
     pub fn active_index(&self) -> Option<usize> {
         self.active_index
@@ -117,9 +192,22 @@ impl<M> Sequencer<M> {
             state.task.on_stop(memory, reason);
             self.active_index = None;
         }
+        self.passed.clear();
+        self.aborted = false;
         true
     }
 
+    /// Tells if any condition of states passed in current run is not met anymore.
+    ///
+    /// Always `false` in [`SequencerMode::Memory`].
+    pub fn is_invalidated(&self, memory: &M) -> bool {
+        self.mode != SequencerMode::Memory
+            && self
+                .passed
+                .iter()
+                .any(|index| !self.states[*index].condition.validate(memory))
+    }
+
     fn change_active_index(
         &mut self,
         index: Option<usize>,
@@ -136,6 +224,10 @@ impl<M> Sequencer<M> {
             }
             state.task.on_stop(memory, stop_reason);
         }
+        match (self.active_index, index) {
+            (Some(prev), Some(next)) if next > prev => self.passed.push(prev),
+            _ => self.passed.clear(),
+        }
         if let Some(index) = index {
             self.states.get_mut(index).unwrap().task.on_enter(memory);
         }
@@ -145,9 +237,17 @@ impl<M> Sequencer<M> {
 
     /// Perform decision making.
     pub fn process(&mut self, memory: &mut M) -> bool {
-        if self.states.is_empty() {
+        if self.states.is_empty() || self.aborted {
             return false;
         }
+        let invalidated = self.is_invalidated(memory);
+        if invalidated {
+            self.reset_with_reason(memory, true, TaskStopReason::Cancelled);
+            if self.mode == SequencerMode::ReactiveAbort {
+                self.aborted = true;
+                return true;
+            }
+        }
         let index = if let Some(index) = self.active_index {
             if self.looped {
                 if self.continuity {
@@ -200,10 +300,16 @@ impl<M> Sequencer<M> {
                     None => None,
                 }
             }
-        } else {
+        } else if self.mode == SequencerMode::Memory {
             self.states
                 .iter()
                 .position(|state| state.condition.validate(memory))
+        } else if self.states[0].condition.validate(memory) {
+            // reactive sequence always starts from its first state, otherwise states that were
+            // skipped would never be checked again.
+            Some(0)
+        } else {
+            None
         };
         let stop_reason = if let Some(active_index) = self.active_index {
             match index {
@@ -218,7 +324,7 @@ impl<M> Sequencer<M> {
             TaskStopReason::Replaced
         };
 
-        if self.change_active_index(index, memory, stop_reason) {
+        if self.change_active_index(index, memory, stop_reason) || invalidated {
             return true;
         }
         if let Some(index) = self.active_index {
@@ -251,6 +357,9 @@ where
 
 impl<M> Task<M> for Sequencer<M> {
     fn is_locked(&self, memory: &M) -> bool {
+        if self.is_invalidated(memory) {
+            return false;
+        }
         if let Some(index) = self.active_index
             && let Some(state) = self.states.get(index)
         {
@@ -288,6 +397,8 @@ impl<M> std::fmt::Debug for Sequencer<M> {
             .field("active_index", &self.active_index)
             .field("looped", &self.looped)
             .field("continuity", &self.continuity)
+            .field("mode", &self.mode)
+            .field("aborted", &self.aborted)
             .finish()
     }
 }
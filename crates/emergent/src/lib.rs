@@ -14,6 +14,7 @@
 //! can be used for automation and modularization of many aspects of game logic, not only agents
 //! and events - consider your creativity being the only limit of what AI techniques can be used for.
 
+pub mod agent_pool;
 pub mod builders;
 pub mod combinators;
 pub mod condition;
@@ -21,6 +22,7 @@ pub mod consideration;
 pub mod decision_makers;
 pub mod evaluators;
 pub mod memory;
+mod random;
 pub mod score_mapping;
 pub mod task;
 
@@ -36,6 +38,16 @@ pub type Scalar = f64;
 
 pub type DefaultKey = String;
 
+/// Compares scores, treating NaN as the lowest score.
+pub(crate) fn compare_scores(a: Scalar, b: Scalar) -> std::cmp::Ordering {
+    match (a.is_nan(), b.is_nan()) {
+        (true, true) => std::cmp::Ordering::Equal,
+        (true, false) => std::cmp::Ordering::Less,
+        (false, true) => std::cmp::Ordering::Greater,
+        (false, false) => a.total_cmp(&b),
+    }
+}
+
 pub trait DecisionMakingTask<M = (), K = DefaultKey>: DecisionMaker<M, K> + Task<M> {}
 
 impl<T, M, K> DecisionMakingTask<M, K> for T where T: DecisionMaker<M, K> + Task<M> {}
@@ -44,15 +56,17 @@ impl<T, M, K> DecisionMakingTask<M, K> for T where T: DecisionMaker<M, K> + Task
 pub mod prelude {
     pub use crate::{
         DecisionMakingTask, DefaultKey, Scalar,
-        builders::{behavior_tree::*, lod::*, *},
+        agent_pool::*,
+        builders::{behavior_tree::*, behavior_tree_dsl::*, lod::*, *},
         combinators::{all::*, any::*, count::*, *},
         condition::*,
         consideration::*,
         decision_makers::{
-            machinery::*, parallelizer::*, planner::*, reasoner::*, selector::*, sequencer::*, *,
+            machinery::*, mcts::*, parallelizer::*, planner::*, reasoner::*, selector::*,
+            sequencer::*, *,
         },
         evaluators::{max::*, min::*, product::*, sum::*, *},
-        memory::{blackboard::*, datatable::*, *},
+        memory::{blackboard::*, datatable::*, entity::*, scoped_blackboard::*, *},
         score_mapping::*,
         task::*,
     };
@@ -1,6 +1,8 @@
+use crate::task::{TransactionCommitPolicy, TransactionJournal, TransactionalMemory};
 use std::{
     any::{Any, TypeId},
     collections::HashMap,
+    sync::LazyLock,
 };
 
 /// Blackboard is a generalized data storage - properties of any type are assigned to keys/names.
@@ -41,19 +43,217 @@ use std::{
 /// assert!(memory.remove("tuple"));
 /// assert_eq!(memory.len(), 0);
 /// ```
+///
+/// # Change tracking
+/// Every change made to a property bumps blackboard version and remembers it as that property
+/// version, so one can ask what has changed since given moment without comparing the data.
+///
+/// ```
+/// use emergent::prelude::*;
+///
+/// let mut memory = Blackboard::default();
+/// memory.set("hungry".to_owned(), false);
+/// memory.set("energy".to_owned(), 1.0f32);
+///
+/// let version = memory.version();
+/// memory.with("energy", |v: &mut f32| *v -= 0.5);
+/// assert_eq!(memory.changed_since(version).collect::<Vec<_>>(), vec!["energy"]);
+/// assert!(memory.has_changed_since("energy", version));
+/// assert!(!memory.has_changed_since("hungry", version));
+/// ```
+///
+/// # Transactions
+/// Blackboard implements [`TransactionalMemory`], so it can be used with
+/// [`JournaledTransactionTask`](crate::task::JournaledTransactionTask) directly - while transaction
+/// is active, previous values of changed properties are recorded and restored on rollback.
+///
+/// In-place mutations (`get_mut`, `raw_mut`, `with`) need to clone previous value, so they work
+/// during transaction only for types blackboard knows how to clone: primitive types and [`String`]
+/// are cloneable by default, other types have to be put with [`Blackboard::set_cloneable`] or
+/// registered with [`Blackboard::register_cloneable`]. In-place mutation of any other type during
+/// transaction is refused (accessors return `None`), since it could not be rolled back.
+///
+/// ```
+/// use emergent::prelude::*;
+///
+/// let mut memory = Blackboard::default();
+/// memory.set("wood".to_owned(), 2usize);
+///
+/// memory.begin_transaction();
+/// memory.with("wood", |v: &mut usize| *v -= 1);
+/// memory.set("plank".to_owned(), true);
+/// memory.rollback_transaction();
+///
+/// assert_eq!(memory.get::<usize>("wood"), Some(&2));
+/// assert!(!memory.has_property("plank"));
+/// ```
 #[derive(Default)]
 pub struct Blackboard {
     properties: HashMap<String, Box<dyn Any + Send + Sync>>,
+    changes: BlackboardChanges,
+    journal: TransactionJournal<BlackboardUndo>,
+    cloners: HashMap<TypeId, BlackboardCloner>,
 }
 
+type BlackboardSubscriber = Box<dyn FnMut(&BlackboardChange) + Send + Sync>;
+
+type BlackboardCloner = fn(&dyn Any) -> Box<dyn Any + Send + Sync>;
+
+fn clone_property<T>(value: &dyn Any) -> Box<dyn Any + Send + Sync>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    Box::new(value.downcast_ref::<T>().unwrap().clone())
+}
+
+fn cloner_entry<T>() -> (TypeId, BlackboardCloner)
+where
+    T: Clone + Send + Sync + 'static,
+{
+    (TypeId::of::<T>(), clone_property::<T> as BlackboardCloner)
+}
+
+/// Cloners of types that every blackboard can clone, shared by all blackboards.
+static BUILTIN_CLONERS: LazyLock<HashMap<TypeId, BlackboardCloner>> = LazyLock::new(|| {
+    HashMap::from([
+        cloner_entry::<bool>(),
+        cloner_entry::<char>(),
+        cloner_entry::<i8>(),
+        cloner_entry::<i16>(),
+        cloner_entry::<i32>(),
+        cloner_entry::<i64>(),
+        cloner_entry::<i128>(),
+        cloner_entry::<isize>(),
+        cloner_entry::<u8>(),
+        cloner_entry::<u16>(),
+        cloner_entry::<u32>(),
+        cloner_entry::<u64>(),
+        cloner_entry::<u128>(),
+        cloner_entry::<usize>(),
+        cloner_entry::<f32>(),
+        cloner_entry::<f64>(),
+        cloner_entry::<String>(),
+    ])
+});
+
 impl std::fmt::Debug for Blackboard {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Blackboard")
             .field("properties", &self.properties.keys().collect::<Vec<_>>())
+            .field("version", &self.changes.version())
+            .field("transaction_depth", &self.journal.depth())
+            .finish()
+    }
+}
+
+/// Blackboard undo record, holds previous value of property.
+pub struct BlackboardUndo {
+    /// Name of changed property.
+    pub name: String,
+    /// Previous value of property (`None` means property did not existed).
+    pub value: Option<Box<dyn Any + Send + Sync>>,
+}
+
+impl std::fmt::Debug for BlackboardUndo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BlackboardUndo")
+            .field("name", &self.name)
+            .field("value", &self.value.is_some())
             .finish()
     }
 }
 
+/// Describes what kind of change happened to blackboard property.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BlackboardChangeKind {
+    /// Property got new value assigned.
+    Set,
+    /// Property was accessed for mutation in-place.
+    Modified,
+    /// Property was removed.
+    Removed,
+}
+
+/// Describes single change made to blackboard property, passed to blackboard subscribers.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BlackboardChange<'a> {
+    /// Name of changed property.
+    pub name: &'a str,
+    /// Kind of change.
+    pub kind: BlackboardChangeKind,
+    /// Blackboard version this change produced.
+    pub version: u64,
+}
+
+/// Handle of blackboard subscriber, used to unsubscribe from changes.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct BlackboardSubscription(usize);
+
+/// Versions of changed properties together with change subscribers.
+#[derive(Default)]
+pub(crate) struct BlackboardChanges {
+    versions: HashMap<String, u64>,
+    version: u64,
+    subscribers: Vec<(usize, BlackboardSubscriber)>,
+    subscribers_id_generator: usize,
+}
+
+impl BlackboardChanges {
+    pub(crate) fn version(&self) -> u64 {
+        self.version
+    }
+
+    pub(crate) fn property_version(&self, name: &str) -> Option<u64> {
+        self.versions.get(name).copied()
+    }
+
+    pub(crate) fn has_changed_since(&self, name: &str, version: u64) -> bool {
+        self.property_version(name)
+            .map(|v| v > version)
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn changed_since(&self, version: u64) -> impl Iterator<Item = &str> {
+        self.versions
+            .iter()
+            .filter(move |(_, v)| **v > version)
+            .map(|(k, _)| k.as_str())
+    }
+
+    pub(crate) fn subscribe<F>(&mut self, f: F) -> BlackboardSubscription
+    where
+        F: FnMut(&BlackboardChange) + 'static + Send + Sync,
+    {
+        let id = self.subscribers_id_generator;
+        self.subscribers_id_generator = self.subscribers_id_generator.wrapping_add(1);
+        self.subscribers.push((id, Box::new(f)));
+        BlackboardSubscription(id)
+    }
+
+    pub(crate) fn unsubscribe(&mut self, subscription: BlackboardSubscription) -> bool {
+        let count = self.subscribers.len();
+        self.subscribers.retain(|(id, _)| *id != subscription.0);
+        count != self.subscribers.len()
+    }
+
+    pub(crate) fn mark_changed(&mut self, name: &str, kind: BlackboardChangeKind) {
+        self.version += 1;
+        if let Some(version) = self.versions.get_mut(name) {
+            *version = self.version;
+        } else {
+            self.versions.insert(name.to_owned(), self.version);
+        }
+        let change = BlackboardChange {
+            name,
+            kind,
+            version: self.version,
+        };
+        for (_, subscriber) in &mut self.subscribers {
+            subscriber(&change);
+        }
+    }
+}
+
 impl Blackboard {
     /// Returns number of properties stored in blackboard.
     pub fn len(&self) -> usize {
@@ -102,10 +302,20 @@ impl Blackboard {
     }
 
     /// Returns mutable reference to property data.
+    ///
+    /// Since blackboard can't tell what will happen with returned reference, successful access
+    /// counts as property modification.
+    ///
+    /// During transaction returns `None` for property of type that is not cloneable (see
+    /// [`Self::is_cloneable`]), because its previous value could not be restored on rollback.
     pub fn get_mut<T>(&mut self, name: &str) -> Option<&mut T>
     where
         T: 'static,
     {
+        if !self.has_property_of_type::<T>(name) || !self.record_previous_value(name) {
+            return None;
+        }
+        self.mark_changed(name, BlackboardChangeKind::Modified);
         if let Some(value) = self.properties.get_mut(name) {
             return value.downcast_mut();
         }
@@ -121,7 +331,17 @@ impl Blackboard {
     }
 
     /// Returns mutable reference to property data as [`Any`].
+    ///
+    /// Since blackboard can't tell what will happen with returned reference, successful access
+    /// counts as property modification.
+    ///
+    /// During transaction returns `None` for property of type that is not cloneable (see
+    /// [`Self::is_cloneable`]), because its previous value could not be restored on rollback.
     pub fn raw_mut(&mut self, name: &str) -> Option<&mut dyn Any> {
+        if !self.has_property(name) || !self.record_previous_value(name) {
+            return None;
+        }
+        self.mark_changed(name, BlackboardChangeKind::Modified);
         if let Some(value) = self.properties.get_mut(name) {
             return Some(&mut *value);
         }
@@ -133,36 +353,193 @@ impl Blackboard {
     where
         T: Any + 'static + Send + Sync,
     {
-        self.properties.insert(name, Box::new(value));
+        self.set_raw(name, Box::new(value));
+    }
+
+    /// Put value to property under given name and register its type as cloneable, so it can be
+    /// mutated in-place during transaction.
+    ///
+    /// See [`Self::register_cloneable`].
+    pub fn set_cloneable<T>(&mut self, name: String, value: T)
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        self.register_cloneable::<T>();
+        self.set(name, value);
     }
 
     /// Put value to property under given name.
     pub fn set_raw(&mut self, name: String, value: Box<dyn Any + Send + Sync>) {
-        self.properties.insert(name, value);
+        self.mark_changed(&name, BlackboardChangeKind::Set);
+        let previous = self.properties.insert(name.clone(), value);
+        if self.journal.is_active() {
+            self.journal.record(BlackboardUndo {
+                name,
+                value: previous,
+            });
+        }
     }
 
     /// Mutate property data in-place with closure.
+    ///
+    /// During transaction closure is not called and `None` is returned for property of type that
+    /// is not cloneable (see [`Self::is_cloneable`]), because its previous value could not be
+    /// restored on rollback.
     pub fn with<T, R, F>(&mut self, name: &str, mut f: F) -> Option<R>
     where
         F: FnMut(&mut T) -> R,
         T: 'static,
     {
+        if !self.has_property_of_type::<T>(name) || !self.record_previous_value(name) {
+            return None;
+        }
         if let Some(value) = self.properties.get_mut(name)
             && let Some(value) = value.downcast_mut()
         {
-            return Some(f(value));
+            let result = f(value);
+            self.mark_changed(name, BlackboardChangeKind::Modified);
+            return Some(result);
         }
         None
     }
 
     /// Remove property under given name.
     pub fn remove(&mut self, name: &str) -> bool {
-        self.properties.remove(name).is_some()
+        if let Some(value) = self.properties.remove(name) {
+            self.mark_changed(name, BlackboardChangeKind::Removed);
+            if self.journal.is_active() {
+                self.journal.record(BlackboardUndo {
+                    name: name.to_owned(),
+                    value: Some(value),
+                });
+            }
+            return true;
+        }
+        false
     }
 
     /// Remove all properties in blackboard.
     pub fn clear(&mut self) {
-        self.properties.clear();
+        for (name, value) in std::mem::take(&mut self.properties) {
+            self.mark_changed(&name, BlackboardChangeKind::Removed);
+            if self.journal.is_active() {
+                self.journal.record(BlackboardUndo {
+                    name,
+                    value: Some(value),
+                });
+            }
+        }
+    }
+
+    /// Registers type that blackboard can clone to record its previous value when it gets mutated
+    /// in-place during active transaction.
+    pub fn register_cloneable<T>(&mut self)
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        let (type_id, cloner) = cloner_entry::<T>();
+        self.cloners.insert(type_id, cloner);
+    }
+
+    /// Tells if blackboard can record previous values of given type mutated in-place.
+    ///
+    /// Primitive types and [`String`] are always cloneable.
+    pub fn is_cloneable<T>(&self) -> bool
+    where
+        T: 'static,
+    {
+        self.cloner(TypeId::of::<T>()).is_some()
+    }
+
+    fn cloner(&self, type_id: TypeId) -> Option<BlackboardCloner> {
+        BUILTIN_CLONERS
+            .get(&type_id)
+            .or_else(|| self.cloners.get(&type_id))
+            .copied()
+    }
+
+    /// Returns current blackboard version.
+    ///
+    /// Version gets bumped on every property change, so it can be stored and later used to ask
+    /// what changed since then (see [`Self::changed_since`]).
+    pub fn version(&self) -> u64 {
+        self.changes.version()
+    }
+
+    /// Returns version of last change made to property under given name.
+    ///
+    /// Removed properties still report version of their removal.
+    pub fn property_version(&self, name: &str) -> Option<u64> {
+        self.changes.property_version(name)
+    }
+
+    /// Tells if property under given name has changed (or got removed) since given version.
+    pub fn has_changed_since(&self, name: &str, version: u64) -> bool {
+        self.changes.has_changed_since(name, version)
+    }
+
+    /// Return iterator over keys of properties changed (or removed) since given version.
+    pub fn changed_since(&self, version: u64) -> impl Iterator<Item = &str> {
+        self.changes.changed_since(version)
+    }
+
+    /// Registers callback called on every property change.
+    ///
+    /// Returns subscription handle that can be used to unsubscribe.
+    pub fn subscribe<F>(&mut self, f: F) -> BlackboardSubscription
+    where
+        F: FnMut(&BlackboardChange) + 'static + Send + Sync,
+    {
+        self.changes.subscribe(f)
+    }
+
+    /// Removes subscriber callback.
+    ///
+    /// Returns `true` if subscriber was found and removed.
+    pub fn unsubscribe(&mut self, subscription: BlackboardSubscription) -> bool {
+        self.changes.unsubscribe(subscription)
+    }
+
+    fn apply_undos(&mut self, undos: Vec<BlackboardUndo>) {
+        for undo in undos {
+            match undo.value {
+                Some(value) => {
+                    self.mark_changed(&undo.name, BlackboardChangeKind::Set);
+                    self.properties.insert(undo.name, value);
+                }
+                None => {
+                    if self.properties.remove(&undo.name).is_some() {
+                        self.mark_changed(&undo.name, BlackboardChangeKind::Removed);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Records previous value of property about to be mutated in-place during transaction.
+    ///
+    /// Returns `false` if property can't be mutated, because its value can't be recorded.
+    fn record_previous_value(&mut self, name: &str) -> bool {
+        if !self.journal.is_active() {
+            return true;
+        }
+        let Some(value) = self.properties.get(name) else {
+            return true;
+        };
+        let value: &dyn Any = value.as_ref();
+        let Some(cloner) = self.cloner(value.type_id()) else {
+            return false;
+        };
+        let value = cloner(value);
+        self.journal.record(BlackboardUndo {
+            name: name.to_owned(),
+            value: Some(value),
+        });
+        true
+    }
+
+    fn mark_changed(&mut self, name: &str, kind: BlackboardChangeKind) {
+        self.changes.mark_changed(name, kind);
     }
 
     /// Return iterator over properties keys.
@@ -186,3 +563,217 @@ impl Blackboard {
         })
     }
 }
+
+/// Common access to named properties shared by [`Blackboard`] and
+/// [`ScopedBlackboard`](crate::memory::scoped_blackboard::ScopedBlackboard).
+///
+/// Conditions, considerations and tasks written against this trait instead of concrete blackboard
+/// type work with both blackboard kinds, so switching agent memory to scoped blackboard does not
+/// require rewriting them.
+///
+/// # Example
+/// ```
+/// use emergent::prelude::*;
+///
+/// fn is_alarm<B: BlackboardAccess>(memory: &B) -> bool {
+///     memory.get::<bool>("alarm").copied().unwrap_or_default()
+/// }
+///
+/// let mut memory = Blackboard::default();
+/// memory.set("alarm".to_owned(), true);
+/// assert!(ClosureCondition::new(is_alarm::<Blackboard>).validate(&memory));
+///
+/// let memory = ScopedBlackboard::default()
+///     .scope("global", memory)
+///     .scope("agent", Blackboard::default());
+/// assert!(ClosureCondition::new(is_alarm::<ScopedBlackboard>).validate(&memory));
+/// ```
+pub trait BlackboardAccess {
+    /// Returns number of properties.
+    fn len(&self) -> usize;
+
+    /// Tells if there are no properties.
+    fn is_empty(&self) -> bool;
+
+    /// Tells if property with given name exists.
+    fn has_property(&self, name: &str) -> bool;
+
+    /// Tells if property with given name exists and is of given type.
+    fn has_property_of_type<T>(&self, name: &str) -> bool
+    where
+        T: 'static;
+
+    /// Returns type ID of property with given name.
+    fn type_id(&self, name: &str) -> Option<TypeId>;
+
+    /// Get reference to property data of given type.
+    fn get<T>(&self, name: &str) -> Option<&T>
+    where
+        T: 'static;
+
+    /// Get mutable reference to property data of given type.
+    fn get_mut<T>(&mut self, name: &str) -> Option<&mut T>
+    where
+        T: 'static;
+
+    /// Get reference to raw property data.
+    fn raw(&self, name: &str) -> Option<&dyn Any>;
+
+    /// Get mutable reference to raw property data.
+    fn raw_mut(&mut self, name: &str) -> Option<&mut dyn Any>;
+
+    /// Set property data of given type.
+    fn set<T>(&mut self, name: String, value: T)
+    where
+        T: Any + 'static + Send + Sync;
+
+    /// Set raw property data.
+    fn set_raw(&mut self, name: String, value: Box<dyn Any + Send + Sync>);
+
+    /// Mutate property data in-place with closure.
+    fn with<T, R, F>(&mut self, name: &str, f: F) -> Option<R>
+    where
+        F: FnMut(&mut T) -> R,
+        T: 'static;
+
+    /// Remove property with given name.
+    fn remove(&mut self, name: &str) -> bool;
+
+    /// Returns current change version.
+    fn version(&self) -> u64;
+
+    /// Returns version at which property with given name was last changed.
+    fn property_version(&self, name: &str) -> Option<u64>;
+
+    /// Tells if property with given name has changed since given version.
+    fn has_changed_since(&self, name: &str, version: u64) -> bool;
+}
+
+impl BlackboardAccess for Blackboard {
+    fn len(&self) -> usize {
+        Blackboard::len(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        Blackboard::is_empty(self)
+    }
+
+    fn has_property(&self, name: &str) -> bool {
+        Blackboard::has_property(self, name)
+    }
+
+    fn has_property_of_type<T>(&self, name: &str) -> bool
+    where
+        T: 'static,
+    {
+        Blackboard::has_property_of_type::<T>(self, name)
+    }
+
+    fn type_id(&self, name: &str) -> Option<TypeId> {
+        Blackboard::type_id(self, name)
+    }
+
+    fn get<T>(&self, name: &str) -> Option<&T>
+    where
+        T: 'static,
+    {
+        Blackboard::get(self, name)
+    }
+
+    fn get_mut<T>(&mut self, name: &str) -> Option<&mut T>
+    where
+        T: 'static,
+    {
+        Blackboard::get_mut(self, name)
+    }
+
+    fn raw(&self, name: &str) -> Option<&dyn Any> {
+        Blackboard::raw(self, name)
+    }
+
+    fn raw_mut(&mut self, name: &str) -> Option<&mut dyn Any> {
+        Blackboard::raw_mut(self, name)
+    }
+
+    fn set<T>(&mut self, name: String, value: T)
+    where
+        T: Any + 'static + Send + Sync,
+    {
+        Blackboard::set(self, name, value)
+    }
+
+    fn set_raw(&mut self, name: String, value: Box<dyn Any + Send + Sync>) {
+        Blackboard::set_raw(self, name, value)
+    }
+
+    fn with<T, R, F>(&mut self, name: &str, f: F) -> Option<R>
+    where
+        F: FnMut(&mut T) -> R,
+        T: 'static,
+    {
+        Blackboard::with(self, name, f)
+    }
+
+    fn remove(&mut self, name: &str) -> bool {
+        Blackboard::remove(self, name)
+    }
+
+    fn version(&self) -> u64 {
+        Blackboard::version(self)
+    }
+
+    fn property_version(&self, name: &str) -> Option<u64> {
+        Blackboard::property_version(self, name)
+    }
+
+    fn has_changed_since(&self, name: &str, version: u64) -> bool {
+        Blackboard::has_changed_since(self, name, version)
+    }
+}
+
+impl TransactionalMemory for Blackboard {
+    type Undo = BlackboardUndo;
+
+    fn begin_transaction(&mut self) {
+        self.journal.begin();
+    }
+
+    fn commit_transaction(&mut self, policy: TransactionCommitPolicy) {
+        self.journal.commit(policy);
+    }
+
+    fn rollback_transaction(&mut self) {
+        let undos = self.journal.rollback().collect::<Vec<_>>();
+        self.apply_undos(undos);
+    }
+
+    fn record_undo(&mut self, undo: Self::Undo) {
+        self.journal.record(undo);
+    }
+
+    fn savepoint_transaction(&mut self, name: &str) -> bool {
+        self.journal.savepoint(name)
+    }
+
+    fn rollback_to_savepoint(&mut self, name: &str) -> bool {
+        if !self.journal.has_savepoint(name) {
+            return false;
+        }
+        let undos = self.journal.rollback_to_savepoint(name).collect::<Vec<_>>();
+        self.apply_undos(undos);
+        true
+    }
+
+    fn release_savepoint(&mut self, name: &str) -> bool {
+        self.journal.release_savepoint(name)
+    }
+
+    fn transaction_depth(&self) -> usize {
+        self.journal.depth()
+    }
+
+    fn rollback_all_transactions(&mut self) {
+        let undos = self.journal.rollback_all().collect::<Vec<_>>();
+        self.apply_undos(undos);
+    }
+}
@@ -1,4 +1,13 @@
-use std::collections::HashMap;
+use crate::{
+    Scalar, compare_scores,
+    condition::*,
+    consideration::*,
+    task::{TransactionCommitPolicy, TransactionJournal, TransactionalMemory},
+};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
 /// DataTable holds set of rows of given type assigned to keys/names.
 ///
@@ -36,11 +45,66 @@ use std::collections::HashMap;
 /// assert!(memory.remove("tuple"));
 /// assert_eq!(memory.len(), 0);
 /// ```
+///
+/// # Transactions
+/// When rows are [`Clone`], datatable implements [`TransactionalMemory`], so it can be used with
+/// [`JournaledTransactionTask`](crate::task::JournaledTransactionTask) directly - while transaction
+/// is active, previous values of changed rows are recorded and restored on rollback.
+///
+/// ```
+/// use emergent::prelude::*;
+///
+/// let mut memory = DataTable::<usize>::default();
+/// memory.set("wood".to_owned(), 2);
+///
+/// memory.begin_transaction();
+/// memory.with("wood", |v| *v -= 1);
+/// memory.set("plank".to_owned(), 1);
+/// memory.rollback_transaction();
+///
+/// assert_eq!(memory.get("wood"), Some(&2));
+/// assert!(!memory.has_row("plank"));
+/// ```
+///
+/// # Queries
+/// Rows can be filtered by predicate, iterated in order of their names or scores, and looked up
+/// by secondary indices built from keys derived from row data.
+///
+/// ```
+/// use emergent::prelude::*;
+///
+/// #[derive(Debug, Clone, PartialEq)]
+/// struct Item {
+///     kind: &'static str,
+///     value: Scalar,
+/// }
+///
+/// let mut memory = DataTable::default();
+/// memory.add_index("kind", |item: &Item| item.kind.to_owned());
+/// memory.set("sword".to_owned(), Item { kind: "weapon", value: 10.0 });
+/// memory.set("axe".to_owned(), Item { kind: "weapon", value: 7.0 });
+/// memory.set("bread".to_owned(), Item { kind: "food", value: 1.0 });
+///
+/// assert!(memory.any(|item| item.kind == "food"));
+/// assert_eq!(memory.filter(|item| item.value > 5.0).count(), 2);
+/// assert_eq!(
+///     memory.iter_sorted().map(|(name, _)| name).collect::<Vec<_>>(),
+///     vec!["axe", "bread", "sword"],
+/// );
+/// assert_eq!(memory.best_by_score(|item| item.value).unwrap().0, "sword");
+/// let mut weapons = memory.indexed("kind", "weapon").map(|(name, _)| name).collect::<Vec<_>>();
+/// weapons.sort();
+/// assert_eq!(weapons, vec!["axe", "sword"]);
+/// ```
 pub struct DataTable<T>
 where
     T: Send + Sync,
 {
     rows: HashMap<String, T>,
+    journal: TransactionJournal<DataTableUndo<T>>,
+    cloner: Option<fn(&T) -> T>,
+    indices: HashMap<String, DataTableIndex<T>>,
+    stale_rows: HashSet<String>,
 }
 
 impl<T> Default for DataTable<T>
@@ -50,10 +114,63 @@ where
     fn default() -> Self {
         Self {
             rows: Default::default(),
+            journal: Default::default(),
+            cloner: None,
+            indices: Default::default(),
+            stale_rows: Default::default(),
+        }
+    }
+}
+
+type DataTableIndexKey<T> = Arc<dyn Fn(&T) -> String + Send + Sync>;
+
+struct DataTableIndex<T> {
+    key: DataTableIndexKey<T>,
+    rows: HashMap<String, HashSet<String>>,
+    row_keys: HashMap<String, String>,
+}
+
+impl<T> Clone for DataTableIndex<T> {
+    fn clone(&self) -> Self {
+        Self {
+            key: self.key.clone(),
+            rows: self.rows.clone(),
+            row_keys: self.row_keys.clone(),
+        }
+    }
+}
+
+impl<T> DataTableIndex<T> {
+    fn insert(&mut self, name: &str, value: &T) {
+        let key = (self.key)(value);
+        self.rows
+            .entry(key.clone())
+            .or_default()
+            .insert(name.to_owned());
+        self.row_keys.insert(name.to_owned(), key);
+    }
+
+    fn remove(&mut self, name: &str) {
+        if let Some(key) = self.row_keys.remove(name)
+            && let Some(rows) = self.rows.get_mut(&key)
+        {
+            rows.remove(name);
+            if rows.is_empty() {
+                self.rows.remove(&key);
+            }
         }
     }
 }
 
+/// Datatable undo record, holds previous value of row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataTableUndo<T> {
+    /// Name of changed row.
+    pub name: String,
+    /// Previous value of row (`None` means row did not existed).
+    pub value: Option<T>,
+}
+
 impl<T> std::fmt::Debug for DataTable<T>
 where
     T: std::fmt::Debug + Send + Sync,
@@ -61,6 +178,7 @@ where
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("DataTable")
             .field("rows", &self.rows)
+            .field("indices", &self.indices.keys().collect::<Vec<_>>())
             .finish()
     }
 }
@@ -72,6 +190,10 @@ where
     fn clone(&self) -> Self {
         Self {
             rows: self.rows.clone(),
+            journal: self.journal.clone(),
+            cloner: self.cloner,
+            indices: self.indices.clone(),
+            stale_rows: self.stale_rows.clone(),
         }
     }
 }
@@ -101,13 +223,28 @@ where
     }
 
     /// Returns mutable reference to row data.
+    ///
+    /// Since datatable can't tell what will happen with returned reference, indices of this row
+    /// get refreshed on next datatable change (queries see its up-to-date state anyway).
     pub fn get_mut(&mut self, name: &str) -> Option<&mut T> {
+        self.refresh_indices();
+        self.record_previous_value(name);
+        if !self.indices.is_empty() && self.rows.contains_key(name) {
+            self.stale_rows.insert(name.to_owned());
+        }
         self.rows.get_mut(name)
     }
 
     /// Put value to row under given name.
     pub fn set(&mut self, name: String, value: T) {
-        self.rows.insert(name, value);
+        self.refresh_indices();
+        let previous = self.insert_row(name.clone(), value);
+        if self.journal.is_active() {
+            self.journal.record(DataTableUndo {
+                name,
+                value: previous,
+            });
+        }
     }
 
     /// Mutate row data in-place with closure.
@@ -115,20 +252,153 @@ where
     where
         F: FnMut(&mut T) -> R,
     {
+        self.refresh_indices();
+        self.record_previous_value(name);
         if let Some(value) = self.rows.get_mut(name) {
-            return Some(f(value));
+            let result = f(value);
+            self.reindex_row(name);
+            return Some(result);
         }
         None
     }
 
     /// Remove row under given name.
     pub fn remove(&mut self, name: &str) -> bool {
-        self.rows.remove(name).is_some()
+        self.refresh_indices();
+        if let Some(value) = self.remove_row(name) {
+            if self.journal.is_active() {
+                self.journal.record(DataTableUndo {
+                    name: name.to_owned(),
+                    value: Some(value),
+                });
+            }
+            return true;
+        }
+        false
     }
 
     /// Remove all rows in datatable.
     pub fn clear(&mut self) {
-        self.rows.clear();
+        self.stale_rows.clear();
+        for index in self.indices.values_mut() {
+            index.rows.clear();
+            index.row_keys.clear();
+        }
+        if self.journal.is_active() {
+            for (name, value) in std::mem::take(&mut self.rows) {
+                self.journal.record(DataTableUndo {
+                    name,
+                    value: Some(value),
+                });
+            }
+        } else {
+            self.rows.clear();
+        }
+    }
+
+    /// Adds secondary index with given name, that groups rows by key derived from row data.
+    ///
+    /// Index with the same name gets replaced.
+    pub fn add_index<F>(&mut self, name: impl ToString, key: F)
+    where
+        F: Fn(&T) -> String + 'static + Send + Sync,
+    {
+        self.refresh_indices();
+        let mut index = DataTableIndex {
+            key: Arc::new(key),
+            rows: Default::default(),
+            row_keys: Default::default(),
+        };
+        for (name, value) in &self.rows {
+            index.insert(name, value);
+        }
+        self.indices.insert(name.to_string(), index);
+    }
+
+    /// Removes secondary index with given name.
+    pub fn remove_index(&mut self, name: &str) -> bool {
+        self.indices.remove(name).is_some()
+    }
+
+    /// Tells if there is secondary index with given name.
+    pub fn has_index(&self, name: &str) -> bool {
+        self.indices.contains_key(name)
+    }
+
+    /// Return iterator over rows that have given key in secondary index with given name.
+    ///
+    /// Yields nothing when there is no such index.
+    pub fn indexed<'a>(&'a self, index: &str, key: &str) -> impl Iterator<Item = (&'a str, &'a T)> {
+        let mut result = Vec::new();
+        if let Some(index) = self.indices.get(index) {
+            if let Some(rows) = index.rows.get(key) {
+                result.extend(
+                    rows.iter()
+                        .filter(|name| !self.stale_rows.contains(*name))
+                        .filter_map(|name| self.rows.get_key_value(name)),
+                );
+            }
+            result.extend(
+                self.stale_rows
+                    .iter()
+                    .filter_map(|name| self.rows.get_key_value(name))
+                    .filter(|(_, value)| (index.key)(value) == key),
+            );
+        }
+        result.into_iter().map(|(k, v)| (k.as_str(), v))
+    }
+
+    /// Return iterator over rows keys and their values that match given predicate.
+    pub fn filter<F>(&self, f: F) -> impl Iterator<Item = (&str, &T)>
+    where
+        F: Fn(&T) -> bool,
+    {
+        self.iter().filter(move |(_, value)| f(value))
+    }
+
+    /// Tells if any row matches given predicate.
+    pub fn any<F>(&self, f: F) -> bool
+    where
+        F: Fn(&T) -> bool,
+    {
+        self.rows.values().any(f)
+    }
+
+    /// Tells if all rows match given predicate.
+    pub fn all<F>(&self, f: F) -> bool
+    where
+        F: Fn(&T) -> bool,
+    {
+        self.rows.values().all(f)
+    }
+
+    /// Returns row with the highest score, along with its name.
+    pub fn best_by_score<F>(&self, f: F) -> Option<(&str, &T)>
+    where
+        F: Fn(&T) -> Scalar,
+    {
+        self.iter_sorted_by_score(f).next()
+    }
+
+    /// Return iterator over rows keys and their values, ordered by rows keys.
+    pub fn iter_sorted(&self) -> impl Iterator<Item = (&str, &T)> {
+        let mut result = self.iter().collect::<Vec<_>>();
+        result.sort_by_key(|(key, _)| *key);
+        result.into_iter()
+    }
+
+    /// Return iterator over rows keys and their values, ordered from the highest to the lowest
+    /// score (rows with equal scores are ordered by their keys). NaN scores are the lowest.
+    pub fn iter_sorted_by_score<F>(&self, f: F) -> impl Iterator<Item = (&str, &T)>
+    where
+        F: Fn(&T) -> Scalar,
+    {
+        let mut result = self
+            .iter()
+            .map(|(key, value)| (key, value, f(value)))
+            .collect::<Vec<_>>();
+        result.sort_by(|(ak, _, a), (bk, _, b)| compare_scores(*b, *a).then_with(|| ak.cmp(bk)));
+        result.into_iter().map(|(key, value, _)| (key, value))
     }
 
     /// Return iterator over rows keys.
@@ -145,4 +415,357 @@ where
     pub fn iter(&self) -> impl Iterator<Item = (&str, &T)> {
         self.rows.iter().map(|(k, v)| (k.as_str(), v))
     }
+
+    fn apply_undos(&mut self, undos: Vec<DataTableUndo<T>>) {
+        self.refresh_indices();
+        for undo in undos {
+            match undo.value {
+                Some(value) => {
+                    self.insert_row(undo.name, value);
+                }
+                None => {
+                    self.remove_row(&undo.name);
+                }
+            }
+        }
+    }
+
+    fn insert_row(&mut self, name: String, value: T) -> Option<T> {
+        for index in self.indices.values_mut() {
+            index.remove(&name);
+            index.insert(&name, &value);
+        }
+        self.rows.insert(name, value)
+    }
+
+    fn remove_row(&mut self, name: &str) -> Option<T> {
+        for index in self.indices.values_mut() {
+            index.remove(name);
+        }
+        self.rows.remove(name)
+    }
+
+    fn reindex_row(&mut self, name: &str) {
+        if let Some(value) = self.rows.get(name) {
+            for index in self.indices.values_mut() {
+                index.remove(name);
+                index.insert(name, value);
+            }
+        }
+    }
+
+    fn refresh_indices(&mut self) {
+        for name in std::mem::take(&mut self.stale_rows) {
+            self.reindex_row(&name);
+        }
+    }
+
+    fn record_previous_value(&mut self, name: &str) {
+        if !self.journal.is_active() {
+            return;
+        }
+        if let Some(cloner) = self.cloner
+            && let Some(value) = self.rows.get(name)
+        {
+            self.journal.record(DataTableUndo {
+                name: name.to_owned(),
+                value: Some(cloner(value)),
+            });
+        }
+    }
+}
+
+impl<T> TransactionalMemory for DataTable<T>
+where
+    T: Clone + Send + Sync,
+{
+    type Undo = DataTableUndo<T>;
+
+    fn begin_transaction(&mut self) {
+        self.cloner = Some(T::clone);
+        self.journal.begin();
+    }
+
+    fn commit_transaction(&mut self, policy: TransactionCommitPolicy) {
+        self.journal.commit(policy);
+    }
+
+    fn rollback_transaction(&mut self) {
+        let undos = self.journal.rollback().collect::<Vec<_>>();
+        self.apply_undos(undos);
+    }
+
+    fn record_undo(&mut self, undo: Self::Undo) {
+        self.journal.record(undo);
+    }
+
+    fn savepoint_transaction(&mut self, name: &str) -> bool {
+        self.journal.savepoint(name)
+    }
+
+    fn rollback_to_savepoint(&mut self, name: &str) -> bool {
+        if !self.journal.has_savepoint(name) {
+            return false;
+        }
+        let undos = self.journal.rollback_to_savepoint(name).collect::<Vec<_>>();
+        self.apply_undos(undos);
+        true
+    }
+
+    fn release_savepoint(&mut self, name: &str) -> bool {
+        self.journal.release_savepoint(name)
+    }
+
+    fn transaction_depth(&self) -> usize {
+        self.journal.depth()
+    }
+
+    fn rollback_all_transactions(&mut self) {
+        let undos = self.journal.rollback_all().collect::<Vec<_>>();
+        self.apply_undos(undos);
+    }
+}
+
+/// Condition that succeeds if any row of datatable found in memory matches predicate.
+///
+/// # Example
+/// ```
+/// use emergent::prelude::*;
+///
+/// struct Memory {
+///     items: DataTable<usize>,
+/// }
+///
+/// let mut memory = Memory { items: DataTable::default() };
+/// memory.items.set("coal".to_owned(), 0);
+/// let condition = DataTableCondition::new(|m: &Memory| &m.items, |count| *count > 0);
+/// assert!(!condition.validate(&memory));
+/// memory.items.set("ore".to_owned(), 2);
+/// assert!(condition.validate(&memory));
+/// ```
+#[allow(clippy::type_complexity)]
+pub struct DataTableCondition<M, T>
+where
+    T: Send + Sync,
+{
+    table: Box<dyn Fn(&M) -> &DataTable<T> + Send + Sync>,
+    predicate: Box<dyn Fn(&T) -> bool + Send + Sync>,
+}
+
+impl<M, T> DataTableCondition<M, T>
+where
+    T: Send + Sync,
+{
+    /// Constructs new condition with function that gets datatable from memory and row predicate.
+    pub fn new<FT, FP>(table: FT, predicate: FP) -> Self
+    where
+        FT: Fn(&M) -> &DataTable<T> + 'static + Send + Sync,
+        FP: Fn(&T) -> bool + 'static + Send + Sync,
+    {
+        Self {
+            table: Box::new(table),
+            predicate: Box::new(predicate),
+        }
+    }
+}
+
+impl<M, T> Condition<M> for DataTableCondition<M, T>
+where
+    T: Send + Sync,
+{
+    fn validate(&self, memory: &M) -> bool {
+        (self.table)(memory).any(&self.predicate)
+    }
+}
+
+impl<M, T> std::fmt::Debug for DataTableCondition<M, T>
+where
+    T: Send + Sync,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DataTableCondition").finish()
+    }
+}
+
+/// Consideration that gives the best score of rows of datatable found in memory.
+///
+/// Empty datatable gives score of 0.
+///
+/// # Example
+/// ```
+/// use emergent::prelude::*;
+///
+/// struct Memory {
+///     recipes: DataTable<Scalar>,
+/// }
+///
+/// let mut memory = Memory { recipes: DataTable::default() };
+/// memory.recipes.set("sword".to_owned(), 0.8);
+/// memory.recipes.set("bread".to_owned(), 0.3);
+/// let consideration = DataTableConsideration::new(|m: &Memory| &m.recipes, |value| *value);
+/// assert_eq!(consideration.score(&memory), 0.8);
+/// ```
+#[allow(clippy::type_complexity)]
+pub struct DataTableConsideration<M, T>
+where
+    T: Send + Sync,
+{
+    table: Box<dyn Fn(&M) -> &DataTable<T> + Send + Sync>,
+    score: Box<dyn Fn(&T) -> Scalar + Send + Sync>,
+}
+
+impl<M, T> DataTableConsideration<M, T>
+where
+    T: Send + Sync,
+{
+    /// Constructs new consideration with function that gets datatable from memory and row score.
+    pub fn new<FT, FS>(table: FT, score: FS) -> Self
+    where
+        FT: Fn(&M) -> &DataTable<T> + 'static + Send + Sync,
+        FS: Fn(&T) -> Scalar + 'static + Send + Sync,
+    {
+        Self {
+            table: Box::new(table),
+            score: Box::new(score),
+        }
+    }
+}
+
+impl<M, T> Consideration<M> for DataTableConsideration<M, T>
+where
+    T: Send + Sync,
+{
+    fn score(&self, memory: &M) -> Scalar {
+        (self.table)(memory)
+            .values()
+            .map(|value| (self.score)(value))
+            .max_by(|a, b| compare_scores(*a, *b))
+            .unwrap_or_default()
+    }
+}
+
+impl<M, T> std::fmt::Debug for DataTableConsideration<M, T>
+where
+    T: Send + Sync,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DataTableConsideration").finish()
+    }
+}
+
+/// Datatable import error.
+#[cfg(any(feature = "json", feature = "csv"))]
+#[derive(Debug)]
+pub enum DataTableImportError {
+    /// Could not parse JSON data.
+    #[cfg(feature = "json")]
+    Json(serde_json::Error),
+    /// Could not parse CSV data.
+    #[cfg(feature = "csv")]
+    Csv(csv::Error),
+    /// There is no column with given name in CSV data.
+    #[cfg(feature = "csv")]
+    KeyColumnDoesNotExists(String),
+}
+
+#[cfg(any(feature = "json", feature = "csv"))]
+impl std::fmt::Display for DataTableImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            #[cfg(feature = "json")]
+            Self::Json(error) => write!(f, "Could not parse JSON data: {}", error),
+            #[cfg(feature = "csv")]
+            Self::Csv(error) => write!(f, "Could not parse CSV data: {}", error),
+            #[cfg(feature = "csv")]
+            Self::KeyColumnDoesNotExists(name) => {
+                write!(f, "There is no column `{}` in CSV data", name)
+            }
+        }
+    }
+}
+
+#[cfg(any(feature = "json", feature = "csv"))]
+impl std::error::Error for DataTableImportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            #[cfg(feature = "json")]
+            Self::Json(error) => Some(error),
+            #[cfg(feature = "csv")]
+            Self::Csv(error) => Some(error),
+            #[cfg(feature = "csv")]
+            Self::KeyColumnDoesNotExists(_) => None,
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl<T> DataTable<T>
+where
+    T: serde::de::DeserializeOwned + Send + Sync,
+{
+    /// Constructs new datatable from JSON object where each field is a row.
+    pub fn from_json(json: &str) -> Result<Self, DataTableImportError> {
+        let mut result = Self::default();
+        result.import_json(json)?;
+        Ok(result)
+    }
+
+    /// Puts rows from JSON object where each field is a row, in order of rows keys.
+    ///
+    /// Returns number of imported rows.
+    pub fn import_json(&mut self, json: &str) -> Result<usize, DataTableImportError> {
+        let rows = serde_json::from_str::<std::collections::BTreeMap<String, T>>(json)
+            .map_err(DataTableImportError::Json)?;
+        let count = rows.len();
+        for (name, value) in rows {
+            self.set(name, value);
+        }
+        Ok(count)
+    }
+}
+
+#[cfg(feature = "csv")]
+impl<T> DataTable<T>
+where
+    T: serde::de::DeserializeOwned + Send + Sync,
+{
+    /// Constructs new datatable from CSV data with headers, where each record is a row and its
+    /// key is read from given column.
+    pub fn from_csv(csv: &str, key_column: &str) -> Result<Self, DataTableImportError> {
+        let mut result = Self::default();
+        result.import_csv(csv, key_column)?;
+        Ok(result)
+    }
+
+    /// Puts rows from CSV data with headers, where each record is a row and its key is read from
+    /// given column. Rows are put in order of records.
+    ///
+    /// Returns number of imported rows.
+    pub fn import_csv(
+        &mut self,
+        csv: &str,
+        key_column: &str,
+    ) -> Result<usize, DataTableImportError> {
+        let mut reader = csv::Reader::from_reader(csv.as_bytes());
+        let headers = reader.headers().map_err(DataTableImportError::Csv)?.clone();
+        let Some(key_index) = headers.iter().position(|header| header == key_column) else {
+            return Err(DataTableImportError::KeyColumnDoesNotExists(
+                key_column.to_owned(),
+            ));
+        };
+        let mut rows = Vec::new();
+        for record in reader.records() {
+            let record = record.map_err(DataTableImportError::Csv)?;
+            let name = record.get(key_index).unwrap_or_default().to_owned();
+            let value = record
+                .deserialize::<T>(Some(&headers))
+                .map_err(DataTableImportError::Csv)?;
+            rows.push((name, value));
+        }
+        let count = rows.len();
+        for (name, value) in rows {
+            self.set(name, value);
+        }
+        Ok(count)
+    }
 }
@@ -0,0 +1,356 @@
+use crate::{Scalar, condition::*, consideration::*};
+
+/// Minimal interface of ECS world that entity memory can work with.
+///
+/// This trait only tells what type identifies entities, access to components is provided per
+/// component type with [`ReadComponent`] and [`WriteComponent`], so every ECS can put its own
+/// bounds on component types (hecs, bevy_ecs or custom ECS can implement these for their worlds).
+pub trait EntityWorld {
+    /// Entity identifier type.
+    type Entity: Copy;
+}
+
+/// Read access to components of given type stored in ECS world.
+pub trait ReadComponent<C>: EntityWorld {
+    /// Calls function with component of entity and returns its result, or `None` if entity does
+    /// not have that component.
+    fn read_component<R>(&self, entity: Self::Entity, f: impl FnOnce(&C) -> R) -> Option<R>;
+}
+
+/// Write access to components of given type stored in ECS world.
+pub trait WriteComponent<C>: ReadComponent<C> {
+    /// Calls function with mutable component of entity and returns its result, or `None` if entity
+    /// does not have that component.
+    fn write_component<R>(
+        &mut self,
+        entity: Self::Entity,
+        f: impl FnOnce(&mut C) -> R,
+    ) -> Option<R>;
+}
+
+impl<W> EntityWorld for &W
+where
+    W: EntityWorld,
+{
+    type Entity = W::Entity;
+}
+
+impl<W> EntityWorld for &mut W
+where
+    W: EntityWorld,
+{
+    type Entity = W::Entity;
+}
+
+impl<W, C> ReadComponent<C> for &W
+where
+    W: ReadComponent<C>,
+{
+    fn read_component<R>(&self, entity: Self::Entity, f: impl FnOnce(&C) -> R) -> Option<R> {
+        (**self).read_component(entity, f)
+    }
+}
+
+impl<W, C> ReadComponent<C> for &mut W
+where
+    W: ReadComponent<C>,
+{
+    fn read_component<R>(&self, entity: Self::Entity, f: impl FnOnce(&C) -> R) -> Option<R> {
+        (**self).read_component(entity, f)
+    }
+}
+
+impl<W, C> WriteComponent<C> for &mut W
+where
+    W: WriteComponent<C>,
+{
+    fn write_component<R>(
+        &mut self,
+        entity: Self::Entity,
+        f: impl FnOnce(&mut C) -> R,
+    ) -> Option<R> {
+        (**self).write_component(entity, f)
+    }
+}
+
+/// Memory that views single entity of ECS world.
+///
+/// World is usually borrowed (`EntityMemory<&World>` for conditions and considerations only, or
+/// `EntityMemory<&mut World>` when tasks write components), but since decision makers are typed by
+/// their memory, long-lived decision makers can also use world handle or world moved into memory
+/// for the time of decision making.
+///
+/// # Example
+/// ```
+/// use emergent::prelude::*;
+/// use std::collections::HashMap;
+///
+/// struct Health(Scalar);
+///
+/// #[derive(Default)]
+/// struct World {
+///     health: HashMap<usize, Health>,
+/// }
+///
+/// impl EntityWorld for World {
+///     type Entity = usize;
+/// }
+///
+/// impl ReadComponent<Health> for World {
+///     fn read_component<R>(&self, entity: usize, f: impl FnOnce(&Health) -> R) -> Option<R> {
+///         self.health.get(&entity).map(f)
+///     }
+/// }
+///
+/// impl WriteComponent<Health> for World {
+///     fn write_component<R>(
+///         &mut self,
+///         entity: usize,
+///         f: impl FnOnce(&mut Health) -> R,
+///     ) -> Option<R> {
+///         self.health.get_mut(&entity).map(f)
+///     }
+/// }
+///
+/// let mut world = World::default();
+/// world.health.insert(0, Health(0.2));
+///
+/// let condition = ComponentCondition::new(|health: &Health| health.0 < 0.5);
+/// let mut heal = ClosureTask::default().enter(|m: &mut EntityMemory<&mut World>| {
+///     m.component_mut(|health: &mut Health| health.0 = 1.0);
+/// });
+///
+/// let mut memory = EntityMemory::new(&mut world, 0);
+/// assert!(condition.validate(&memory));
+/// heal.on_enter(&mut memory);
+/// assert!(!condition.validate(&memory));
+/// assert_eq!(memory.world.health[&0].0, 1.0);
+/// ```
+pub struct EntityMemory<W>
+where
+    W: EntityWorld,
+{
+    /// Viewed ECS world (usually reference to it).
+    pub world: W,
+    /// Entity that decision maker works for.
+    pub entity: W::Entity,
+}
+
+impl<W> EntityMemory<W>
+where
+    W: EntityWorld,
+{
+    /// Constructs new entity memory.
+    pub fn new(world: W, entity: W::Entity) -> Self {
+        Self { world, entity }
+    }
+
+    /// Gives back viewed world.
+    pub fn into_world(self) -> W {
+        self.world
+    }
+
+    /// Calls function with component of viewed entity and returns its result.
+    pub fn component<C, R>(&self, f: impl FnOnce(&C) -> R) -> Option<R>
+    where
+        W: ReadComponent<C>,
+    {
+        self.world.read_component(self.entity, f)
+    }
+
+    /// Calls function with mutable component of viewed entity and returns its result.
+    pub fn component_mut<C, R>(&mut self, f: impl FnOnce(&mut C) -> R) -> Option<R>
+    where
+        W: WriteComponent<C>,
+    {
+        self.world.write_component(self.entity, f)
+    }
+
+    /// Returns copy of component of viewed entity.
+    pub fn get<C>(&self) -> Option<C>
+    where
+        W: ReadComponent<C>,
+        C: Clone,
+    {
+        self.component(C::clone)
+    }
+
+    /// Tells if viewed entity has component.
+    pub fn has<C>(&self) -> bool
+    where
+        W: ReadComponent<C>,
+    {
+        self.component(|_: &C| ()).is_some()
+    }
+}
+
+impl<W> std::fmt::Debug for EntityMemory<W>
+where
+    W: EntityWorld,
+    W::Entity: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EntityMemory")
+            .field("entity", &self.entity)
+            .finish()
+    }
+}
+
+/// Condition that validates component of entity viewed by [`EntityMemory`].
+///
+/// Condition is not met when entity does not have that component, unless changed with
+/// [`ComponentCondition::missing`].
+///
+/// # Example
+/// ```
+/// use emergent::prelude::*;
+///
+/// struct Ammo(usize);
+///
+/// struct World(Vec<Option<Ammo>>);
+///
+/// impl EntityWorld for World {
+///     type Entity = usize;
+/// }
+///
+/// impl ReadComponent<Ammo> for World {
+///     fn read_component<R>(&self, entity: usize, f: impl FnOnce(&Ammo) -> R) -> Option<R> {
+///         self.0.get(entity)?.as_ref().map(f)
+///     }
+/// }
+///
+/// let world = World(vec![Some(Ammo(0)), Some(Ammo(5)), None]);
+/// let condition = ComponentCondition::new(|ammo: &Ammo| ammo.0 > 0);
+/// assert!(!condition.validate(&EntityMemory::new(&world, 0)));
+/// assert!(condition.validate(&EntityMemory::new(&world, 1)));
+/// assert!(!condition.validate(&EntityMemory::new(&world, 2)));
+/// assert!(ComponentCondition::<Ammo>::exists().validate(&EntityMemory::new(&world, 1)));
+/// ```
+pub struct ComponentCondition<C> {
+    predicate: Box<dyn Fn(&C) -> bool + Send + Sync>,
+    missing: bool,
+}
+
+impl<C> ComponentCondition<C> {
+    /// Constructs new condition with component predicate.
+    pub fn new<F>(predicate: F) -> Self
+    where
+        F: Fn(&C) -> bool + 'static + Send + Sync,
+    {
+        Self {
+            predicate: Box::new(predicate),
+            missing: false,
+        }
+    }
+
+    /// Constructs new condition that is met when entity has component.
+    pub fn exists() -> Self {
+        Self::new(|_| true)
+    }
+
+    /// Sets result of condition when entity does not have component.
+    pub fn missing(mut self, value: bool) -> Self {
+        self.missing = value;
+        self
+    }
+
+    /// Returns result of condition when entity does not have component.
+    pub fn get_missing(&self) -> bool {
+        self.missing
+    }
+}
+
+impl<W, C> Condition<EntityMemory<W>> for ComponentCondition<C>
+where
+    W: ReadComponent<C>,
+{
+    fn validate(&self, memory: &EntityMemory<W>) -> bool {
+        memory
+            .component(|component| (self.predicate)(component))
+            .unwrap_or(self.missing)
+    }
+}
+
+impl<C> std::fmt::Debug for ComponentCondition<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ComponentCondition")
+            .field("missing", &self.missing)
+            .finish()
+    }
+}
+
+/// Consideration that scores component of entity viewed by [`EntityMemory`].
+///
+/// Entity without that component gives score of 0, unless changed with
+/// [`ComponentConsideration::missing`].
+///
+/// # Example
+/// ```
+/// use emergent::prelude::*;
+///
+/// struct Hunger(Scalar);
+///
+/// struct World(Vec<Option<Hunger>>);
+///
+/// impl EntityWorld for World {
+///     type Entity = usize;
+/// }
+///
+/// impl ReadComponent<Hunger> for World {
+///     fn read_component<R>(&self, entity: usize, f: impl FnOnce(&Hunger) -> R) -> Option<R> {
+///         self.0.get(entity)?.as_ref().map(f)
+///     }
+/// }
+///
+/// let world = World(vec![Some(Hunger(0.75)), None]);
+/// let consideration = ComponentConsideration::new(|hunger: &Hunger| hunger.0).missing(0.5);
+/// assert_eq!(consideration.score(&EntityMemory::new(&world, 0)), 0.75);
+/// assert_eq!(consideration.score(&EntityMemory::new(&world, 1)), 0.5);
+/// ```
+pub struct ComponentConsideration<C> {
+    scorer: Box<dyn Fn(&C) -> Scalar + Send + Sync>,
+    missing: Scalar,
+}
+
+impl<C> ComponentConsideration<C> {
+    /// Constructs new consideration with component scoring function.
+    pub fn new<F>(scorer: F) -> Self
+    where
+        F: Fn(&C) -> Scalar + 'static + Send + Sync,
+    {
+        Self {
+            scorer: Box::new(scorer),
+            missing: 0.0,
+        }
+    }
+
+    /// Sets score given when entity does not have component.
+    pub fn missing(mut self, value: Scalar) -> Self {
+        self.missing = value;
+        self
+    }
+
+    /// Returns score given when entity does not have component.
+    pub fn get_missing(&self) -> Scalar {
+        self.missing
+    }
+}
+
+impl<W, C> Consideration<EntityMemory<W>> for ComponentConsideration<C>
+where
+    W: ReadComponent<C>,
+{
+    fn score(&self, memory: &EntityMemory<W>) -> Scalar {
+        memory
+            .component(|component| (self.scorer)(component))
+            .unwrap_or(self.missing)
+    }
+}
+
+impl<C> std::fmt::Debug for ComponentConsideration<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ComponentConsideration")
+            .field("missing", &self.missing)
+            .finish()
+    }
+}
@@ -2,3 +2,5 @@
 
 pub mod blackboard;
 pub mod datatable;
+pub mod entity;
+pub mod scoped_blackboard;
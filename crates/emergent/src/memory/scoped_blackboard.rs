@@ -0,0 +1,627 @@
+use crate::{
+    memory::blackboard::{
+        Blackboard, BlackboardAccess, BlackboardChange, BlackboardChangeKind, BlackboardChanges,
+        BlackboardSubscription, BlackboardUndo,
+    },
+    task::{TransactionCommitPolicy, TransactionalMemory},
+};
+use std::{
+    any::{Any, TypeId},
+    collections::{HashMap, HashSet},
+};
+
+/// Tells into which scope of [`ScopedBlackboard`] property changes go.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum BlackboardWriteTarget {
+    /// Changes go to the innermost scope (usually agent scope), shadowing outer scopes properties.
+    #[default]
+    Innermost,
+    /// Changes go to the scope that currently provides the property, or to the innermost scope if
+    /// none of scopes has it.
+    Owner,
+    /// Changes always go to the scope with given name.
+    Scope(String),
+}
+
+/// Blackboard made of stack of named [`Blackboard`] scopes with lookups falling back from the
+/// innermost scope to the outermost one.
+///
+/// Common setup is to have global scope shared by everyone, squad scope shared by squad members
+/// and agent scope with private agent knowledge. Reading a property finds it in the innermost
+/// scope that has it, while writing it follows write target rules (see [`BlackboardWriteTarget`]),
+/// either default one or the one assigned to given property name.
+///
+/// Note that all mutating operations (`set`, `get_mut`, `raw_mut`, `with`, `remove`, etc.) resolve
+/// their scope with the same write target rules, so agent can't accidentally mutate shared
+/// knowledge unless rules allow that. This means that unlike reads, in-place mutations and removals
+/// do not fall back to outer scopes: with [`BlackboardWriteTarget::Innermost`] they return `None`
+/// (or `false`) for property provided only by outer scope - use [`BlackboardWriteTarget::Owner`]
+/// rule for properties that should be mutated where they live. When write target scope does not
+/// exist, `set` creates it as the innermost scope (named `"default"` when there are no scopes).
+///
+/// Scopes are owned by scoped blackboard, so to share them between agents, move them in before
+/// running agent decision making and take them back after that (see [`Self::replace_scope`]).
+///
+/// Scoped blackboard is a separate type, so code written for `&Blackboard` does not accept it -
+/// write conditions, considerations and tasks against [`BlackboardAccess`] trait, implemented by
+/// both blackboard kinds, to share them between agents using either memory type.
+///
+/// # Change tracking
+/// Scoped blackboard tracks changes made through it the same way [`Blackboard`] does, adding or
+/// removing scopes counts as change of all their properties. Changes made directly to scope
+/// blackboards (see [`Self::scope_mut`]) are tracked only by these blackboards.
+///
+/// # Transactions
+/// Transaction frames are opened in all scopes, scopes added during transaction join its active
+/// frames, while removed scopes are given back with frames still active.
+///
+/// # Example
+/// ```
+/// use emergent::prelude::*;
+///
+/// let mut global = Blackboard::default();
+/// global.set("alarm".to_owned(), false);
+/// let mut squad = Blackboard::default();
+/// squad.set("target".to_owned(), "bridge".to_owned());
+///
+/// let mut memory = ScopedBlackboard::default()
+///     .scope("global", global)
+///     .scope("squad", squad)
+///     .scope("agent", Blackboard::default())
+///     .write_rule("alarm", BlackboardWriteTarget::Scope("global".to_owned()));
+///
+/// assert_eq!(memory.get::<String>("target").unwrap(), "bridge");
+/// memory.set("target".to_owned(), "tower".to_owned());
+/// assert_eq!(memory.get::<String>("target").unwrap(), "tower");
+/// assert_eq!(memory.property_scope("target"), Some("agent"));
+/// memory.set("alarm".to_owned(), true);
+/// assert_eq!(memory.scope_ref("global").unwrap().get::<bool>("alarm"), Some(&true));
+/// assert_eq!(memory.len(), 2);
+/// ```
+#[derive(Default)]
+pub struct ScopedBlackboard {
+    scopes: Vec<(String, Blackboard)>,
+    write_target: BlackboardWriteTarget,
+    write_rules: HashMap<String, BlackboardWriteTarget>,
+    changes: BlackboardChanges,
+    transaction_depth: usize,
+}
+
+impl std::fmt::Debug for ScopedBlackboard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ScopedBlackboard")
+            .field("scopes", &self.scopes)
+            .field("write_target", &self.write_target)
+            .field("write_rules", &self.write_rules)
+            .field("version", &self.changes.version())
+            .field("transaction_depth", &self.transaction_depth)
+            .finish()
+    }
+}
+
+/// Scoped blackboard undo record, holds undo record of scope with given name.
+#[derive(Debug)]
+pub struct ScopedBlackboardUndo {
+    /// Name of scope.
+    pub scope: String,
+    /// Undo record of scope blackboard.
+    pub undo: BlackboardUndo,
+}
+
+impl ScopedBlackboard {
+    /// Adds new scope that becomes the innermost scope.
+    pub fn scope(mut self, name: impl ToString, blackboard: Blackboard) -> Self {
+        self.push_scope(name, blackboard);
+        self
+    }
+
+    /// Sets default write target.
+    pub fn write_target(mut self, target: BlackboardWriteTarget) -> Self {
+        self.write_target = target;
+        self
+    }
+
+    /// Sets write target used for property with given name.
+    pub fn write_rule(mut self, name: impl ToString, target: BlackboardWriteTarget) -> Self {
+        self.write_rules.insert(name.to_string(), target);
+        self
+    }
+
+    /// Returns default write target.
+    pub fn get_write_target(&self) -> &BlackboardWriteTarget {
+        &self.write_target
+    }
+
+    /// Returns write target used for property with given name.
+    pub fn get_write_rule(&self, name: &str) -> &BlackboardWriteTarget {
+        self.write_rules.get(name).unwrap_or(&self.write_target)
+    }
+
+    /// Adds new scope that becomes the innermost scope.
+    pub fn push_scope(&mut self, name: impl ToString, mut blackboard: Blackboard) {
+        self.join_transactions(&mut blackboard);
+        self.mark_scope_changed(&blackboard, BlackboardChangeKind::Set);
+        self.scopes.push((name.to_string(), blackboard));
+    }
+
+    /// Removes the innermost scope and returns it with its name.
+    pub fn pop_scope(&mut self) -> Option<(String, Blackboard)> {
+        let result = self.scopes.pop()?;
+        self.mark_scope_changed(&result.1, BlackboardChangeKind::Removed);
+        Some(result)
+    }
+
+    /// Replaces blackboard of scope with given name and returns previous one.
+    ///
+    /// Returns `None` when there is no scope with given name.
+    pub fn replace_scope(&mut self, name: &str, mut blackboard: Blackboard) -> Option<Blackboard> {
+        let index = self.scopes.iter().position(|(n, _)| n == name)?;
+        self.join_transactions(&mut blackboard);
+        let result = std::mem::replace(&mut self.scopes[index].1, blackboard);
+        self.mark_scope_changed(&result, BlackboardChangeKind::Removed);
+        let keys = self.scopes[index]
+            .1
+            .keys()
+            .map(|key| key.to_owned())
+            .collect::<Vec<_>>();
+        for key in keys {
+            self.changes.mark_changed(&key, BlackboardChangeKind::Set);
+        }
+        Some(result)
+    }
+
+    /// Returns reference to scope blackboard.
+    pub fn scope_ref(&self, name: &str) -> Option<&Blackboard> {
+        self.scopes
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, scope)| scope)
+    }
+
+    /// Returns mutable reference to scope blackboard.
+    ///
+    /// Changes made directly to scope blackboard are not tracked by scoped blackboard.
+    pub fn scope_mut(&mut self, name: &str) -> Option<&mut Blackboard> {
+        self.scopes
+            .iter_mut()
+            .find(|(n, _)| n == name)
+            .map(|(_, scope)| scope)
+    }
+
+    /// Return iterator over scopes names, from the outermost to the innermost one.
+    pub fn scope_names(&self) -> impl Iterator<Item = &str> {
+        self.scopes.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Consumes scoped blackboard and returns its scopes, from the outermost to the innermost one.
+    pub fn into_scopes(self) -> Vec<(String, Blackboard)> {
+        self.scopes
+    }
+
+    /// Returns name of the scope that provides property under given name.
+    pub fn property_scope(&self, name: &str) -> Option<&str> {
+        self.owner_index(name)
+            .map(|index| self.scopes[index].0.as_str())
+    }
+
+    /// Returns number of unique properties visible through all scopes.
+    pub fn len(&self) -> usize {
+        self.keys().count()
+    }
+
+    /// Tells if there are no properties stored in any scope.
+    pub fn is_empty(&self) -> bool {
+        self.scopes.iter().all(|(_, scope)| scope.is_empty())
+    }
+
+    /// Tells if there is a property under given name stored in any scope.
+    pub fn has_property(&self, name: &str) -> bool {
+        self.owner_index(name).is_some()
+    }
+
+    /// Tells if there is a property of given type under given name visible through scopes.
+    pub fn has_property_of_type<T>(&self, name: &str) -> bool
+    where
+        T: 'static,
+    {
+        self.owner(name)
+            .map(|scope| scope.has_property_of_type::<T>(name))
+            .unwrap_or_default()
+    }
+
+    /// Returns type ID of a property data visible through scopes.
+    pub fn type_id(&self, name: &str) -> Option<TypeId> {
+        self.owner(name).and_then(|scope| scope.type_id(name))
+    }
+
+    /// Returns reference to property data visible through scopes.
+    pub fn get<T>(&self, name: &str) -> Option<&T>
+    where
+        T: 'static,
+    {
+        self.owner(name).and_then(|scope| scope.get(name))
+    }
+
+    /// Returns mutable reference to property data from write target scope.
+    ///
+    /// Since scoped blackboard can't tell what will happen with returned reference, successful
+    /// access counts as property modification. See [`Blackboard::get_mut`] for behavior during
+    /// transaction.
+    pub fn get_mut<T>(&mut self, name: &str) -> Option<&mut T>
+    where
+        T: 'static,
+    {
+        let index = self.write_index(name)?;
+        let value = self.scopes[index].1.get_mut(name)?;
+        self.changes
+            .mark_changed(name, BlackboardChangeKind::Modified);
+        Some(value)
+    }
+
+    /// Returns reference to property data visible through scopes as [`Any`].
+    pub fn raw(&self, name: &str) -> Option<&dyn Any> {
+        self.owner(name).and_then(|scope| scope.raw(name))
+    }
+
+    /// Returns mutable reference to property data from write target scope as [`Any`].
+    ///
+    /// Since scoped blackboard can't tell what will happen with returned reference, successful
+    /// access counts as property modification. See [`Blackboard::raw_mut`] for behavior during
+    /// transaction.
+    pub fn raw_mut(&mut self, name: &str) -> Option<&mut dyn Any> {
+        let index = self.write_index(name)?;
+        let value = self.scopes[index].1.raw_mut(name)?;
+        self.changes
+            .mark_changed(name, BlackboardChangeKind::Modified);
+        Some(value)
+    }
+
+    /// Put value to property under given name in write target scope.
+    pub fn set<T>(&mut self, name: String, value: T)
+    where
+        T: Any + 'static + Send + Sync,
+    {
+        self.set_raw(name, Box::new(value));
+    }
+
+    /// Put value to property under given name in write target scope and register its type as
+    /// cloneable in that scope.
+    ///
+    /// See [`Blackboard::set_cloneable`].
+    pub fn set_cloneable<T>(&mut self, name: String, value: T)
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        let index = self.write_index_or_create(&name);
+        self.scopes[index].1.register_cloneable::<T>();
+        self.set_raw(name, Box::new(value));
+    }
+
+    /// Put value to property under given name in write target scope.
+    pub fn set_raw(&mut self, name: String, value: Box<dyn Any + Send + Sync>) {
+        let index = self.write_index_or_create(&name);
+        self.changes.mark_changed(&name, BlackboardChangeKind::Set);
+        self.scopes[index].1.set_raw(name, value);
+    }
+
+    /// Mutate property data from write target scope in-place with closure.
+    ///
+    /// See [`Blackboard::with`] for behavior during transaction.
+    pub fn with<T, R, F>(&mut self, name: &str, f: F) -> Option<R>
+    where
+        F: FnMut(&mut T) -> R,
+        T: 'static,
+    {
+        let index = self.write_index(name)?;
+        let result = self.scopes[index].1.with(name, f)?;
+        self.changes
+            .mark_changed(name, BlackboardChangeKind::Modified);
+        Some(result)
+    }
+
+    /// Remove property under given name from write target scope.
+    pub fn remove(&mut self, name: &str) -> bool {
+        let Some(index) = self.write_index(name) else {
+            return false;
+        };
+        if self.scopes[index].1.remove(name) {
+            self.changes
+                .mark_changed(name, BlackboardChangeKind::Removed);
+            return true;
+        }
+        false
+    }
+
+    /// Remove all properties in default write target scope.
+    pub fn clear(&mut self) {
+        if let Some(index) = self.target_index(&self.write_target, None) {
+            let keys = self.scopes[index]
+                .1
+                .keys()
+                .map(|key| key.to_owned())
+                .collect::<Vec<_>>();
+            self.scopes[index].1.clear();
+            for key in keys {
+                self.changes
+                    .mark_changed(&key, BlackboardChangeKind::Removed);
+            }
+        }
+    }
+
+    /// Returns current scoped blackboard version.
+    ///
+    /// See [`Blackboard::version`].
+    pub fn version(&self) -> u64 {
+        self.changes.version()
+    }
+
+    /// Returns version of last change made to property under given name.
+    ///
+    /// Removed properties still report version of their removal.
+    pub fn property_version(&self, name: &str) -> Option<u64> {
+        self.changes.property_version(name)
+    }
+
+    /// Tells if property under given name has changed (or got removed) since given version.
+    pub fn has_changed_since(&self, name: &str, version: u64) -> bool {
+        self.changes.has_changed_since(name, version)
+    }
+
+    /// Return iterator over keys of properties changed (or removed) since given version.
+    pub fn changed_since(&self, version: u64) -> impl Iterator<Item = &str> {
+        self.changes.changed_since(version)
+    }
+
+    /// Registers callback called on every property change.
+    ///
+    /// Returns subscription handle that can be used to unsubscribe.
+    pub fn subscribe<F>(&mut self, f: F) -> BlackboardSubscription
+    where
+        F: FnMut(&BlackboardChange) + 'static + Send + Sync,
+    {
+        self.changes.subscribe(f)
+    }
+
+    /// Removes subscriber callback.
+    ///
+    /// Returns `true` if subscriber was found and removed.
+    pub fn unsubscribe(&mut self, subscription: BlackboardSubscription) -> bool {
+        self.changes.unsubscribe(subscription)
+    }
+
+    /// Return iterator over unique properties keys visible through scopes.
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        let mut visited = HashSet::new();
+        self.scopes
+            .iter()
+            .rev()
+            .flat_map(|(_, scope)| scope.keys())
+            .filter(move |key| visited.insert(*key))
+    }
+
+    /// Return iterator over unique properties keys and type IDs visible through scopes.
+    pub fn key_types(&self) -> impl Iterator<Item = (&str, TypeId)> {
+        self.keys()
+            .filter_map(|key| self.type_id(key).map(|type_id| (key, type_id)))
+    }
+
+    /// Return iterator over unique properties keys and their data as [`Any`] visible through
+    /// scopes.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &dyn Any)> {
+        self.keys()
+            .filter_map(|key| self.raw(key).map(|value| (key, value)))
+    }
+
+    fn owner_index(&self, name: &str) -> Option<usize> {
+        self.scopes
+            .iter()
+            .rposition(|(_, scope)| scope.has_property(name))
+    }
+
+    fn owner(&self, name: &str) -> Option<&Blackboard> {
+        self.owner_index(name).map(|index| &self.scopes[index].1)
+    }
+
+    fn target_index(&self, target: &BlackboardWriteTarget, name: Option<&str>) -> Option<usize> {
+        match target {
+            BlackboardWriteTarget::Innermost => self.scopes.len().checked_sub(1),
+            BlackboardWriteTarget::Owner => name
+                .and_then(|name| self.owner_index(name))
+                .or_else(|| self.scopes.len().checked_sub(1)),
+            BlackboardWriteTarget::Scope(scope) => self.scopes.iter().position(|(n, _)| n == scope),
+        }
+    }
+
+    fn write_index(&self, name: &str) -> Option<usize> {
+        self.target_index(self.get_write_rule(name), Some(name))
+    }
+
+    fn write_index_or_create(&mut self, name: &str) -> usize {
+        if let Some(index) = self.write_index(name) {
+            return index;
+        }
+        let scope = match self.get_write_rule(name) {
+            BlackboardWriteTarget::Scope(scope) => scope.to_owned(),
+            _ => "default".to_owned(),
+        };
+        self.push_scope(scope, Blackboard::default());
+        self.scopes.len() - 1
+    }
+
+    fn join_transactions(&self, blackboard: &mut Blackboard) {
+        for _ in 0..self.transaction_depth {
+            blackboard.begin_transaction();
+        }
+    }
+
+    fn mark_scope_changed(&mut self, scope: &Blackboard, kind: BlackboardChangeKind) {
+        for key in scope.keys() {
+            self.changes.mark_changed(key, kind);
+        }
+    }
+
+    fn rollback_scopes(&mut self, mut f: impl FnMut(&mut Blackboard) -> bool) -> bool {
+        let mut result = false;
+        let mut changed = vec![];
+        for (_, scope) in &mut self.scopes {
+            let version = scope.version();
+            result |= f(scope);
+            changed.extend(scope.changed_since(version).map(|key| key.to_owned()));
+        }
+        for key in changed {
+            let kind = if self.has_property(&key) {
+                BlackboardChangeKind::Set
+            } else {
+                BlackboardChangeKind::Removed
+            };
+            self.changes.mark_changed(&key, kind);
+        }
+        result
+    }
+}
+
+impl BlackboardAccess for ScopedBlackboard {
+    fn len(&self) -> usize {
+        ScopedBlackboard::len(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        ScopedBlackboard::is_empty(self)
+    }
+
+    fn has_property(&self, name: &str) -> bool {
+        ScopedBlackboard::has_property(self, name)
+    }
+
+    fn has_property_of_type<T>(&self, name: &str) -> bool
+    where
+        T: 'static,
+    {
+        ScopedBlackboard::has_property_of_type::<T>(self, name)
+    }
+
+    fn type_id(&self, name: &str) -> Option<TypeId> {
+        ScopedBlackboard::type_id(self, name)
+    }
+
+    fn get<T>(&self, name: &str) -> Option<&T>
+    where
+        T: 'static,
+    {
+        ScopedBlackboard::get(self, name)
+    }
+
+    fn get_mut<T>(&mut self, name: &str) -> Option<&mut T>
+    where
+        T: 'static,
+    {
+        ScopedBlackboard::get_mut(self, name)
+    }
+
+    fn raw(&self, name: &str) -> Option<&dyn Any> {
+        ScopedBlackboard::raw(self, name)
+    }
+
+    fn raw_mut(&mut self, name: &str) -> Option<&mut dyn Any> {
+        ScopedBlackboard::raw_mut(self, name)
+    }
+
+    fn set<T>(&mut self, name: String, value: T)
+    where
+        T: Any + 'static + Send + Sync,
+    {
+        ScopedBlackboard::set(self, name, value)
+    }
+
+    fn set_raw(&mut self, name: String, value: Box<dyn Any + Send + Sync>) {
+        ScopedBlackboard::set_raw(self, name, value)
+    }
+
+    fn with<T, R, F>(&mut self, name: &str, f: F) -> Option<R>
+    where
+        F: FnMut(&mut T) -> R,
+        T: 'static,
+    {
+        ScopedBlackboard::with(self, name, f)
+    }
+
+    fn remove(&mut self, name: &str) -> bool {
+        ScopedBlackboard::remove(self, name)
+    }
+
+    fn version(&self) -> u64 {
+        ScopedBlackboard::version(self)
+    }
+
+    fn property_version(&self, name: &str) -> Option<u64> {
+        ScopedBlackboard::property_version(self, name)
+    }
+
+    fn has_changed_since(&self, name: &str, version: u64) -> bool {
+        ScopedBlackboard::has_changed_since(self, name, version)
+    }
+}
+
+impl TransactionalMemory for ScopedBlackboard {
+    type Undo = ScopedBlackboardUndo;
+
+    fn begin_transaction(&mut self) {
+        self.transaction_depth += 1;
+        for (_, scope) in &mut self.scopes {
+            scope.begin_transaction();
+        }
+    }
+
+    fn commit_transaction(&mut self, policy: TransactionCommitPolicy) {
+        self.transaction_depth = self.transaction_depth.saturating_sub(1);
+        for (_, scope) in &mut self.scopes {
+            scope.commit_transaction(policy);
+        }
+    }
+
+    fn rollback_transaction(&mut self) {
+        self.transaction_depth = self.transaction_depth.saturating_sub(1);
+        self.rollback_scopes(|scope| {
+            scope.rollback_transaction();
+            true
+        });
+    }
+
+    fn record_undo(&mut self, undo: Self::Undo) {
+        if let Some(scope) = self.scope_mut(&undo.scope) {
+            scope.record_undo(undo.undo);
+        }
+    }
+
+    fn savepoint_transaction(&mut self, name: &str) -> bool {
+        let mut result = false;
+        for (_, scope) in &mut self.scopes {
+            result |= scope.savepoint_transaction(name);
+        }
+        result
+    }
+
+    fn rollback_to_savepoint(&mut self, name: &str) -> bool {
+        self.rollback_scopes(|scope| scope.rollback_to_savepoint(name))
+    }
+
+    fn release_savepoint(&mut self, name: &str) -> bool {
+        let mut result = false;
+        for (_, scope) in &mut self.scopes {
+            result |= scope.release_savepoint(name);
+        }
+        result
+    }
+
+    fn transaction_depth(&self) -> usize {
+        self.transaction_depth
+    }
+
+    fn rollback_all_transactions(&mut self) {
+        self.transaction_depth = 0;
+        self.rollback_scopes(|scope| {
+            scope.rollback_all_transactions();
+            true
+        });
+    }
+}
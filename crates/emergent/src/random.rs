@@ -0,0 +1,39 @@
+//! Small deterministic random numbers generator used by stochastic decision makers.
+
+use crate::Scalar;
+
+/// SplitMix64 generator - fast, good enough for gameplay and fully reproducible from seed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct Random(u64);
+
+impl Default for Random {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns number in `[0; 1)` range.
+    pub fn next_scalar(&mut self) -> Scalar {
+        let value = ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) as Scalar;
+        // rounding to lower precision scalar might produce 1.
+        value.min(1.0 - Scalar::EPSILON)
+    }
+
+    /// Returns index in `[0; count)` range.
+    pub fn next_index(&mut self, count: usize) -> usize {
+        (self.next_u64() % count.max(1) as u64) as usize
+    }
+}
@@ -20,6 +20,22 @@
 //!   should simply do nothing while that task is active.
 //! - [`ClosureTask`]: a wrapper around closure-based tasks where each life-cycle method is provided
 //!   by the user as separate closures, best for prototyping or making small non-repetitive logic.
+//!
+//! There are also decorator tasks that wrap another task and change how it runs:
+//! - [`RepeatTask`]: runs wrapped task again when it finishes, given number of times or until
+//!   condition is met.
+//! - [`RetryTask`]: runs wrapped task again when it finishes without meeting success condition.
+//! - [`CooldownTask`]: blocks wrapped task from running again for some time after it stops.
+//! - [`TimeoutTask`]: stops wrapped task when it runs for too long.
+//! - [`GuardTask`]: runs wrapped task only as long as condition is met.
+//! - [`AlwaysSucceedTask`]: runs wrapped task only when condition is met, but never fails.
+//! - [`InverterTask`]: runs wrapped task only when condition is met, but inverts node success.
+//!
+//! With `async` feature there is also `AsyncTask` that runs future created when task starts and
+//! polls it while task is processed.
+
+use crate::{Scalar, condition::*};
+use std::sync::{Arc, Mutex};
 
 /// Describes why task has stopped its work.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -98,6 +114,13 @@ pub trait Task<M = ()>: Send + Sync {
     fn on_process(&mut self, memory: &mut M) -> bool {
         false
     }
+
+    /// Returns name that identifies this task, if it has one.
+    ///
+    /// Used to write behavior trees back to text (see [`crate::builders::behavior_tree_dsl`]).
+    fn task_name(&self) -> Option<&str> {
+        None
+    }
 }
 
 /// Task that represent no work. Use it when AI has to do nothing.
@@ -240,105 +263,126 @@ impl<M> std::fmt::Debug for ClosureTask<M> {
     }
 }
 
-/// Decides what happens to undo records when a journaled transaction commits.
-#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
-pub enum TransactionCommitPolicy {
-    /// Commit accepts this transaction's changes and discards its undo log.
-    ///
-    /// Parent transaction rollbacks won't undo changes recorded by this transaction.
-    #[default]
-    Isolated,
-    /// Commit moves this transaction's undo log into its parent transaction.
-    ///
-    /// Parent transaction rollbacks can still undo changes recorded by this transaction.
-    MergeIntoParent,
-}
-
-/// Stack of undo records used by journaled transactions.
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct TransactionJournal<U> {
-    frames: Vec<Vec<U>>,
+/// Task that wraps another task and gives it a name.
+///
+/// # Example
+/// ```
+/// use emergent::prelude::*;
+///
+/// let task = NamedTask::<()>::new("idle", NoTask);
+/// assert_eq!(task.task_name(), Some("idle"));
+/// ```
+pub struct NamedTask<M = ()> {
+    name: String,
+    task: Box<dyn Task<M>>,
 }
 
-impl<U> Default for TransactionJournal<U> {
-    fn default() -> Self {
-        Self { frames: Vec::new() }
+impl<M> NamedTask<M> {
+    /// Constructs new named task.
+    pub fn new<T>(name: impl ToString, task: T) -> Self
+    where
+        T: Task<M> + 'static,
+    {
+        Self::new_raw(name, Box::new(task))
     }
-}
 
-impl<U> TransactionJournal<U> {
-    /// Starts new transaction frame.
-    pub fn begin(&mut self) {
-        self.frames.push(Vec::new());
+    /// Constructs new named task from raw task.
+    pub fn new_raw(name: impl ToString, task: Box<dyn Task<M>>) -> Self {
+        Self {
+            name: name.to_string(),
+            task,
+        }
     }
 
-    /// Records undo entry in currently active transaction frame.
-    ///
-    /// Returns `false` when there is no active transaction frame.
-    pub fn record(&mut self, undo: U) -> bool {
-        let Some(frame) = self.frames.last_mut() else {
-            return false;
-        };
-        frame.push(undo);
-        true
+    /// Returns name.
+    pub fn name(&self) -> &str {
+        &self.name
     }
 
-    /// Commits currently active transaction frame.
-    pub fn commit(&mut self, policy: TransactionCommitPolicy) {
-        let Some(frame) = self.frames.pop() else {
-            return;
-        };
+    /// Returns immutable access to wrapped task.
+    pub fn task(&self) -> &dyn Task<M> {
+        self.task.as_ref()
+    }
 
-        if policy == TransactionCommitPolicy::MergeIntoParent
-            && let Some(parent) = self.frames.last_mut()
-        {
-            parent.extend(frame);
-        }
+    /// Returns mutable access to wrapped task.
+    pub fn task_mut(&mut self) -> &mut dyn Task<M> {
+        self.task.as_mut()
     }
+}
 
-    /// Rolls back currently active transaction frame.
-    pub fn rollback(&mut self) -> impl Iterator<Item = U> {
-        self.frames.pop().unwrap_or_default().into_iter().rev()
+impl<M> Task<M> for NamedTask<M> {
+    fn is_locked(&self, memory: &M) -> bool {
+        self.task.is_locked(memory)
     }
 
-    /// Tells if there is an active transaction frame.
-    pub fn is_active(&self) -> bool {
-        !self.frames.is_empty()
+    fn on_enter(&mut self, memory: &mut M) {
+        self.task.on_enter(memory);
     }
 
-    /// Returns number of active transaction frames.
-    pub fn depth(&self) -> usize {
-        self.frames.len()
+    fn on_exit(&mut self, memory: &mut M) {
+        self.task.on_exit(memory);
     }
-}
 
-/// Memory that stores and applies journaled transaction undo records.
-pub trait TransactionalMemory {
-    /// Domain-specific undo record.
-    type Undo;
+    fn on_stop(&mut self, memory: &mut M, reason: TaskStopReason) {
+        self.task.on_stop(memory, reason);
+    }
 
-    /// Starts new transaction frame.
-    fn begin_transaction(&mut self);
+    fn on_update(&mut self, memory: &mut M) {
+        self.task.on_update(memory);
+    }
 
-    /// Commits currently active transaction frame.
-    fn commit_transaction(&mut self, policy: TransactionCommitPolicy);
+    fn on_process(&mut self, memory: &mut M) -> bool {
+        self.task.on_process(memory)
+    }
 
-    /// Rolls back currently active transaction frame.
-    fn rollback_transaction(&mut self);
+    fn task_name(&self) -> Option<&str> {
+        Some(&self.name)
+    }
+}
 
-    /// Records undo entry in currently active transaction frame.
-    fn record_undo(&mut self, undo: Self::Undo);
+impl<M> std::fmt::Debug for NamedTask<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NamedTask")
+            .field("name", &self.name)
+            .finish()
+    }
 }
 
-/// Wrapper around task that creates transaction scope with undo records stored in memory.
-pub struct JournaledTransactionTask<M = ()> {
+/// Shared function that reads current time from memory, used by time-based tasks.
+///
+/// Time units are up to the user, as long as durations use the same units.
+pub type TaskClock<M = ()> = Arc<dyn Fn(&M) -> Scalar + Send + Sync>;
+
+/// Wrapper around task that runs it again whenever it finishes (gets unlocked).
+///
+/// By default it repeats forever, but it can be limited with number of runs (see [`Self::count`])
+/// and/or condition that stops repeating when met (see [`Self::until`]). Task stays locked as long
+/// as there are runs left, and each next run starts on [`Task::on_process`].
+///
+/// # Example
+/// ```
+/// use emergent::prelude::*;
+///
+/// let mut task = RepeatTask::new(ClosureTask::default().enter(|m: &mut usize| *m += 1)).count(3);
+/// let mut memory = 0;
+/// task.on_enter(&mut memory);
+/// assert!(task.is_locked(&memory));
+/// assert!(task.on_process(&mut memory));
+/// assert!(task.on_process(&mut memory));
+/// assert!(!task.is_locked(&memory));
+/// assert!(!task.on_process(&mut memory));
+/// assert_eq!(memory, 3);
+/// ```
+pub struct RepeatTask<M = ()> {
     task: Box<dyn Task<M>>,
+    count: Option<usize>,
+    until: Option<Box<dyn Condition<M>>>,
+    iteration: usize,
     active: bool,
-    commit_policy: TransactionCommitPolicy,
 }
 
-impl<M> JournaledTransactionTask<M> {
-    /// Constructs journaled transaction wrapper around task.
+impl<M> RepeatTask<M> {
+    /// Constructs wrapper that repeats task forever.
     pub fn new<T>(task: T) -> Self
     where
         T: Task<M> + 'static,
@@ -346,24 +390,45 @@ impl<M> JournaledTransactionTask<M> {
         Self::new_raw(Box::new(task))
     }
 
-    /// Constructs journaled transaction wrapper around raw task.
+    /// Constructs wrapper that repeats raw task forever.
     pub fn new_raw(task: Box<dyn Task<M>>) -> Self {
         Self {
             task,
+            count: None,
+            until: None,
+            iteration: 0,
             active: false,
-            commit_policy: TransactionCommitPolicy::default(),
         }
     }
 
-    /// Sets commit policy.
-    pub fn commit_policy(mut self, policy: TransactionCommitPolicy) -> Self {
-        self.commit_policy = policy;
+    /// Sets number of runs (task always runs at least once).
+    pub fn count(mut self, count: usize) -> Self {
+        self.count = Some(count);
         self
     }
 
-    /// Returns commit policy.
-    pub fn get_commit_policy(&self) -> TransactionCommitPolicy {
-        self.commit_policy
+    /// Sets condition that stops repeating when met.
+    pub fn until<C>(self, condition: C) -> Self
+    where
+        C: Condition<M> + 'static,
+    {
+        self.until_raw(Box::new(condition))
+    }
+
+    /// Sets raw condition that stops repeating when met.
+    pub fn until_raw(mut self, condition: Box<dyn Condition<M>>) -> Self {
+        self.until = Some(condition);
+        self
+    }
+
+    /// Returns number of runs.
+    pub fn get_count(&self) -> Option<usize> {
+        self.count
+    }
+
+    /// Returns index of current run.
+    pub fn iteration(&self) -> usize {
+        self.iteration
     }
 
     /// Returns immutable access to wrapped task.
@@ -375,18 +440,29 @@ impl<M> JournaledTransactionTask<M> {
     pub fn task_mut(&mut self) -> &mut dyn Task<M> {
         self.task.as_mut()
     }
+
+    fn wants_repeat(&self, memory: &M) -> bool {
+        if let Some(count) = self.count
+            && self.iteration + 1 >= count
+        {
+            return false;
+        }
+        if let Some(until) = &self.until
+            && until.validate(memory)
+        {
+            return false;
+        }
+        true
+    }
 }
 
-impl<M> Task<M> for JournaledTransactionTask<M>
-where
-    M: TransactionalMemory,
-{
+impl<M> Task<M> for RepeatTask<M> {
     fn is_locked(&self, memory: &M) -> bool {
-        self.task.is_locked(memory)
+        self.active && (self.task.is_locked(memory) || self.wants_repeat(memory))
     }
 
     fn on_enter(&mut self, memory: &mut M) {
-        memory.begin_transaction();
+        self.iteration = 0;
         self.task.on_enter(memory);
         self.active = true;
     }
@@ -396,97 +472,102 @@ where
     }
 
     fn on_stop(&mut self, memory: &mut M, reason: TaskStopReason) {
-        self.task.on_stop(memory, reason);
-
-        if !self.active {
-            return;
-        }
-
-        match reason {
-            TaskStopReason::Completed => {
-                memory.commit_transaction(self.commit_policy);
-            }
-            TaskStopReason::Cancelled | TaskStopReason::Replaced => {
-                memory.rollback_transaction();
-            }
+        if self.active {
+            self.task.on_stop(memory, reason);
+            self.active = false;
         }
-
-        self.active = false;
     }
 
     fn on_update(&mut self, memory: &mut M) {
-        self.task.on_update(memory);
+        if self.active {
+            self.task.on_update(memory);
+        }
     }
 
     fn on_process(&mut self, memory: &mut M) -> bool {
+        if !self.active {
+            return false;
+        }
+        if !self.task.is_locked(memory) && self.wants_repeat(memory) {
+            self.task.on_stop(memory, TaskStopReason::Completed);
+            self.iteration += 1;
+            self.task.on_enter(memory);
+            return true;
+        }
         self.task.on_process(memory)
     }
 }
 
-impl<M> std::fmt::Debug for JournaledTransactionTask<M> {
+impl<M> std::fmt::Debug for RepeatTask<M> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("JournaledTransactionTask")
+        f.debug_struct("RepeatTask")
+            .field("count", &self.count)
+            .field("iteration", &self.iteration)
             .field("active", &self.active)
-            .field("commit_policy", &self.commit_policy)
             .finish()
     }
 }
 
-/// Wrapper around task that creates transaction scope with custom begin/commit/rollback hooks.
-#[allow(clippy::type_complexity)]
-pub struct TransactionScopeTask<M = ()> {
+/// Wrapper around task that runs it again when it finishes (gets unlocked) without meeting success
+/// condition, up to given number of retries.
+///
+/// Failed attempts are stopped with [`TaskStopReason::Cancelled`], so transactional tasks roll back
+/// their changes before next attempt starts on [`Task::on_process`].
+///
+/// # Example
+/// ```
+/// use emergent::prelude::*;
+///
+/// let mut task = RetryTask::new(
+///     ClosureTask::default().enter(|m: &mut usize| *m += 1),
+///     ClosureCondition::new(|m: &usize| *m >= 2),
+///     5,
+/// );
+/// let mut memory = 0;
+/// task.on_enter(&mut memory);
+/// assert!(task.is_locked(&memory));
+/// assert!(task.on_process(&mut memory));
+/// assert!(!task.is_locked(&memory));
+/// assert_eq!(memory, 2);
+/// assert_eq!(task.attempt(), 1);
+/// ```
+pub struct RetryTask<M = ()> {
     task: Box<dyn Task<M>>,
+    success: Box<dyn Condition<M>>,
+    retries: usize,
+    attempt: usize,
     active: bool,
-    begin: Option<Box<dyn FnMut(&mut M) + Send + Sync>>,
-    commit: Option<Box<dyn FnMut(&mut M) + Send + Sync>>,
-    rollback: Option<Box<dyn FnMut(&mut M) + Send + Sync>>,
 }
 
-impl<M> TransactionScopeTask<M> {
-    /// Constructs transaction wrapper around task.
-    pub fn new<T>(task: T) -> Self
+impl<M> RetryTask<M> {
+    /// Constructs wrapper around task with success condition and number of retries.
+    pub fn new<T, C>(task: T, success: C, retries: usize) -> Self
     where
         T: Task<M> + 'static,
+        C: Condition<M> + 'static,
     {
-        Self::new_raw(Box::new(task))
+        Self::new_raw(Box::new(task), Box::new(success), retries)
     }
 
-    /// Constructs transaction wrapper around raw task.
-    pub fn new_raw(task: Box<dyn Task<M>>) -> Self {
+    /// Constructs wrapper around raw task with raw success condition and number of retries.
+    pub fn new_raw(task: Box<dyn Task<M>>, success: Box<dyn Condition<M>>, retries: usize) -> Self {
         Self {
             task,
+            success,
+            retries,
+            attempt: 0,
             active: false,
-            begin: None,
-            commit: None,
-            rollback: None,
         }
     }
 
-    /// Sets begin transaction hook.
-    pub fn begin<F>(mut self, f: F) -> Self
-    where
-        F: FnMut(&mut M) + 'static + Send + Sync,
-    {
-        self.begin = Some(Box::new(f));
-        self
-    }
-
-    /// Sets commit transaction hook.
-    pub fn commit<F>(mut self, f: F) -> Self
-    where
-        F: FnMut(&mut M) + 'static + Send + Sync,
-    {
-        self.commit = Some(Box::new(f));
-        self
+    /// Returns number of retries.
+    pub fn retries(&self) -> usize {
+        self.retries
     }
 
-    /// Sets rollback transaction hook.
-    pub fn rollback<F>(mut self, f: F) -> Self
-    where
-        F: FnMut(&mut M) + 'static + Send + Sync,
-    {
-        self.rollback = Some(Box::new(f));
-        self
+    /// Returns index of current attempt.
+    pub fn attempt(&self) -> usize {
+        self.attempt
     }
 
     /// Returns immutable access to wrapped task.
@@ -498,17 +579,19 @@ impl<M> TransactionScopeTask<M> {
     pub fn task_mut(&mut self) -> &mut dyn Task<M> {
         self.task.as_mut()
     }
+
+    fn wants_retry(&self, memory: &M) -> bool {
+        self.attempt < self.retries && !self.success.validate(memory)
+    }
 }
 
-impl<M> Task<M> for TransactionScopeTask<M> {
+impl<M> Task<M> for RetryTask<M> {
     fn is_locked(&self, memory: &M) -> bool {
-        self.task.is_locked(memory)
+        self.active && (self.task.is_locked(memory) || self.wants_retry(memory))
     }
 
     fn on_enter(&mut self, memory: &mut M) {
-        if let Some(f) = &mut self.begin {
-            f(memory);
-        }
+        self.attempt = 0;
         self.task.on_enter(memory);
         self.active = true;
     }
@@ -518,30 +601,1226 @@ impl<M> Task<M> for TransactionScopeTask<M> {
     }
 
     fn on_stop(&mut self, memory: &mut M, reason: TaskStopReason) {
-        self.task.on_stop(memory, reason);
-
-        if !self.active {
-            return;
-        }
-
-        match reason {
-            TaskStopReason::Completed => {
-                if let Some(f) = &mut self.commit {
-                    f(memory);
-                }
-            }
-            TaskStopReason::Cancelled | TaskStopReason::Replaced => {
-                if let Some(f) = &mut self.rollback {
-                    f(memory);
-                }
-            }
+        if self.active {
+            self.task.on_stop(memory, reason);
+            self.active = false;
         }
-
-        self.active = false;
     }
 
     fn on_update(&mut self, memory: &mut M) {
-        self.task.on_update(memory);
+        if self.active {
+            self.task.on_update(memory);
+        }
+    }
+
+    fn on_process(&mut self, memory: &mut M) -> bool {
+        if !self.active {
+            return false;
+        }
+        if !self.task.is_locked(memory) && self.wants_retry(memory) {
+            self.task.on_stop(memory, TaskStopReason::Cancelled);
+            self.attempt += 1;
+            self.task.on_enter(memory);
+            return true;
+        }
+        self.task.on_process(memory)
+    }
+}
+
+impl<M> std::fmt::Debug for RetryTask<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RetryTask")
+            .field("retries", &self.retries)
+            .field("attempt", &self.attempt)
+            .field("active", &self.active)
+            .finish()
+    }
+}
+
+/// Wrapper around task that blocks it from running again for given duration after it stops.
+///
+/// Since task can't refuse to start, blocking is done with [`CooldownCondition`] (see
+/// [`Self::condition`]) that has to be used as condition of the state that runs this task.
+///
+/// # Example
+/// ```
+/// use emergent::prelude::*;
+/// use std::sync::Arc;
+///
+/// struct Memory {
+///     time: Scalar,
+///     attacks: usize,
+/// }
+///
+/// let mut task = CooldownTask::new(
+///     ClosureTask::default().enter(|m: &mut Memory| m.attacks += 1),
+///     2.0,
+///     Arc::new(|m: &Memory| m.time),
+/// );
+/// let condition = task.condition();
+/// let mut memory = Memory { time: 0.0, attacks: 0 };
+/// assert!(condition.validate(&memory));
+/// task.on_enter(&mut memory);
+/// task.on_stop(&mut memory, TaskStopReason::Completed);
+/// memory.time = 1.0;
+/// assert!(!condition.validate(&memory));
+/// memory.time = 2.0;
+/// assert!(condition.validate(&memory));
+/// ```
+pub struct CooldownTask<M = ()> {
+    task: Box<dyn Task<M>>,
+    duration: Scalar,
+    clock: TaskClock<M>,
+    ready_at: Arc<Mutex<Option<Scalar>>>,
+    active: bool,
+}
+
+impl<M> CooldownTask<M> {
+    /// Constructs wrapper around task with cooldown duration and clock.
+    pub fn new<T>(task: T, duration: Scalar, clock: TaskClock<M>) -> Self
+    where
+        T: Task<M> + 'static,
+    {
+        Self::new_raw(Box::new(task), duration, clock)
+    }
+
+    /// Constructs wrapper around raw task with cooldown duration and clock.
+    pub fn new_raw(task: Box<dyn Task<M>>, duration: Scalar, clock: TaskClock<M>) -> Self {
+        Self {
+            task,
+            duration,
+            clock,
+            ready_at: Default::default(),
+            active: false,
+        }
+    }
+
+    /// Returns cooldown duration.
+    pub fn duration(&self) -> Scalar {
+        self.duration
+    }
+
+    /// Returns condition that fails while task is cooling down.
+    pub fn condition(&self) -> CooldownCondition<M> {
+        CooldownCondition {
+            clock: self.clock.clone(),
+            ready_at: self.ready_at.clone(),
+        }
+    }
+
+    /// Tells if task is not cooling down.
+    pub fn is_ready(&self, memory: &M) -> bool {
+        self.condition().validate(memory)
+    }
+
+    /// Returns immutable access to wrapped task.
+    pub fn task(&self) -> &dyn Task<M> {
+        self.task.as_ref()
+    }
+
+    /// Returns mutable access to wrapped task.
+    pub fn task_mut(&mut self) -> &mut dyn Task<M> {
+        self.task.as_mut()
+    }
+}
+
+impl<M> Task<M> for CooldownTask<M> {
+    fn is_locked(&self, memory: &M) -> bool {
+        self.task.is_locked(memory)
+    }
+
+    fn on_enter(&mut self, memory: &mut M) {
+        self.task.on_enter(memory);
+        self.active = true;
+    }
+
+    fn on_exit(&mut self, memory: &mut M) {
+        self.on_stop(memory, TaskStopReason::Cancelled);
+    }
+
+    fn on_stop(&mut self, memory: &mut M, reason: TaskStopReason) {
+        if self.active {
+            self.task.on_stop(memory, reason);
+            if let Ok(mut ready_at) = self.ready_at.lock() {
+                *ready_at = Some((self.clock)(memory) + self.duration);
+            }
+            self.active = false;
+        }
+    }
+
+    fn on_update(&mut self, memory: &mut M) {
+        self.task.on_update(memory);
+    }
+
+    fn on_process(&mut self, memory: &mut M) -> bool {
+        self.task.on_process(memory)
+    }
+}
+
+impl<M> std::fmt::Debug for CooldownTask<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CooldownTask")
+            .field("duration", &self.duration)
+            .field("ready_at", &self.ready_at)
+            .field("active", &self.active)
+            .finish()
+    }
+}
+
+/// Condition that fails while its [`CooldownTask`] is cooling down.
+pub struct CooldownCondition<M = ()> {
+    clock: TaskClock<M>,
+    ready_at: Arc<Mutex<Option<Scalar>>>,
+}
+
+impl<M> Condition<M> for CooldownCondition<M> {
+    fn validate(&self, memory: &M) -> bool {
+        self.ready_at
+            .lock()
+            .ok()
+            .and_then(|ready_at| *ready_at)
+            .map(|ready_at| (self.clock)(memory) >= ready_at)
+            .unwrap_or(true)
+    }
+}
+
+impl<M> std::fmt::Debug for CooldownCondition<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CooldownCondition")
+            .field("ready_at", &self.ready_at)
+            .finish()
+    }
+}
+
+/// Wrapper around task that stops it with [`TaskStopReason::Cancelled`] when it runs for longer
+/// than given duration, which also unlocks this task. Task stopped by its owner after timeout has
+/// passed is also stopped with [`TaskStopReason::Cancelled`].
+///
+/// # Example
+/// ```
+/// use emergent::prelude::*;
+/// use std::sync::Arc;
+///
+/// struct Memory {
+///     time: Scalar,
+///     cancelled: bool,
+/// }
+///
+/// let mut task = TimeoutTask::new(
+///     ClosureTask::default()
+///         .locked(|_: &Memory| true)
+///         .stop(|m: &mut Memory, reason| m.cancelled = reason == TaskStopReason::Cancelled),
+///     2.0,
+///     Arc::new(|m: &Memory| m.time),
+/// );
+/// let mut memory = Memory { time: 0.0, cancelled: false };
+/// task.on_enter(&mut memory);
+/// memory.time = 1.0;
+/// task.on_update(&mut memory);
+/// assert!(task.is_locked(&memory));
+/// memory.time = 2.0;
+/// task.on_update(&mut memory);
+/// assert!(!task.is_locked(&memory));
+/// assert!(task.has_expired());
+/// assert!(memory.cancelled);
+/// ```
+pub struct TimeoutTask<M = ()> {
+    task: Box<dyn Task<M>>,
+    duration: Scalar,
+    clock: TaskClock<M>,
+    started_at: Option<Scalar>,
+    expired: bool,
+}
+
+impl<M> TimeoutTask<M> {
+    /// Constructs wrapper around task with timeout duration and clock.
+    pub fn new<T>(task: T, duration: Scalar, clock: TaskClock<M>) -> Self
+    where
+        T: Task<M> + 'static,
+    {
+        Self::new_raw(Box::new(task), duration, clock)
+    }
+
+    /// Constructs wrapper around raw task with timeout duration and clock.
+    pub fn new_raw(task: Box<dyn Task<M>>, duration: Scalar, clock: TaskClock<M>) -> Self {
+        Self {
+            task,
+            duration,
+            clock,
+            started_at: None,
+            expired: false,
+        }
+    }
+
+    /// Returns timeout duration.
+    pub fn duration(&self) -> Scalar {
+        self.duration
+    }
+
+    /// Tells if task got stopped because of timeout.
+    pub fn has_expired(&self) -> bool {
+        self.expired
+    }
+
+    /// Returns immutable access to wrapped task.
+    pub fn task(&self) -> &dyn Task<M> {
+        self.task.as_ref()
+    }
+
+    /// Returns mutable access to wrapped task.
+    pub fn task_mut(&mut self) -> &mut dyn Task<M> {
+        self.task.as_mut()
+    }
+
+    fn is_running(&self) -> bool {
+        self.started_at.is_some() && !self.expired
+    }
+
+    fn is_overdue(&self, memory: &M) -> bool {
+        self.started_at
+            .map(|started_at| (self.clock)(memory) - started_at >= self.duration)
+            .unwrap_or_default()
+    }
+
+    fn expire(&mut self, memory: &mut M) -> bool {
+        if self.is_running() && self.is_overdue(memory) {
+            self.task.on_stop(memory, TaskStopReason::Cancelled);
+            self.expired = true;
+            return true;
+        }
+        false
+    }
+}
+
+impl<M> Task<M> for TimeoutTask<M> {
+    fn is_locked(&self, memory: &M) -> bool {
+        self.is_running() && !self.is_overdue(memory) && self.task.is_locked(memory)
+    }
+
+    fn on_enter(&mut self, memory: &mut M) {
+        self.started_at = Some((self.clock)(memory));
+        self.expired = false;
+        self.task.on_enter(memory);
+    }
+
+    fn on_exit(&mut self, memory: &mut M) {
+        self.on_stop(memory, TaskStopReason::Cancelled);
+    }
+
+    fn on_stop(&mut self, memory: &mut M, reason: TaskStopReason) {
+        if !self.expire(memory) && self.is_running() {
+            self.task.on_stop(memory, reason);
+        }
+        self.started_at = None;
+    }
+
+    fn on_update(&mut self, memory: &mut M) {
+        if !self.expire(memory) && self.is_running() {
+            self.task.on_update(memory);
+        }
+    }
+
+    fn on_process(&mut self, memory: &mut M) -> bool {
+        if self.expire(memory) {
+            return true;
+        }
+        self.is_running() && self.task.on_process(memory)
+    }
+}
+
+impl<M> std::fmt::Debug for TimeoutTask<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TimeoutTask")
+            .field("duration", &self.duration)
+            .field("started_at", &self.started_at)
+            .field("expired", &self.expired)
+            .finish()
+    }
+}
+
+/// Wrapper around task that runs it only as long as condition is met.
+///
+/// Condition is checked on enter (task doesn't start when it's not met) and on every update and
+/// process, aborting task with [`TaskStopReason::Cancelled`] as soon as condition is not met, which
+/// also unlocks this task.
+///
+/// # Example
+/// ```
+/// use emergent::prelude::*;
+///
+/// let mut task = GuardTask::new(
+///     ClosureCondition::new(|m: &usize| *m < 3),
+///     ClosureTask::default()
+///         .locked(|_: &usize| true)
+///         .update(|m: &mut usize| *m += 1),
+/// );
+/// let mut memory = 0;
+/// task.on_enter(&mut memory);
+/// for _ in 0..5 {
+///     task.on_update(&mut memory);
+/// }
+/// assert_eq!(memory, 3);
+/// assert!(!task.is_locked(&memory));
+/// assert!(!task.is_active());
+/// ```
+pub struct GuardTask<M = ()> {
+    condition: Arc<dyn Condition<M>>,
+    task: Box<dyn Task<M>>,
+    active: bool,
+}
+
+impl<M> GuardTask<M> {
+    /// Constructs wrapper around task with guard condition.
+    pub fn new<C, T>(condition: C, task: T) -> Self
+    where
+        C: Condition<M> + 'static,
+        T: Task<M> + 'static,
+    {
+        Self::new_raw(Arc::new(condition), Box::new(task))
+    }
+
+    /// Constructs wrapper around raw task with shared guard condition.
+    pub fn new_raw(condition: Arc<dyn Condition<M>>, task: Box<dyn Task<M>>) -> Self {
+        Self {
+            condition,
+            task,
+            active: false,
+        }
+    }
+
+    /// Returns shared guard condition.
+    pub fn condition(&self) -> Arc<dyn Condition<M>> {
+        self.condition.clone()
+    }
+
+    /// Tells if wrapped task is running.
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Returns immutable access to wrapped task.
+    pub fn task(&self) -> &dyn Task<M> {
+        self.task.as_ref()
+    }
+
+    /// Returns mutable access to wrapped task.
+    pub fn task_mut(&mut self) -> &mut dyn Task<M> {
+        self.task.as_mut()
+    }
+
+    fn abort(&mut self, memory: &mut M) -> bool {
+        if self.active && !self.condition.validate(memory) {
+            self.task.on_stop(memory, TaskStopReason::Cancelled);
+            self.active = false;
+            return true;
+        }
+        false
+    }
+}
+
+impl<M> Task<M> for GuardTask<M> {
+    fn is_locked(&self, memory: &M) -> bool {
+        self.active && self.condition.validate(memory) && self.task.is_locked(memory)
+    }
+
+    fn on_enter(&mut self, memory: &mut M) {
+        if self.condition.validate(memory) {
+            self.task.on_enter(memory);
+            self.active = true;
+        }
+    }
+
+    fn on_exit(&mut self, memory: &mut M) {
+        self.on_stop(memory, TaskStopReason::Cancelled);
+    }
+
+    fn on_stop(&mut self, memory: &mut M, reason: TaskStopReason) {
+        if self.active {
+            self.task.on_stop(memory, reason);
+            self.active = false;
+        }
+    }
+
+    fn on_update(&mut self, memory: &mut M) {
+        if !self.abort(memory) && self.active {
+            self.task.on_update(memory);
+        }
+    }
+
+    fn on_process(&mut self, memory: &mut M) -> bool {
+        if self.abort(memory) {
+            return true;
+        }
+        self.active && self.task.on_process(memory)
+    }
+}
+
+impl<M> std::fmt::Debug for GuardTask<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GuardTask")
+            .field("active", &self.active)
+            .finish()
+    }
+}
+
+/// Wrapper around task that runs it only when condition is met on enter, otherwise it does
+/// nothing.
+///
+/// Used to make optional steps: state with this task can use always passing condition, so failing
+/// wrapped task condition won't fail parent sequences.
+///
+/// # Example
+/// ```
+/// use emergent::prelude::*;
+///
+/// let mut task = AlwaysSucceedTask::new(
+///     ClosureCondition::new(|m: &usize| *m > 0),
+///     ClosureTask::default().enter(|m: &mut usize| *m += 1),
+/// );
+/// let mut memory = 0;
+/// task.on_enter(&mut memory);
+/// assert_eq!(memory, 0);
+/// assert!(!task.is_active());
+/// memory = 1;
+/// task.on_enter(&mut memory);
+/// assert_eq!(memory, 2);
+/// assert!(task.is_active());
+/// ```
+pub struct AlwaysSucceedTask<M = ()> {
+    condition: Box<dyn Condition<M>>,
+    task: Box<dyn Task<M>>,
+    active: bool,
+}
+
+impl<M> AlwaysSucceedTask<M> {
+    /// Constructs wrapper around task with condition.
+    pub fn new<C, T>(condition: C, task: T) -> Self
+    where
+        C: Condition<M> + 'static,
+        T: Task<M> + 'static,
+    {
+        Self::new_raw(Box::new(condition), Box::new(task))
+    }
+
+    /// Constructs wrapper around raw task with raw condition.
+    pub fn new_raw(condition: Box<dyn Condition<M>>, task: Box<dyn Task<M>>) -> Self {
+        Self {
+            condition,
+            task,
+            active: false,
+        }
+    }
+
+    /// Tells if wrapped task is running.
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Returns immutable access to wrapped task.
+    pub fn task(&self) -> &dyn Task<M> {
+        self.task.as_ref()
+    }
+
+    /// Returns mutable access to wrapped task.
+    pub fn task_mut(&mut self) -> &mut dyn Task<M> {
+        self.task.as_mut()
+    }
+}
+
+impl<M> Task<M> for AlwaysSucceedTask<M> {
+    fn is_locked(&self, memory: &M) -> bool {
+        self.active && self.task.is_locked(memory)
+    }
+
+    fn on_enter(&mut self, memory: &mut M) {
+        if self.condition.validate(memory) {
+            self.task.on_enter(memory);
+            self.active = true;
+        }
+    }
+
+    fn on_exit(&mut self, memory: &mut M) {
+        self.on_stop(memory, TaskStopReason::Cancelled);
+    }
+
+    fn on_stop(&mut self, memory: &mut M, reason: TaskStopReason) {
+        if self.active {
+            self.task.on_stop(memory, reason);
+            self.active = false;
+        }
+    }
+
+    fn on_update(&mut self, memory: &mut M) {
+        if self.active {
+            self.task.on_update(memory);
+        }
+    }
+
+    fn on_process(&mut self, memory: &mut M) -> bool {
+        self.active && self.task.on_process(memory)
+    }
+}
+
+impl<M> std::fmt::Debug for AlwaysSucceedTask<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AlwaysSucceedTask")
+            .field("active", &self.active)
+            .finish()
+    }
+}
+
+/// Wrapper around task that inverts success of its node: node succeeds when wrapped node fails and
+/// fails when wrapped node succeeds.
+///
+/// Node success is decided by its condition, so node with this task has to use inverted wrapped
+/// condition (see [`Self::condition`]). Wrapped task runs only when wrapped condition is met on
+/// enter, so inverted node entered because wrapped node has failed does not run task of failed
+/// node, while inverted node entered regardless of its condition (for example as tree root) runs
+/// wrapped node as usual.
+///
+/// # Example
+/// ```
+/// use emergent::prelude::*;
+///
+/// let mut task = InverterTask::new(
+///     ClosureCondition::new(|m: &usize| *m > 0),
+///     ClosureTask::default().enter(|m: &mut usize| *m += 1),
+/// );
+/// let condition = task.condition();
+/// let mut memory = 0;
+/// assert!(condition.validate(&memory));
+/// task.on_enter(&mut memory);
+/// assert_eq!(memory, 0);
+/// assert!(!task.is_active());
+/// memory = 1;
+/// assert!(!condition.validate(&memory));
+/// task.on_enter(&mut memory);
+/// assert_eq!(memory, 2);
+/// assert!(task.is_active());
+/// ```
+pub struct InverterTask<M = ()> {
+    condition: Arc<dyn Condition<M>>,
+    task: Box<dyn Task<M>>,
+    active: bool,
+}
+
+impl<M> InverterTask<M> {
+    /// Constructs wrapper around task with its condition.
+    pub fn new<C, T>(condition: C, task: T) -> Self
+    where
+        C: Condition<M> + 'static,
+        T: Task<M> + 'static,
+    {
+        Self::new_raw(Arc::new(condition), Box::new(task))
+    }
+
+    /// Constructs wrapper around raw task with its shared condition.
+    pub fn new_raw(condition: Arc<dyn Condition<M>>, task: Box<dyn Task<M>>) -> Self {
+        Self {
+            condition,
+            task,
+            active: false,
+        }
+    }
+
+    /// Returns inverted condition of wrapped task, which tells if inverted node succeeds.
+    pub fn condition(&self) -> ConditionInvert<M>
+    where
+        M: 'static,
+    {
+        ConditionInvert::new(self.condition.clone())
+    }
+
+    /// Tells if wrapped task is running.
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Returns immutable access to wrapped task.
+    pub fn task(&self) -> &dyn Task<M> {
+        self.task.as_ref()
+    }
+
+    /// Returns mutable access to wrapped task.
+    pub fn task_mut(&mut self) -> &mut dyn Task<M> {
+        self.task.as_mut()
+    }
+}
+
+impl<M> Task<M> for InverterTask<M> {
+    fn is_locked(&self, memory: &M) -> bool {
+        self.active && self.task.is_locked(memory)
+    }
+
+    fn on_enter(&mut self, memory: &mut M) {
+        if self.condition.validate(memory) {
+            self.task.on_enter(memory);
+            self.active = true;
+        }
+    }
+
+    fn on_exit(&mut self, memory: &mut M) {
+        self.on_stop(memory, TaskStopReason::Cancelled);
+    }
+
+    fn on_stop(&mut self, memory: &mut M, reason: TaskStopReason) {
+        if self.active {
+            self.task.on_stop(memory, reason);
+            self.active = false;
+        }
+    }
+
+    fn on_update(&mut self, memory: &mut M) {
+        if self.active {
+            self.task.on_update(memory);
+        }
+    }
+
+    fn on_process(&mut self, memory: &mut M) -> bool {
+        self.active && self.task.on_process(memory)
+    }
+}
+
+impl<M> std::fmt::Debug for InverterTask<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InverterTask")
+            .field("active", &self.active)
+            .finish()
+    }
+}
+
+/// Decides what happens to undo records when a journaled transaction commits.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum TransactionCommitPolicy {
+    /// Commit accepts this transaction's changes and discards its undo log.
+    ///
+    /// Parent transaction rollbacks won't undo changes recorded by this transaction.
+    #[default]
+    Isolated,
+    /// Commit moves this transaction's undo log into its parent transaction.
+    ///
+    /// Parent transaction rollbacks can still undo changes recorded by this transaction.
+    MergeIntoParent,
+}
+
+/// Stack of undo records used by journaled transactions.
+///
+/// Each transaction frame can also contain named savepoints that allow to roll back only changes
+/// recorded after them, without dropping the whole frame.
+///
+/// # Example
+/// ```
+/// use emergent::prelude::*;
+///
+/// let mut journal = TransactionJournal::default();
+/// journal.begin();
+/// journal.record(1);
+/// journal.savepoint("smelt");
+/// journal.record(2);
+/// journal.record(3);
+/// assert_eq!(journal.savepoint_entries("smelt"), &[2, 3]);
+/// assert_eq!(journal.rollback_to_savepoint("smelt").collect::<Vec<_>>(), vec![3, 2]);
+/// assert_eq!(journal.entries(), &[1]);
+/// journal.begin();
+/// journal.record(4);
+/// assert_eq!(journal.rollback_all().collect::<Vec<_>>(), vec![4, 1]);
+/// assert_eq!(journal.depth(), 0);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionJournal<U> {
+    frames: Vec<TransactionFrame<U>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TransactionFrame<U> {
+    undos: Vec<U>,
+    savepoints: Vec<(String, usize)>,
+}
+
+impl<U> Default for TransactionFrame<U> {
+    fn default() -> Self {
+        Self {
+            undos: Vec::new(),
+            savepoints: Vec::new(),
+        }
+    }
+}
+
+impl<U> TransactionFrame<U> {
+    fn savepoint_index(&self, name: &str) -> Option<usize> {
+        self.savepoints.iter().rposition(|(n, _)| n == name)
+    }
+}
+
+impl<U> Default for TransactionJournal<U> {
+    fn default() -> Self {
+        Self { frames: Vec::new() }
+    }
+}
+
+impl<U> TransactionJournal<U> {
+    /// Starts new transaction frame.
+    pub fn begin(&mut self) {
+        self.frames.push(TransactionFrame::default());
+    }
+
+    /// Records undo entry in currently active transaction frame.
+    ///
+    /// Returns `false` when there is no active transaction frame.
+    pub fn record(&mut self, undo: U) -> bool {
+        let Some(frame) = self.frames.last_mut() else {
+            return false;
+        };
+        frame.undos.push(undo);
+        true
+    }
+
+    /// Commits currently active transaction frame.
+    ///
+    /// Savepoints of committed frame are released.
+    pub fn commit(&mut self, policy: TransactionCommitPolicy) {
+        let Some(frame) = self.frames.pop() else {
+            return;
+        };
+
+        if policy == TransactionCommitPolicy::MergeIntoParent
+            && let Some(parent) = self.frames.last_mut()
+        {
+            parent.undos.extend(frame.undos);
+        }
+    }
+
+    /// Rolls back currently active transaction frame.
+    pub fn rollback(&mut self) -> impl Iterator<Item = U> {
+        self.frames
+            .pop()
+            .unwrap_or_default()
+            .undos
+            .into_iter()
+            .rev()
+    }
+
+    /// Rolls back all active transaction frames, from the innermost to the outermost one.
+    pub fn rollback_all(&mut self) -> impl Iterator<Item = U> {
+        std::mem::take(&mut self.frames)
+            .into_iter()
+            .rev()
+            .flat_map(|frame| frame.undos.into_iter().rev())
+    }
+
+    /// Creates named savepoint in currently active transaction frame.
+    ///
+    /// Savepoint with the same name as already existing one shadows it until released.
+    /// Returns `false` when there is no active transaction frame.
+    pub fn savepoint(&mut self, name: impl ToString) -> bool {
+        let Some(frame) = self.frames.last_mut() else {
+            return false;
+        };
+        frame.savepoints.push((name.to_string(), frame.undos.len()));
+        true
+    }
+
+    /// Tells if currently active transaction frame has savepoint with given name.
+    pub fn has_savepoint(&self, name: &str) -> bool {
+        self.frames
+            .last()
+            .and_then(|frame| frame.savepoint_index(name))
+            .is_some()
+    }
+
+    /// Returns iterator over savepoints names of currently active transaction frame.
+    pub fn savepoints(&self) -> impl Iterator<Item = &str> {
+        self.frames
+            .last()
+            .into_iter()
+            .flat_map(|frame| frame.savepoints.iter().map(|(name, _)| name.as_str()))
+    }
+
+    /// Rolls back entries recorded after named savepoint in currently active transaction frame.
+    ///
+    /// Savepoint itself is kept, while savepoints created after it are released.
+    /// Yields nothing when there is no such savepoint.
+    pub fn rollback_to_savepoint(&mut self, name: &str) -> impl Iterator<Item = U> {
+        let undos = match self.frames.last_mut() {
+            Some(frame) => match frame.savepoint_index(name) {
+                Some(index) => {
+                    frame.savepoints.truncate(index + 1);
+                    let start = frame.savepoints[index].1;
+                    frame.undos.split_off(start)
+                }
+                None => Vec::new(),
+            },
+            None => Vec::new(),
+        };
+        undos.into_iter().rev()
+    }
+
+    /// Releases named savepoint (and savepoints created after it) in currently active transaction
+    /// frame, keeping recorded entries.
+    ///
+    /// Returns `false` when there is no such savepoint.
+    pub fn release_savepoint(&mut self, name: &str) -> bool {
+        if let Some(frame) = self.frames.last_mut()
+            && let Some(index) = frame.savepoint_index(name)
+        {
+            frame.savepoints.truncate(index);
+            return true;
+        }
+        false
+    }
+
+    /// Returns entries recorded in currently active transaction frame, in order of recording.
+    pub fn entries(&self) -> &[U] {
+        self.frames
+            .last()
+            .map(|frame| frame.undos.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Returns entries recorded after named savepoint in currently active transaction frame, in
+    /// order of recording.
+    pub fn savepoint_entries(&self, name: &str) -> &[U] {
+        if let Some(frame) = self.frames.last()
+            && let Some(index) = frame.savepoint_index(name)
+        {
+            return &frame.undos[frame.savepoints[index].1..];
+        }
+        &[]
+    }
+
+    /// Returns iterator over entries of all active transaction frames, from the outermost to the
+    /// innermost frame, in order of recording.
+    pub fn all_entries(&self) -> impl Iterator<Item = &U> {
+        self.frames.iter().flat_map(|frame| frame.undos.iter())
+    }
+
+    /// Tells if there is an active transaction frame.
+    pub fn is_active(&self) -> bool {
+        !self.frames.is_empty()
+    }
+
+    /// Returns number of active transaction frames.
+    pub fn depth(&self) -> usize {
+        self.frames.len()
+    }
+}
+
+/// Memory that stores and applies journaled transaction undo records.
+///
+/// Savepoints and depth related methods are optional - by default memory reports no support for
+/// savepoints and no active transaction frames.
+pub trait TransactionalMemory {
+    /// Domain-specific undo record.
+    type Undo;
+
+    /// Starts new transaction frame.
+    fn begin_transaction(&mut self);
+
+    /// Commits currently active transaction frame.
+    fn commit_transaction(&mut self, policy: TransactionCommitPolicy);
+
+    /// Rolls back currently active transaction frame.
+    fn rollback_transaction(&mut self);
+
+    /// Records undo entry in currently active transaction frame.
+    fn record_undo(&mut self, undo: Self::Undo);
+
+    /// Creates named savepoint in currently active transaction frame.
+    ///
+    /// Returns `false` when savepoint could not be created.
+    #[allow(unused_variables)]
+    fn savepoint_transaction(&mut self, name: &str) -> bool {
+        false
+    }
+
+    /// Rolls back changes recorded after named savepoint in currently active transaction frame.
+    ///
+    /// Returns `false` when there is no such savepoint.
+    #[allow(unused_variables)]
+    fn rollback_to_savepoint(&mut self, name: &str) -> bool {
+        false
+    }
+
+    /// Releases named savepoint in currently active transaction frame, keeping recorded changes.
+    ///
+    /// Returns `false` when there is no such savepoint.
+    #[allow(unused_variables)]
+    fn release_savepoint(&mut self, name: &str) -> bool {
+        false
+    }
+
+    /// Returns number of active transaction frames.
+    ///
+    /// Memory that doesn't override it reports no active frames, so default
+    /// [`Self::rollback_all_transactions`] does nothing for it.
+    fn transaction_depth(&self) -> usize {
+        0
+    }
+
+    /// Rolls back all active transaction frames.
+    ///
+    /// Useful for abort paths that have to leave memory in state from before any transaction.
+    /// Default implementation rolls back as many frames as [`Self::transaction_depth`] reports, so
+    /// memory has to opt in by overriding either of these methods.
+    fn rollback_all_transactions(&mut self) {
+        for _ in 0..self.transaction_depth() {
+            self.rollback_transaction();
+        }
+    }
+}
+
+/// Wrapper around task that creates transaction scope with undo records stored in memory.
+///
+/// By default it opens new transaction frame, but when given savepoint name (see
+/// [`Self::savepoint`]), it creates savepoint in already active transaction frame instead, so
+/// interruption rolls back only changes made by this task, without dropping the whole frame.
+pub struct JournaledTransactionTask<M = ()> {
+    task: Box<dyn Task<M>>,
+    active: bool,
+    commit_policy: TransactionCommitPolicy,
+    savepoint: Option<String>,
+    savepoint_active: bool,
+}
+
+impl<M> JournaledTransactionTask<M> {
+    /// Constructs journaled transaction wrapper around task.
+    pub fn new<T>(task: T) -> Self
+    where
+        T: Task<M> + 'static,
+    {
+        Self::new_raw(Box::new(task))
+    }
+
+    /// Constructs journaled transaction wrapper around raw task.
+    pub fn new_raw(task: Box<dyn Task<M>>) -> Self {
+        Self {
+            task,
+            active: false,
+            commit_policy: TransactionCommitPolicy::default(),
+            savepoint: None,
+            savepoint_active: false,
+        }
+    }
+
+    /// Sets savepoint name.
+    ///
+    /// On enter task creates this savepoint in currently active transaction frame. Completion
+    /// releases it, while interruption rolls back to it and then releases it. When memory couldn't
+    /// create savepoint, task falls back to opening new transaction frame.
+    pub fn savepoint(mut self, name: impl ToString) -> Self {
+        self.savepoint = Some(name.to_string());
+        self
+    }
+
+    /// Returns savepoint name.
+    pub fn get_savepoint(&self) -> Option<&str> {
+        self.savepoint.as_deref()
+    }
+
+    /// Sets commit policy.
+    pub fn commit_policy(mut self, policy: TransactionCommitPolicy) -> Self {
+        self.commit_policy = policy;
+        self
+    }
+
+    /// Returns commit policy.
+    pub fn get_commit_policy(&self) -> TransactionCommitPolicy {
+        self.commit_policy
+    }
+
+    /// Returns immutable access to wrapped task.
+    pub fn task(&self) -> &dyn Task<M> {
+        self.task.as_ref()
+    }
+
+    /// Returns mutable access to wrapped task.
+    pub fn task_mut(&mut self) -> &mut dyn Task<M> {
+        self.task.as_mut()
+    }
+}
+
+impl<M> Task<M> for JournaledTransactionTask<M>
+where
+    M: TransactionalMemory,
+{
+    fn is_locked(&self, memory: &M) -> bool {
+        self.task.is_locked(memory)
+    }
+
+    fn on_enter(&mut self, memory: &mut M) {
+        self.savepoint_active = match &self.savepoint {
+            Some(name) => memory.savepoint_transaction(name),
+            None => false,
+        };
+        if !self.savepoint_active {
+            memory.begin_transaction();
+        }
+        self.task.on_enter(memory);
+        self.active = true;
+    }
+
+    fn on_exit(&mut self, memory: &mut M) {
+        self.on_stop(memory, TaskStopReason::Cancelled);
+    }
+
+    fn on_stop(&mut self, memory: &mut M, reason: TaskStopReason) {
+        self.task.on_stop(memory, reason);
+
+        if !self.active {
+            return;
+        }
+
+        match (reason, self.savepoint.as_deref()) {
+            (TaskStopReason::Completed, Some(name)) if self.savepoint_active => {
+                memory.release_savepoint(name);
+            }
+            (TaskStopReason::Completed, _) => {
+                memory.commit_transaction(self.commit_policy);
+            }
+            (TaskStopReason::Cancelled | TaskStopReason::Replaced, Some(name))
+                if self.savepoint_active =>
+            {
+                memory.rollback_to_savepoint(name);
+                memory.release_savepoint(name);
+            }
+            (TaskStopReason::Cancelled | TaskStopReason::Replaced, _) => {
+                memory.rollback_transaction();
+            }
+        }
+
+        self.active = false;
+        self.savepoint_active = false;
+    }
+
+    fn on_update(&mut self, memory: &mut M) {
+        self.task.on_update(memory);
+    }
+
+    fn on_process(&mut self, memory: &mut M) -> bool {
+        self.task.on_process(memory)
+    }
+}
+
+impl<M> std::fmt::Debug for JournaledTransactionTask<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JournaledTransactionTask")
+            .field("active", &self.active)
+            .field("commit_policy", &self.commit_policy)
+            .field("savepoint", &self.savepoint)
+            .finish()
+    }
+}
+
+/// Wrapper around task that creates transaction scope with custom begin/commit/rollback hooks.
+#[allow(clippy::type_complexity)]
+pub struct TransactionScopeTask<M = ()> {
+    task: Box<dyn Task<M>>,
+    active: bool,
+    begin: Option<Box<dyn FnMut(&mut M) + Send + Sync>>,
+    commit: Option<Box<dyn FnMut(&mut M) + Send + Sync>>,
+    rollback: Option<Box<dyn FnMut(&mut M) + Send + Sync>>,
+}
+
+impl<M> TransactionScopeTask<M> {
+    /// Constructs transaction wrapper around task.
+    pub fn new<T>(task: T) -> Self
+    where
+        T: Task<M> + 'static,
+    {
+        Self::new_raw(Box::new(task))
+    }
+
+    /// Constructs transaction wrapper around raw task.
+    pub fn new_raw(task: Box<dyn Task<M>>) -> Self {
+        Self {
+            task,
+            active: false,
+            begin: None,
+            commit: None,
+            rollback: None,
+        }
+    }
+
+    /// Sets begin transaction hook.
+    pub fn begin<F>(mut self, f: F) -> Self
+    where
+        F: FnMut(&mut M) + 'static + Send + Sync,
+    {
+        self.begin = Some(Box::new(f));
+        self
+    }
+
+    /// Sets commit transaction hook.
+    pub fn commit<F>(mut self, f: F) -> Self
+    where
+        F: FnMut(&mut M) + 'static + Send + Sync,
+    {
+        self.commit = Some(Box::new(f));
+        self
+    }
+
+    /// Sets rollback transaction hook.
+    pub fn rollback<F>(mut self, f: F) -> Self
+    where
+        F: FnMut(&mut M) + 'static + Send + Sync,
+    {
+        self.rollback = Some(Box::new(f));
+        self
+    }
+
+    /// Returns immutable access to wrapped task.
+    pub fn task(&self) -> &dyn Task<M> {
+        self.task.as_ref()
+    }
+
+    /// Returns mutable access to wrapped task.
+    pub fn task_mut(&mut self) -> &mut dyn Task<M> {
+        self.task.as_mut()
+    }
+}
+
+impl<M> Task<M> for TransactionScopeTask<M> {
+    fn is_locked(&self, memory: &M) -> bool {
+        self.task.is_locked(memory)
+    }
+
+    fn on_enter(&mut self, memory: &mut M) {
+        if let Some(f) = &mut self.begin {
+            f(memory);
+        }
+        self.task.on_enter(memory);
+        self.active = true;
+    }
+
+    fn on_exit(&mut self, memory: &mut M) {
+        self.on_stop(memory, TaskStopReason::Cancelled);
+    }
+
+    fn on_stop(&mut self, memory: &mut M, reason: TaskStopReason) {
+        self.task.on_stop(memory, reason);
+
+        if !self.active {
+            return;
+        }
+
+        match reason {
+            TaskStopReason::Completed => {
+                if let Some(f) = &mut self.commit {
+                    f(memory);
+                }
+            }
+            TaskStopReason::Cancelled | TaskStopReason::Replaced => {
+                if let Some(f) = &mut self.rollback {
+                    f(memory);
+                }
+            }
+        }
+
+        self.active = false;
+    }
+
+    fn on_update(&mut self, memory: &mut M) {
+        self.task.on_update(memory);
     }
 
     fn on_process(&mut self, memory: &mut M) -> bool {
@@ -556,3 +1835,190 @@ impl<M> std::fmt::Debug for TransactionScopeTask<M> {
             .finish()
     }
 }
+
+/// Boxed future run by [`AsyncTask`].
+#[cfg(feature = "async")]
+pub type AsyncTaskFuture<T> = std::pin::Pin<Box<dyn std::future::Future<Output = T> + Send>>;
+
+/// Factory creating futures run by [`AsyncTask`].
+#[cfg(feature = "async")]
+pub type AsyncTaskFactory<M, T> = Box<dyn Fn(&M) -> AsyncTaskFuture<T> + Send + Sync>;
+
+/// Task that runs future created by factory when task starts its work.
+///
+/// Future is created on enter, polled on every process and dropped on stop if it is still
+/// pending, which cancels its work. Output of completed future is passed to completion closure
+/// together with memory, so that is the place to apply results of async work.
+///
+/// This task does not depend on any async runtime: future is polled with no-op waker by default,
+/// but if future is driven by some executor's reactor (timers, sockets) you can provide waker
+/// that for example schedules next AI tick with [`AsyncTask::waker`].
+///
+/// Available with `async` feature.
+///
+/// # Example
+/// ```
+/// use emergent::prelude::*;
+///
+/// struct Memory {
+///     target: usize,
+///     path: Vec<usize>,
+/// }
+///
+/// let mut task = AsyncTask::new(|m: &Memory| {
+///     let target = m.target;
+///     async move { (0..=target).collect::<Vec<_>>() }
+/// })
+/// .complete(|path, m: &mut Memory| m.path = path)
+/// .lock_while_pending(true);
+///
+/// let mut memory = Memory { target: 3, path: vec![] };
+/// task.on_enter(&mut memory);
+/// assert!(task.is_pending());
+/// assert!(task.is_locked(&memory));
+/// assert!(task.on_process(&mut memory));
+/// assert!(!task.is_pending());
+/// assert!(!task.is_locked(&memory));
+/// assert_eq!(memory.path, vec![0, 1, 2, 3]);
+/// ```
+#[cfg(feature = "async")]
+#[allow(clippy::type_complexity)]
+pub struct AsyncTask<M = (), T = ()> {
+    factory: AsyncTaskFactory<M, T>,
+    complete: Option<Box<dyn FnMut(T, &mut M) + Send + Sync>>,
+    cancel: Option<Box<dyn FnMut(&mut M) + Send + Sync>>,
+    // futures are not required to be `Sync`, mutex makes this task `Sync` anyway.
+    future: Mutex<Option<AsyncTaskFuture<T>>>,
+    pending: bool,
+    lock_while_pending: bool,
+    waker: std::task::Waker,
+}
+
+#[cfg(feature = "async")]
+impl<M, T> AsyncTask<M, T> {
+    /// Constructs new async task from future factory.
+    pub fn new<F, R>(factory: F) -> Self
+    where
+        F: Fn(&M) -> R + 'static + Send + Sync,
+        R: std::future::Future<Output = T> + 'static + Send,
+    {
+        Self::new_raw(Box::new(move |memory| Box::pin(factory(memory))))
+    }
+
+    /// Constructs new async task from raw future factory.
+    pub fn new_raw(factory: AsyncTaskFactory<M, T>) -> Self {
+        Self {
+            factory,
+            complete: None,
+            cancel: None,
+            future: Mutex::new(None),
+            pending: false,
+            lock_while_pending: false,
+            waker: std::task::Waker::noop().clone(),
+        }
+    }
+
+    /// Sets closure called with future output when future completes.
+    pub fn complete<F>(mut self, f: F) -> Self
+    where
+        F: FnMut(T, &mut M) + 'static + Send + Sync,
+    {
+        self.complete = Some(Box::new(f));
+        self
+    }
+
+    /// Sets closure called when task stops while future is still pending.
+    pub fn cancel<F>(mut self, f: F) -> Self
+    where
+        F: FnMut(&mut M) + 'static + Send + Sync,
+    {
+        self.cancel = Some(Box::new(f));
+        self
+    }
+
+    /// Marks if task should be locked as long as future is pending.
+    pub fn lock_while_pending(mut self, value: bool) -> Self {
+        self.lock_while_pending = value;
+        self
+    }
+
+    /// Tells if task is locked as long as future is pending.
+    pub fn get_lock_while_pending(&self) -> bool {
+        self.lock_while_pending
+    }
+
+    /// Sets waker passed to future when it gets polled.
+    pub fn waker(mut self, waker: std::task::Waker) -> Self {
+        self.waker = waker;
+        self
+    }
+
+    /// Tells if future has started and did not complete yet.
+    pub fn is_pending(&self) -> bool {
+        self.pending
+    }
+
+    fn poll(&mut self, memory: &mut M) -> bool {
+        let future = self
+            .future
+            .get_mut()
+            .unwrap_or_else(|error| error.into_inner());
+        let Some(pinned) = future.as_mut() else {
+            return false;
+        };
+        let mut context = std::task::Context::from_waker(&self.waker);
+        match pinned.as_mut().poll(&mut context) {
+            std::task::Poll::Ready(output) => {
+                *future = None;
+                self.pending = false;
+                if let Some(f) = &mut self.complete {
+                    f(output, memory);
+                }
+                true
+            }
+            std::task::Poll::Pending => false,
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<M, T> Task<M> for AsyncTask<M, T> {
+    fn is_locked(&self, _: &M) -> bool {
+        self.lock_while_pending && self.pending
+    }
+
+    fn on_enter(&mut self, memory: &mut M) {
+        *self
+            .future
+            .get_mut()
+            .unwrap_or_else(|error| error.into_inner()) = Some((self.factory)(memory));
+        self.pending = true;
+    }
+
+    fn on_stop(&mut self, memory: &mut M, _: TaskStopReason) {
+        self.future
+            .get_mut()
+            .unwrap_or_else(|error| error.into_inner())
+            .take();
+        if self.pending {
+            self.pending = false;
+            if let Some(f) = &mut self.cancel {
+                f(memory);
+            }
+        }
+    }
+
+    fn on_process(&mut self, memory: &mut M) -> bool {
+        self.poll(memory)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<M, T> std::fmt::Debug for AsyncTask<M, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AsyncTask")
+            .field("pending", &self.pending)
+            .field("lock_while_pending", &self.lock_while_pending)
+            .finish()
+    }
+}
@@ -1,23 +1,52 @@
 use crate::{
     DecisionMakingTask, Scalar,
+    agent_pool::AgentPool,
     builders::{
-        behavior_tree::{BehaviorTree, BehaviorTreeTask},
-        lod::{Lod, LodMemory},
+        behavior_tree::{
+            BehaviorTree, BehaviorTreeLibrary, BehaviorTreeLibraryError, BehaviorTreeTask,
+        },
+        behavior_tree_dsl::{
+            BEHAVIOR_TREE_DSL_MAX_DEPTH, BehaviorTreeDslError, BehaviorTreeDslErrorKind,
+            BehaviorTreeRegistry,
+        },
+        lod::{Lod, LodController, LodHandoff, LodMemory, LodSummary},
     },
     condition::{ClosureCondition, Condition},
-    consideration::Consideration,
+    consideration::{ClosureConsideration, Consideration},
     decision_makers::{
-        machinery::{Machinery, MachineryChange, MachineryState},
-        parallelizer::{Parallelizer, ParallelizerState},
-        planner::{Planner, PlannerAction},
+        DecisionMaker, NoDecisionMaker, SingleDecisionMaker,
+        machinery::{
+            Machinery, MachineryBuilder, MachineryChange, MachineryState, MachineryTransitionMode,
+            MachineryTransitionTable,
+        },
+        mcts::{Mcts, MctsModel},
+        parallelizer::{
+            Parallelizer, ParallelizerFailurePolicy, ParallelizerOutcome, ParallelizerPolicy,
+            ParallelizerState, ParallelizerSuccessPolicy,
+        },
+        planner::{
+            PlanFailureReason, PlanOutcome, Planner, PlannerAction, PlannerActionInstance,
+            PlannerActionTemplate, PlannerBuilder, PlannerError, PlannerGoalRejection,
+            PlannerReplanReason,
+        },
         reasoner::{Reasoner, ReasonerState},
-        selector::{Selector, SelectorState},
-        sequencer::{Sequencer, SequencerState},
+        selector::{Selector, SelectorAbortMode, SelectorState},
+        sequencer::{Sequencer, SequencerMode, SequencerState},
+    },
+    memory::{
+        blackboard::{Blackboard, BlackboardAccess, BlackboardChangeKind},
+        datatable::{DataTable, DataTableCondition, DataTableConsideration},
+        entity::{
+            ComponentCondition, ComponentConsideration, EntityMemory, EntityWorld, ReadComponent,
+            WriteComponent,
+        },
+        scoped_blackboard::{BlackboardWriteTarget, ScopedBlackboard},
     },
-    memory::{blackboard::Blackboard, datatable::DataTable},
     task::{
-        ClosureTask, JournaledTransactionTask, NoTask, Task, TaskStopReason,
-        TransactionCommitPolicy, TransactionJournal, TransactionScopeTask, TransactionalMemory,
+        AlwaysSucceedTask, ClosureTask, CooldownCondition, CooldownTask, GuardTask, InverterTask,
+        JournaledTransactionTask, NoTask, RepeatTask, RetryTask, Task, TaskClock, TaskStopReason,
+        TimeoutTask, TransactionCommitPolicy, TransactionJournal, TransactionScopeTask,
+        TransactionalMemory,
     },
 };
 use std::{
@@ -234,6 +263,113 @@ fn test_machinery() {
     );
     assert_eq!(machinery.active_state(), None);
 }
+#[test]
+fn test_machinery_probabilistic() {
+    #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+    enum Animation {
+        Idle,
+        Scratch,
+        Yawn,
+        Sleep,
+    }
+
+    struct Memory {
+        tiredness: Scalar,
+    }
+
+    let make_machinery = |seed| {
+        MachineryBuilder::default()
+            .state(
+                Animation::Idle,
+                MachineryState::task(NoTask)
+                    .change(MachineryChange::new(Animation::Scratch, true))
+                    .change(MachineryChange::new(Animation::Yawn, true))
+                    .change(MachineryChange::new(
+                        Animation::Sleep,
+                        ClosureCondition::new(|m: &Memory| m.tiredness >= 1.0),
+                    ))
+                    .weight(
+                        Animation::Yawn,
+                        ClosureConsideration::new(|m: &Memory| m.tiredness),
+                    )
+                    .transition_mode(MachineryTransitionMode::Random),
+            )
+            .state(
+                Animation::Scratch,
+                MachineryState::task(NoTask).change(MachineryChange::new(Animation::Idle, true)),
+            )
+            .state(
+                Animation::Yawn,
+                MachineryState::task(NoTask).change(MachineryChange::new(Animation::Idle, true)),
+            )
+            .state(Animation::Sleep, MachineryState::task(NoTask))
+            .build()
+            .transition_table(MachineryTransitionTable::default().weight(
+                Animation::Idle,
+                Animation::Scratch,
+                2.0,
+            ))
+            .seed(seed)
+    };
+
+    let mut memory = Memory { tiredness: 0.5 };
+    let mut machinery = make_machinery(0);
+    // unreachable sleep state would absorb everything if estimation started from it.
+    machinery
+        .change_active_state(Some(Animation::Idle), &mut memory, true)
+        .unwrap();
+    assert_eq!(
+        machinery
+            .get_transition_table()
+            .get(&Animation::Idle, &Animation::Scratch),
+        Some(2.0)
+    );
+    let distribution = machinery.estimate_stationary_distribution(&memory, 200);
+    assert!((distribution[&Animation::Idle] - 0.5).abs() < 1.0e-3);
+    assert!((distribution[&Animation::Scratch] - 0.4).abs() < 1.0e-3);
+    assert!((distribution[&Animation::Yawn] - 0.1).abs() < 1.0e-3);
+    assert!(distribution[&Animation::Sleep] < 1.0e-3);
+
+    // empirical frequencies match estimation and same seed gives the same run.
+    let run = |seed| {
+        let mut machinery = make_machinery(seed);
+        let mut memory = Memory { tiredness: 0.5 };
+        machinery
+            .change_active_state(Some(Animation::Idle), &mut memory, true)
+            .unwrap();
+        (0..10000)
+            .map(|_| {
+                machinery.process(&mut memory);
+                *machinery.active_state().unwrap()
+            })
+            .collect::<Vec<_>>()
+    };
+    let history = run(7);
+    assert_eq!(history, run(7));
+    assert_ne!(history, run(8));
+    let frequency = |animation| {
+        history.iter().filter(|a| **a == animation).count() as Scalar / history.len() as Scalar
+    };
+    assert!((frequency(Animation::Idle) - 0.5).abs() < 0.02);
+    assert!((frequency(Animation::Scratch) - 0.4).abs() < 0.02);
+    assert!((frequency(Animation::Yawn) - 0.1).abs() < 0.02);
+
+    // absorbing state takes it all.
+    let memory = Memory { tiredness: 1.0 };
+    let distribution = machinery.estimate_stationary_distribution(&memory, 200);
+    assert!((distribution[&Animation::Sleep] - 1.0).abs() < 1.0e-3);
+
+    // zero weight changes never happen.
+    let mut machinery = make_machinery(0);
+    let mut memory = Memory { tiredness: 0.0 };
+    for _ in 0..100 {
+        machinery
+            .change_active_state(Some(Animation::Idle), &mut memory, true)
+            .unwrap();
+        assert!(machinery.process(&mut memory));
+        assert_eq!(machinery.active_state(), Some(&Animation::Scratch));
+    }
+}
 
 #[test]
 fn test_planner() {
@@ -430,205 +566,2044 @@ fn test_planner() {
 }
 
 #[test]
-fn test_sequencer() {
-    let mut memory = false;
-
-    let mut sequencer = Sequencer::new(
-        vec![
-            SequencerState::new(
-                ClosureCondition::new(|m| *m),
-                ClosureTask::default().enter(|m| *m = false),
-            ),
-            SequencerState::new(
-                ClosureCondition::new(|m| !m),
-                ClosureTask::default().enter(|m| *m = true),
-            ),
-        ],
-        true,
-        true,
-    );
+fn test_planner_goals() {
+    struct Memory {
+        hunger: Scalar,
+        has_food: bool,
+        has_money: bool,
+    }
 
-    assert!(sequencer.process(&mut memory));
-    assert!(memory);
-    assert!(sequencer.process(&mut memory));
-    assert!(!memory);
-    assert!(sequencer.process(&mut memory));
-    assert!(memory);
-}
+    let builder = || {
+        PlannerBuilder::new(NoDecisionMaker)
+            .action(
+                "buy",
+                PlannerAction::task(1.0, NoTask)
+                    .precondition("has_money")
+                    .postcondition("has_food"),
+            )
+            .action(
+                "eat",
+                PlannerAction::task(1.0, NoTask)
+                    .precondition("has_food")
+                    .postcondition("no_food"),
+            )
+            .action(
+                "wander",
+                PlannerAction::task(1.0, NoTask)
+                    .precondition("no_food")
+                    .precondition("no_money"),
+            )
+            .condition("has_money", ClosureCondition::new(|m: &Memory| m.has_money))
+            .condition("no_money", ClosureCondition::new(|m: &Memory| !m.has_money))
+            .condition("has_food", ClosureCondition::new(|m: &Memory| m.has_food))
+            .condition("no_food", ClosureCondition::new(|m: &Memory| !m.has_food))
+            .goal("eat", ClosureConsideration::new(|m: &Memory| m.hunger))
+            .goal("wander", 0.5)
+    };
 
-#[test]
-fn test_selector() {
-    let mut memory = false;
+    assert_eq!(
+        builder().goal("sleep", 1.0).build().unwrap_err(),
+        PlannerError::ActionDoesNotExists("sleep")
+    );
 
-    let mut selector = Selector::new(vec![
-        SelectorState::new(
-            ClosureCondition::new(|m| *m),
-            ClosureTask::default().enter(|m| *m = false),
-        ),
-        SelectorState::new(
-            ClosureCondition::new(|m| !m),
-            ClosureTask::default().enter(|m| *m = true),
-        ),
-    ]);
+    let mut planner = builder().build().unwrap();
+    let mut memory = Memory {
+        hunger: 1.0,
+        has_food: false,
+        has_money: false,
+    };
 
-    assert!(selector.process(&mut memory));
-    assert!(memory);
-    assert!(selector.process(&mut memory));
-    assert!(!memory);
-    assert!(selector.process(&mut memory));
-    assert!(memory);
-}
+    assert!(planner.process(&mut memory));
+    assert_eq!(planner.active_plan(), Some(["wander"].as_slice()));
+    assert_eq!(
+        planner.rejected_goals(),
+        &[("eat", PlannerGoalRejection::Unreachable)]
+    );
 
-#[test]
-fn test_parallelizer() {
-    let mut memory = false;
+    assert!(!planner.process(&mut memory));
+    assert_eq!(planner.active_plan(), None);
 
-    let mut parallelizer = Parallelizer::new(vec![
-        ParallelizerState::new(
-            ClosureCondition::new(|m| *m),
-            ClosureTask::default().enter(|m| *m = false),
-        ),
-        ParallelizerState::new(
-            ClosureCondition::new(|m| !m),
-            ClosureTask::default().enter(|m| *m = true),
-        ),
-    ]);
+    memory.has_money = true;
+    assert!(planner.process(&mut memory));
+    assert_eq!(planner.active_plan(), Some(["buy", "eat"].as_slice()));
+    assert!(planner.rejected_goals().is_empty());
 
-    assert!(parallelizer.process(&mut memory));
-    assert!(memory);
-    assert!(parallelizer.process(&mut memory));
-    assert!(!memory);
-    assert!(parallelizer.process(&mut memory));
-    assert!(memory);
+    memory.hunger = 0.0;
+    assert!(planner.process(&mut memory));
+    assert_eq!(
+        planner.active_plan(),
+        Some(["buy", "eat", "wander"].as_slice())
+    );
+    assert_eq!(
+        planner.rejected_goals(),
+        &[("eat", PlannerGoalRejection::Undesired)]
+    );
 }
 
 #[test]
-fn test_lod() {
-    const DELTA_TIME: Scalar = 1.0;
-
+fn test_planner_replanning() {
     struct Memory {
-        time_since_last_meal: Scalar,
-        hunger: Scalar,
+        time: Scalar,
+        key_nearby: bool,
+        has_pick: bool,
+        locked: bool,
+        stops: Vec<(&'static str, TaskStopReason)>,
     }
 
-    let mut lod = Lod::default()
-        // level 0 means agent is not in area and we optimize AI processing by not doing any work,
-        // so on task exit we just estimate how much more hungry agent can get during task time.
-        .level(ClosureTask::default().exit(|m: &mut LodMemory<Memory>| {
-            m.memory.hunger -= m.memory.time_since_last_meal;
-            println!("* Background hunger estimation: {}", m.memory.hunger);
-        }))
-        // level 1 means agent is in area and we want to accurately change its hunger level.
-        .level(ClosureTask::default().update(|m: &mut LodMemory<Memory>| {
-            m.memory.hunger -= DELTA_TIME;
-            println!("* Foreground hunger calculation: {}", m.memory.hunger);
-        }))
-        .build();
+    impl Memory {
+        fn new() -> Self {
+            Self {
+                time: 0.0,
+                key_nearby: true,
+                has_pick: false,
+                locked: false,
+                stops: vec![],
+            }
+        }
+    }
 
-    let mut memory = LodMemory {
-        lod_level: 0,
-        memory: Memory {
-            time_since_last_meal: 0.0,
-            hunger: 10.0,
-        },
+    let task = |name: &'static str| {
+        ClosureTask::default()
+            .locked(|m: &Memory| m.locked)
+            .stop(move |m: &mut Memory, reason| m.stops.push((name, reason)))
+    };
+    let clock = || -> TaskClock<Memory> { Arc::new(|m: &Memory| m.time) };
+    let build = || {
+        PlannerBuilder::new(SingleDecisionMaker::new("enter"))
+            .action(
+                "get_key",
+                PlannerAction::task(1.0, task("get_key"))
+                    .precondition("key_nearby")
+                    .postcondition("has_key"),
+            )
+            .action(
+                "open_door",
+                PlannerAction::task(1.0, task("open_door"))
+                    .precondition("has_key")
+                    .postcondition("door_open"),
+            )
+            .action(
+                "pick_lock",
+                PlannerAction::task(1.0, task("pick_lock"))
+                    .precondition("has_pick")
+                    .postcondition("door_open"),
+            )
+            .action(
+                "enter",
+                PlannerAction::task(1.0, task("enter"))
+                    .precondition("door_open")
+                    .postcondition("inside"),
+            )
+            .condition(
+                "key_nearby",
+                ClosureCondition::new(|m: &Memory| m.key_nearby),
+            )
+            .condition("has_pick", ClosureCondition::new(|m: &Memory| m.has_pick))
+            .condition("has_key", false)
+            .condition("door_open", false)
+            .condition("inside", false)
+            .build()
+            .unwrap()
     };
 
-    // we start with agent running in the background.
-    assert_eq!(lod.active_index(), None);
-    assert!(lod.process(&mut memory));
-    assert_eq!(lod.active_index(), Some(0));
-    // agent will now run in foreground and we assume 5 seconds have passed since last meal.
-    memory.lod_level = 1;
-    memory.memory.time_since_last_meal = 5.0;
-    assert!(lod.process(&mut memory));
-    assert_eq!(lod.active_index(), Some(1));
-    assert_eq!(memory.memory.hunger, 5.0);
-    lod.update(&mut memory);
-    assert_eq!(memory.memory.hunger, 4.0);
-}
+    let mut planner = build()
+        .replan_on_invalid_step(true)
+        .step_timeout(2.0, clock());
+    let mut memory = Memory::new();
+    assert!(planner.process(&mut memory));
+    assert_eq!(
+        planner.active_plan(),
+        Some(["get_key", "open_door", "enter"].as_slice())
+    );
+    assert_eq!(planner.replan_reason(), None);
 
-#[test]
-fn test_behavior_tree() {
-    struct Memory {
-        mode: bool,
-        counter: usize,
-    }
+    memory.key_nearby = false;
+    memory.has_pick = true;
+    assert!(planner.process(&mut memory));
+    assert_eq!(
+        planner.replan_reason(),
+        Some(PlannerReplanReason::InvalidStep)
+    );
+    assert_eq!(
+        planner.active_plan(),
+        Some(["pick_lock", "enter"].as_slice())
+    );
+    assert_eq!(memory.stops, vec![("get_key", TaskStopReason::Cancelled)]);
 
-    struct Countdown(pub usize);
+    memory.time = 1.0;
+    assert!(!planner.process(&mut memory));
+    assert_eq!(planner.replan_reason(), None);
 
-    impl Task<Memory> for Countdown {
-        fn is_locked(&self, memory: &Memory) -> bool {
-            memory.counter > 0
-        }
+    memory.time = 2.0;
+    assert!(planner.process(&mut memory));
+    assert_eq!(
+        planner.replan_reason(),
+        Some(PlannerReplanReason::StepTimeout)
+    );
+    assert_eq!(
+        planner.active_plan(),
+        Some(["pick_lock", "enter"].as_slice())
+    );
+    assert_eq!(memory.stops[1], ("pick_lock", TaskStopReason::Cancelled));
 
-        fn on_enter(&mut self, memory: &mut Memory) {
-            memory.counter = self.0;
-        }
+    let mut planner = build().replan_every(5.0, clock());
+    let mut memory = Memory::new();
+    assert!(planner.process(&mut memory));
 
-        fn on_update(&mut self, memory: &mut Memory) {
-            memory.counter = memory.counter.max(1) - 1;
-        }
-    }
+    memory.time = 5.0;
+    assert!(!planner.process(&mut memory));
+    assert_eq!(planner.replan_reason(), Some(PlannerReplanReason::Periodic));
+    assert!(memory.stops.is_empty());
 
-    struct FlipMode;
+    memory.time = 7.0;
+    memory.key_nearby = false;
+    memory.has_pick = true;
+    assert!(!planner.process(&mut memory));
+    assert_eq!(planner.replan_reason(), None);
 
-    impl Task<Memory> for FlipMode {
-        fn on_enter(&mut self, memory: &mut Memory) {
-            memory.mode = !memory.mode;
-        }
-    }
+    memory.time = 10.0;
+    assert!(planner.process(&mut memory));
+    assert_eq!(planner.replan_reason(), Some(PlannerReplanReason::Periodic));
+    assert_eq!(
+        planner.active_plan(),
+        Some(["pick_lock", "enter"].as_slice())
+    );
+    assert_eq!(memory.stops, vec![("get_key", TaskStopReason::Replaced)]);
 
-    struct IsMode(pub bool);
+    // replanning blocked by locked action does not report reason.
+    memory.time = 15.0;
+    memory.locked = true;
+    assert!(!planner.process(&mut memory));
+    assert_eq!(planner.replan_reason(), None);
+}
 
-    impl Condition<Memory> for IsMode {
-        fn validate(&self, memory: &Memory) -> bool {
-            memory.mode == self.0
+#[test]
+fn test_planner_explanation() {
+    struct Locked;
+
+    impl Task<bool> for Locked {
+        fn is_locked(&self, memory: &bool) -> bool {
+            *memory
         }
     }
 
-    // we define a tree that will perform ping-pong with delay:
-    // first we wait 2 turns, flip memory state, then wait 1 turn and flip back memory state.
-    let mut tree = BehaviorTree::selector(true)
-        .node(
-            BehaviorTree::sequence(IsMode(true))
-                .node(BehaviorTree::state(true, Countdown(2)))
-                .node(BehaviorTree::state(true, FlipMode)),
+    let mut planner = PlannerBuilder::new(NoDecisionMaker)
+        .action(
+            "chop",
+            PlannerAction::task(1.0, Locked).postcondition("wood"),
         )
-        .node(
-            BehaviorTree::sequence(IsMode(false))
-                .node(BehaviorTree::state(true, Countdown(1)))
-                .node(BehaviorTree::state(true, FlipMode)),
+        .action(
+            "build_furnace",
+            PlannerAction::task(2.0, NoTask)
+                .precondition("wood")
+                .postcondition("furnace"),
         )
-        .build();
-
-    let mut memory = Memory {
-        mode: true,
-        counter: 0,
-    };
+        .action(
+            "craft_sword",
+            PlannerAction::task(1.0, NoTask).precondition("iron"),
+        )
+        .condition("wood", false)
+        .condition("furnace", false)
+        .condition("iron", false)
+        .build()
+        .unwrap();
+    let mut memory = false;
 
-    assert!(tree.on_process(&mut memory));
-    assert!(memory.mode);
-    assert_eq!(memory.counter, 2);
+    let outcome = planner
+        .find_plan(Some("craft_sword"), &mut memory, false)
+        .unwrap();
+    let failure = outcome.failure().unwrap();
+    assert_eq!(failure.reason, PlanFailureReason::Unreachable);
+    assert_eq!(failure.explored, vec!["chop", "build_furnace"]);
+    assert_eq!(failure.best_path, vec!["chop"]);
+    assert_eq!(
+        failure.costs,
+        map! { _ : "chop" => 1.0, "build_furnace" => 2.0, }
+    );
+    assert_eq!(failure.missing_conditions, set! { "iron", });
+    assert!(!outcome.has_changed());
 
-    assert!(!tree.on_process(&mut memory));
-    tree.on_update(&mut memory);
-    assert!(memory.mode);
-    assert_eq!(memory.counter, 1);
+    assert_eq!(
+        planner.find_plan(Some("build_furnace"), &mut memory, false),
+        Ok(PlanOutcome::Started)
+    );
+    assert_eq!(
+        planner.active_plan(),
+        Some(["chop", "build_furnace"].as_slice())
+    );
+    assert_eq!(
+        planner.find_plan(Some("chop"), &mut memory, false),
+        Ok(PlanOutcome::Unchanged)
+    );
+    assert_eq!(
+        planner.find_plan(Some("unknown"), &mut memory, false),
+        Err(PlannerError::ActionDoesNotExists("unknown"))
+    );
 
-    assert!(!tree.on_process(&mut memory));
-    tree.on_update(&mut memory);
-    assert!(memory.mode);
-    assert_eq!(memory.counter, 0);
+    memory = true;
+    assert_eq!(
+        planner
+            .find_plan(None, &mut memory, false)
+            .unwrap()
+            .failure()
+            .map(|failure| &failure.reason),
+        Some(&PlanFailureReason::Locked("chop"))
+    );
+    assert_eq!(
+        planner.find_plan(None, &mut memory, true),
+        Ok(PlanOutcome::Cleared)
+    );
+    assert_eq!(planner.active_plan(), None);
+}
 
-    assert!(tree.on_process(&mut memory));
+#[test]
+fn test_planner_mutation() {
+    let task = |name: &'static str| {
+        ClosureTask::default().stop(move |m: &mut Vec<_>, reason| m.push((name, reason)))
+    };
+    let mut planner = PlannerBuilder::new(SingleDecisionMaker::new("eat"))
+        .action(
+            "hunt",
+            PlannerAction::task(5.0, task("hunt")).postcondition("food"),
+        )
+        .action(
+            "eat",
+            PlannerAction::task(1.0, task("eat")).precondition("food"),
+        )
+        .condition("food", false)
+        .build()
+        .unwrap();
+    let mut memory = vec![];
 
-    assert!(tree.on_process(&mut memory));
-    assert!(!memory.mode);
-    assert_eq!(memory.counter, 1);
+    assert!(planner.process(&mut memory));
+    assert_eq!(planner.active_plan(), Some(["hunt", "eat"].as_slice()));
 
-    assert!(!tree.on_process(&mut memory));
-    tree.on_update(&mut memory);
-    assert!(!memory.mode);
+    assert_eq!(
+        planner.add_action(
+            "buy",
+            PlannerAction::task(1.0, task("buy")).precondition("money")
+        ),
+        Err(PlannerError::ConditionDoesNotExists("money"))
+    );
+    assert_eq!(
+        planner.remove_condition(&"money").err(),
+        Some(PlannerError::ConditionDoesNotExists("money"))
+    );
+    assert!(planner.add_condition("money", true).is_none());
+    assert!(planner.has_condition(&"money"));
+    assert_eq!(
+        planner.add_action(
+            "buy",
+            PlannerAction::task(1.0, task("buy"))
+                .precondition("money")
+                .postcondition("food"),
+        ),
+        Ok(())
+    );
+    assert!(planner.has_action(&"buy"));
+
+    assert!(planner.remove_action(&"hunt", &mut memory).is_ok());
+    assert_eq!(memory, vec![("hunt", TaskStopReason::Cancelled)]);
+    assert_eq!(planner.active_plan(), None);
+    assert_eq!(
+        planner.remove_action(&"hunt", &mut memory).err(),
+        Some(PlannerError::ActionDoesNotExists("hunt"))
+    );
+
+    assert!(planner.process(&mut memory));
+    assert_eq!(planner.active_plan(), Some(["buy", "eat"].as_slice()));
+
+    assert_eq!(
+        planner.set_action_enabled(&"eat", false, &mut memory),
+        Ok(())
+    );
+    assert_eq!(planner.is_action_enabled(&"eat"), Some(false));
+    assert_eq!(memory[1], ("buy", TaskStopReason::Cancelled));
+    assert!(!planner.process(&mut memory));
+    assert_eq!(planner.active_plan(), None);
+    assert_eq!(
+        planner.rejected_goals(),
+        &[("eat", PlannerGoalRejection::Disabled)]
+    );
+
+    assert_eq!(
+        planner.set_action_enabled(&"eat", true, &mut memory),
+        Ok(())
+    );
+    assert!(planner.process(&mut memory));
+    assert_eq!(planner.active_plan(), Some(["buy", "eat"].as_slice()));
+
+    assert_eq!(
+        planner.remove_condition(&"money").err(),
+        Some(PlannerError::ConditionIsInUse("money"))
+    );
+    assert!(planner.remove_action(&"buy", &mut memory).is_ok());
+    assert!(planner.remove_condition(&"money").is_ok());
+    assert!(!planner.has_condition(&"money"));
+}
+
+#[test]
+fn test_planner_templates() {
+    struct Memory {
+        ores: Vec<usize>,
+        location: usize,
+        target: Option<usize>,
+        has_ore: bool,
+    }
+
+    let goto = PlannerActionTemplate::new(
+        |m: &Memory| m.ores.clone(),
+        |ore| {
+            PlannerActionInstance::new(
+                format!("goto({})", ore),
+                PlannerAction::task(
+                    ClosureConsideration::new(move |m: &Memory| m.location.abs_diff(ore) as Scalar),
+                    ClosureTask::default().enter(move |m: &mut Memory| m.target = Some(ore)),
+                )
+                .precondition("ready".to_owned())
+                .postcondition(format!("at({})", ore)),
+            )
+            .condition(
+                format!("at({})", ore),
+                ClosureCondition::new(move |m: &Memory| m.location == ore),
+            )
+        },
+    );
+    let mine = PlannerActionTemplate::new(
+        |m: &Memory| m.ores.clone(),
+        |ore| {
+            PlannerActionInstance::new(
+                format!("mine({})", ore),
+                PlannerAction::task(1.0, NoTask)
+                    .precondition(format!("at({})", ore))
+                    .postcondition("ore".to_owned()),
+            )
+        },
+    );
+    let mut planner = PlannerBuilder::new(SingleDecisionMaker::new("smelt".to_owned()))
+        .action(
+            "idle".to_owned(),
+            PlannerAction::task(1.0, NoTask)
+                .precondition("ready".to_owned())
+                .precondition("free".to_owned())
+                .postcondition("ready".to_owned()),
+        )
+        .action(
+            "smelt".to_owned(),
+            PlannerAction::task(1.0, NoTask).precondition("ore".to_owned()),
+        )
+        .condition("ready".to_owned(), true)
+        .condition("free".to_owned(), true)
+        .condition(
+            "ore".to_owned(),
+            ClosureCondition::new(|m: &Memory| m.has_ore),
+        )
+        .template(goto)
+        .template(mine)
+        .build()
+        .unwrap();
+    let mut memory = Memory {
+        ores: vec![2, 10],
+        location: 0,
+        target: None,
+        has_ore: false,
+    };
+
+    assert!(planner.process(&mut memory));
+    assert_eq!(
+        planner.active_plan().unwrap(),
+        ["idle", "goto(2)", "mine(2)", "smelt"]
+    );
+    assert!(planner.is_template_action(&"goto(10)".to_owned()));
+    assert!(!planner.is_template_action(&"idle".to_owned()));
+    assert!(planner.has_condition(&"at(10)".to_owned()));
+
+    planner.process(&mut memory);
+    assert_eq!(planner.active_action().unwrap(), "goto(2)");
+    assert_eq!(memory.target, Some(2));
+
+    memory.ores = vec![10];
+    assert_eq!(planner.refresh_templates(&memory), Ok(()));
+    assert!(planner.has_action(&"goto(2)".to_owned()));
+    assert!(planner.has_condition(&"at(2)".to_owned()));
+
+    planner.change_mind(None, &mut memory);
+    assert_eq!(planner.refresh_templates(&memory), Ok(()));
+    assert!(!planner.has_action(&"goto(2)".to_owned()));
+    assert!(!planner.has_condition(&"at(2)".to_owned()));
+    assert!(planner.has_action(&"mine(10)".to_owned()));
+    assert_eq!(planner.template_error(), None);
+
+    let mine = || {
+        PlannerActionTemplate::new(
+            |m: &Memory| m.ores.clone(),
+            |ore| {
+                PlannerActionInstance::new(
+                    format!("mine({})", ore),
+                    PlannerAction::task(1.0, NoTask).postcondition("ore".to_owned()),
+                )
+            },
+        )
+    };
+    let builder = || {
+        PlannerBuilder::new(NoDecisionMaker)
+            .action(
+                "smelt".to_owned(),
+                PlannerAction::task(1.0, NoTask).precondition("ore".to_owned()),
+            )
+            .condition(
+                "ore".to_owned(),
+                ClosureCondition::new(|m: &Memory| m.has_ore),
+            )
+    };
+    // goals are validated against static actions even when there are templates.
+    assert_eq!(
+        builder()
+            .template(mine().generates(|id: &String| id.starts_with("mine(")))
+            .goal("smetl".to_owned(), 1.0)
+            .build()
+            .unwrap_err(),
+        PlannerError::ActionDoesNotExists("smetl".to_owned())
+    );
+    assert_eq!(
+        builder()
+            .template(mine())
+            .goal("mine(2)".to_owned(), 1.0)
+            .build()
+            .unwrap_err(),
+        PlannerError::ActionDoesNotExists("mine(2)".to_owned())
+    );
+    let mut planner = builder()
+        .template(mine().generates(|id: &String| id.starts_with("mine(")))
+        .template(PlannerActionTemplate::new(
+            |_: &Memory| vec![()],
+            |_| PlannerActionInstance::new("smelt".to_owned(), PlannerAction::task(1.0, NoTask)),
+        ))
+        .goal("smelt".to_owned(), 1.0)
+        .goal("mine(2)".to_owned(), 0.5)
+        .build()
+        .unwrap();
+    memory.ores = vec![2];
+    assert!(planner.process(&mut memory));
+    assert_eq!(
+        planner.template_error(),
+        Some(&PlannerError::ActionAlreadyExists("smelt".to_owned()))
+    );
+    assert_eq!(planner.active_plan().unwrap(), ["mine(2)", "smelt"]);
+    // explicit planning does not fail on template error either.
+    assert_eq!(
+        planner
+            .find_plan(Some("smelt".to_owned()), &mut memory, true)
+            .map(|outcome| outcome.has_changed()),
+        Ok(true)
+    );
+    assert_eq!(
+        planner.template_error(),
+        Some(&PlannerError::ActionAlreadyExists("smelt".to_owned()))
+    );
+}
+
+#[test]
+fn test_planner_incremental() {
+    struct Memory {
+        hunger: Scalar,
+        log: Vec<(&'static str, Option<TaskStopReason>)>,
+    }
+
+    let task = |name: &'static str| {
+        ClosureTask::default()
+            .enter(move |m: &mut Memory| m.log.push((name, None)))
+            .stop(move |m: &mut Memory, reason| m.log.push((name, Some(reason))))
+    };
+    let mut planner = PlannerBuilder::new(NoDecisionMaker)
+        .action(
+            "idle",
+            PlannerAction::task(1.0, task("idle"))
+                .precondition("home")
+                .precondition("idle")
+                .postcondition("home"),
+        )
+        .action(
+            "sleep",
+            PlannerAction::task(0.5, task("sleep"))
+                .precondition("home")
+                .postcondition("rested"),
+        )
+        .action(
+            "walk",
+            PlannerAction::task(1.0, task("walk"))
+                .precondition("home")
+                .postcondition("outside"),
+        )
+        .action(
+            "buy",
+            PlannerAction::task(1.0, task("buy"))
+                .precondition("outside")
+                .postcondition("food"),
+        )
+        .action(
+            "eat",
+            PlannerAction::task(1.0, task("eat")).precondition("food"),
+        )
+        .condition("home", true)
+        .condition("idle", true)
+        .condition("rested", false)
+        .condition("outside", false)
+        .condition("food", false)
+        .goal("sleep", 1.0)
+        .goal("eat", ClosureConsideration::new(|m: &Memory| m.hunger))
+        .build()
+        .unwrap()
+        .planning_budget(1)
+        .fallback_task(task("fallback"));
+    let mut memory = Memory {
+        hunger: 0.0,
+        log: vec![],
+    };
+    assert_eq!(planner.get_planning_budget(), Some(1));
+
+    assert!(!planner.process(&mut memory));
+    assert!(planner.is_planning());
+    assert_eq!(planner.planning_goal(), Some(&"sleep"));
+    assert!(planner.is_fallback_active());
+    assert_eq!(memory.log, vec![("fallback", None)]);
+
+    assert!(planner.process(&mut memory));
+    assert!(!planner.is_planning());
+    assert!(!planner.is_fallback_active());
+    assert_eq!(planner.active_plan(), Some(["idle", "sleep"].as_slice()));
+    assert_eq!(
+        memory.log[1..],
+        [("fallback", Some(TaskStopReason::Replaced)), ("idle", None),]
+    );
+
+    memory.hunger = 2.0;
+    memory.log.clear();
+    let mut steps = 0;
+    while {
+        planner.process(&mut memory);
+        planner.is_planning()
+    } {
+        steps += 1;
+        assert_eq!(planner.planning_goal(), Some(&"eat"));
+        assert_eq!(planner.active_goal(), Some(&"sleep"));
+        assert!(!planner.is_fallback_active());
+    }
+    assert!(steps > 1);
+    assert_eq!(
+        planner.active_plan(),
+        Some(["idle", "walk", "buy", "eat"].as_slice())
+    );
+    assert_eq!(
+        memory.log,
+        vec![
+            ("idle", Some(TaskStopReason::Completed)),
+            ("sleep", None),
+            ("sleep", Some(TaskStopReason::Cancelled)),
+            ("idle", None),
+        ]
+    );
+
+    memory.hunger = 0.0;
+    planner.process(&mut memory);
+    assert!(planner.is_planning());
+    planner.change_mind(Some("eat"), &mut memory);
+    assert!(!planner.is_planning());
+}
+
+#[test]
+fn test_planner_incremental_goal_change() {
+    struct Memory {
+        goal: Option<&'static str>,
+        idle: bool,
+        minds: Vec<Option<&'static str>>,
+        log: Vec<&'static str>,
+    }
+
+    struct GoalSelector;
+
+    impl DecisionMaker<Memory, &'static str> for GoalSelector {
+        fn decide(&mut self, memory: &mut Memory) -> Option<&'static str> {
+            memory.goal
+        }
+
+        fn change_mind(&mut self, id: Option<&'static str>, memory: &mut Memory) -> bool {
+            memory.minds.push(id);
+            true
+        }
+    }
+
+    let task =
+        |name: &'static str| ClosureTask::default().enter(move |m: &mut Memory| m.log.push(name));
+    let mut planner = PlannerBuilder::new(GoalSelector)
+        .action(
+            "idle",
+            PlannerAction::task(1.0, task("idle"))
+                .precondition("home")
+                .precondition("idle")
+                .precondition("awake")
+                .postcondition("home")
+                .postcondition("idle"),
+        )
+        .action(
+            "sleep",
+            PlannerAction::task(0.5, task("sleep"))
+                .precondition("home")
+                .precondition("idle")
+                .postcondition("rested"),
+        )
+        .action(
+            "walk",
+            PlannerAction::task(1.0, task("walk"))
+                .precondition("home")
+                .postcondition("outside"),
+        )
+        .action(
+            "buy",
+            PlannerAction::task(1.0, task("buy"))
+                .precondition("outside")
+                .postcondition("food"),
+        )
+        .action(
+            "eat",
+            PlannerAction::task(1.0, task("eat")).precondition("food"),
+        )
+        .condition("home", true)
+        .condition("idle", ClosureCondition::new(|m: &Memory| m.idle))
+        .condition("awake", true)
+        .condition("rested", false)
+        .condition("outside", false)
+        .condition("food", false)
+        .build()
+        .unwrap()
+        .planning_budget(1);
+    let mut memory = Memory {
+        goal: Some("eat"),
+        idle: true,
+        minds: vec![],
+        log: vec![],
+    };
+
+    assert!(!planner.process(&mut memory));
+    assert_eq!(planner.planning_goal(), Some(&"eat"));
+    memory.goal = Some("sleep");
+    while {
+        planner.process(&mut memory);
+        planner.is_planning()
+    } {
+        assert_eq!(planner.planning_goal(), Some(&"sleep"));
+    }
+    assert_eq!(planner.active_goal(), Some(&"sleep"));
+    assert_eq!(planner.active_plan(), Some(["idle", "sleep"].as_slice()));
+    assert_eq!(memory.minds, vec![Some("sleep")]);
+    assert_eq!(memory.log, vec!["idle"]);
+
+    memory.goal = Some("eat");
+    memory.log.clear();
+    planner.process(&mut memory);
+    assert_eq!(planner.planning_goal(), Some(&"eat"));
+    memory.idle = false;
+    while {
+        planner.process(&mut memory);
+        planner.is_planning()
+    } {}
+    assert_eq!(planner.active_goal(), Some(&"eat"));
+    assert_eq!(
+        planner.active_plan(),
+        Some(["walk", "buy", "eat"].as_slice())
+    );
+    assert_eq!(memory.minds, vec![Some("sleep"), Some("eat")]);
+    assert_eq!(memory.log.last(), Some(&"walk"));
+}
+
+#[test]
+fn test_planner_unreachable_goal_settles() {
+    struct Memory {
+        goal: Option<&'static str>,
+        idle: bool,
+        log: Vec<&'static str>,
+    }
+
+    struct GoalSelector;
+
+    impl DecisionMaker<Memory, &'static str> for GoalSelector {
+        fn decide(&mut self, memory: &mut Memory) -> Option<&'static str> {
+            memory.goal
+        }
+
+        fn change_mind(&mut self, _: Option<&'static str>, _: &mut Memory) -> bool {
+            true
+        }
+    }
+
+    let task =
+        |name: &'static str| ClosureTask::default().enter(move |m: &mut Memory| m.log.push(name));
+    let mut planner = PlannerBuilder::new(GoalSelector)
+        .action(
+            "idle",
+            PlannerAction::task(1.0, task("idle"))
+                .precondition("home")
+                .precondition("idle")
+                .precondition("awake")
+                .postcondition("home")
+                .postcondition("idle"),
+        )
+        .action(
+            "sleep",
+            PlannerAction::task(0.5, task("sleep"))
+                .precondition("home")
+                .precondition("idle")
+                .postcondition("rested"),
+        )
+        .action(
+            "walk",
+            PlannerAction::task(1.0, task("walk"))
+                .precondition("home")
+                .postcondition("outside"),
+        )
+        .action(
+            "fly",
+            PlannerAction::task(1.0, task("fly")).precondition("wings"),
+        )
+        .condition("home", true)
+        .condition("idle", ClosureCondition::new(|m: &Memory| m.idle))
+        .condition("awake", true)
+        .condition("rested", false)
+        .condition("outside", false)
+        .condition("wings", false)
+        .goal("sleep", 1.0)
+        .build()
+        .unwrap()
+        .planning_budget(1);
+    let mut memory = Memory {
+        goal: Some("fly"),
+        idle: true,
+        log: vec![],
+    };
+
+    for _ in 0..100 {
+        planner.process(&mut memory);
+        if !planner.is_planning() {
+            break;
+        }
+    }
+    assert_eq!(planner.active_goal(), Some(&"sleep"));
+    assert_eq!(memory.log, vec!["idle"]);
+    // unreachable goal is not searched again while nothing changes.
+    for _ in 0..10 {
+        assert!(!planner.process(&mut memory));
+        assert!(!planner.is_planning());
+        assert_eq!(
+            planner.rejected_goals(),
+            &[("fly", PlannerGoalRejection::Unreachable)]
+        );
+    }
+    assert_eq!(planner.active_goal(), Some(&"sleep"));
+    assert_eq!(memory.log, vec!["idle", "sleep"]);
+
+    memory.goal = Some("walk");
+    for _ in 0..100 {
+        planner.process(&mut memory);
+        if !planner.is_planning() {
+            break;
+        }
+    }
+    assert_eq!(planner.active_goal(), Some(&"walk"));
+    assert!(planner.rejected_goals().is_empty());
+}
+
+#[test]
+fn test_agent_pool() {
+    struct Memory {
+        processed: usize,
+        updated: usize,
+        order: Arc<Mutex<Vec<usize>>>,
+    }
+
+    fn agent(index: usize) -> ClosureTask<Memory> {
+        ClosureTask::default()
+            .process(move |m: &mut Memory| {
+                m.processed += 1;
+                m.order.lock().unwrap().push(index);
+                false
+            })
+            .update(|m: &mut Memory| m.updated += 1)
+    }
+
+    let order = Arc::new(Mutex::new(vec![]));
+    let memory = |order: &Arc<Mutex<Vec<usize>>>| Memory {
+        processed: 0,
+        updated: 0,
+        order: order.clone(),
+    };
+    let mut pool = AgentPool::default().deterministic(true);
+    assert!(pool.get_deterministic());
+    assert!(pool.is_empty());
+    let a = pool.add(agent(0), memory(&order));
+    let b = pool.add_with_tick_rate(agent(1), memory(&order), 2);
+    let c = pool.add_with_tick_rate(agent(2), memory(&order), 0);
+    assert_eq!(pool.len(), 3);
+    assert_eq!(pool.tick_rate(c), Some(1));
+    assert!(pool.is_due(a));
+    assert!(!pool.is_due(b));
+
+    assert_eq!(pool.tick(), 2);
+    assert_eq!(pool.tick(), 3);
+    assert_eq!(pool.tick(), 2);
+    assert_eq!(pool.ticks(), 3);
+    assert_eq!(*order.lock().unwrap(), vec![0, 2, 0, 1, 2, 0, 2]);
+    assert_eq!(pool.memory(a).unwrap().processed, 3);
+    assert_eq!(pool.memory(a).unwrap().updated, 3);
+    assert_eq!(pool.memory(b).unwrap().processed, 1);
+    assert_eq!(pool.memory(c).unwrap().updated, 3);
+
+    assert!(pool.set_tick_rate(a, 3));
+    let (_, removed) = pool.remove(b).unwrap();
+    assert_eq!(removed.processed, 1);
+    assert!(!pool.contains(b));
+    assert!(pool.remove(b).is_none());
+    assert!(!pool.set_tick_rate(b, 3));
+    order.lock().unwrap().clear();
+    // tick 3 is due for agent `a` with tick rate 3, since `(3 + 0) % 3 == 0`.
+    assert_eq!(pool.tick(), 2);
+    assert_eq!(pool.tick(), 1);
+    assert_eq!(*order.lock().unwrap(), vec![0, 2, 2]);
+    assert_eq!(
+        pool.iter().map(|(id, _)| id).collect::<Vec<_>>(),
+        vec![a, c]
+    );
+
+    // sequential ticking has the same bounds in every feature configuration.
+    fn tick_pool<M, D: Task<M>>(pool: &mut AgentPool<M, D>) -> usize {
+        pool.tick()
+    }
+
+    pool.set_deterministic(false);
+    assert_eq!(tick_pool(&mut pool), 1);
+    assert_eq!(pool.memory(c).unwrap().processed, 6);
+
+    #[cfg(feature = "parallel")]
+    {
+        assert_eq!(pool.par_tick(), 2);
+        assert_eq!(pool.ticks(), 7);
+        assert_eq!(pool.memory(a).unwrap().processed, 5);
+        assert_eq!(pool.memory(c).unwrap().processed, 7);
+    }
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn test_async_task() {
+    use crate::task::AsyncTask;
+    use std::{
+        future::Future,
+        pin::Pin,
+        sync::atomic::{AtomicUsize, Ordering},
+        task::{Context, Poll, Wake},
+    };
+
+    struct Countdown(usize);
+
+    impl Future for Countdown {
+        type Output = usize;
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            if self.0 == 0 {
+                Poll::Ready(42)
+            } else {
+                self.0 -= 1;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct Wakes(AtomicUsize);
+
+    impl Wake for Wakes {
+        fn wake(self: Arc<Self>) {
+            self.wake_by_ref();
+        }
+
+        fn wake_by_ref(self: &Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[derive(Default)]
+    struct Memory {
+        steps: usize,
+        result: Option<usize>,
+        cancelled: usize,
+    }
+
+    let wakes = Arc::new(Wakes::default());
+    let mut task = AsyncTask::new(|m: &Memory| Countdown(m.steps))
+        .complete(|output, m: &mut Memory| m.result = Some(output))
+        .cancel(|m: &mut Memory| m.cancelled += 1)
+        .lock_while_pending(true)
+        .waker(wakes.clone().into());
+    assert!(task.get_lock_while_pending());
+    let mut memory = Memory {
+        steps: 2,
+        ..Default::default()
+    };
+    assert!(!task.is_pending());
+    assert!(!task.on_process(&mut memory));
+
+    task.on_enter(&mut memory);
+    assert!(task.is_locked(&memory));
+    assert!(!task.on_process(&mut memory));
+    assert!(!task.on_process(&mut memory));
+    assert_eq!(wakes.0.load(Ordering::SeqCst), 2);
+    assert!(task.on_process(&mut memory));
+    assert!(!task.is_locked(&memory));
+    assert_eq!(memory.result, Some(42));
+    task.on_stop(&mut memory, TaskStopReason::Completed);
+    assert_eq!(memory.cancelled, 0);
+
+    memory.result = None;
+    task.on_enter(&mut memory);
+    assert!(!task.on_process(&mut memory));
+    task.on_stop(&mut memory, TaskStopReason::Replaced);
+    assert!(!task.is_pending());
+    assert!(!task.is_locked(&memory));
+    assert!(!task.on_process(&mut memory));
+    assert_eq!(memory.cancelled, 1);
+    assert_eq!(memory.result, None);
+}
+
+#[test]
+fn test_entity_memory() {
+    #[derive(Debug, Clone, PartialEq)]
+    struct Health(Scalar);
+
+    struct Ammo(usize);
+
+    #[derive(Default)]
+    struct World {
+        health: HashMap<usize, Health>,
+        ammo: HashMap<usize, Ammo>,
+    }
+
+    impl EntityWorld for World {
+        type Entity = usize;
+    }
+
+    impl ReadComponent<Health> for World {
+        fn read_component<R>(&self, entity: usize, f: impl FnOnce(&Health) -> R) -> Option<R> {
+            self.health.get(&entity).map(f)
+        }
+    }
+
+    impl WriteComponent<Health> for World {
+        fn write_component<R>(
+            &mut self,
+            entity: usize,
+            f: impl FnOnce(&mut Health) -> R,
+        ) -> Option<R> {
+            self.health.get_mut(&entity).map(f)
+        }
+    }
+
+    impl ReadComponent<Ammo> for World {
+        fn read_component<R>(&self, entity: usize, f: impl FnOnce(&Ammo) -> R) -> Option<R> {
+            self.ammo.get(&entity).map(f)
+        }
+    }
+
+    let mut world = World::default();
+    world.health.insert(0, Health(0.25));
+    world.ammo.insert(0, Ammo(3));
+    world.health.insert(1, Health(1.0));
+
+    // world moved into memory lets decision maker outlive any world borrow.
+    let mut reasoner = Reasoner::new(map! {ReasonerState<EntityMemory<World>>:
+        "attack" => ReasonerState::new(
+            ComponentConsideration::new(|health: &Health| health.0)
+                .missing(0.0),
+            GuardTask::new(ComponentCondition::new(|ammo: &Ammo| ammo.0 > 0), NoTask),
+        ),
+        "heal" => ReasonerState::new(
+            ComponentConsideration::new(|health: &Health| 1.0 - health.0),
+            ClosureTask::default().update(|m: &mut EntityMemory<World>| {
+                m.component_mut(|health: &mut Health| health.0 += 0.5);
+            }),
+        ),
+    });
+
+    let mut memory = EntityMemory::new(world, 0);
+    assert!(memory.has::<Ammo>());
+    assert_eq!(memory.get::<Health>(), Some(Health(0.25)));
+    assert!(reasoner.process(&mut memory));
+    assert_eq!(reasoner.active_state(), Some(&"heal"));
+    reasoner.update(&mut memory);
+    assert_eq!(memory.get::<Health>(), Some(Health(0.75)));
+    assert!(reasoner.process(&mut memory));
+    assert_eq!(reasoner.active_state(), Some(&"attack"));
+    let mut world = memory.into_world();
+
+    memory = EntityMemory::new(world, 1);
+    assert!(!memory.has::<Ammo>());
+    assert!(!ComponentCondition::<Ammo>::exists().validate(&memory));
+    assert!(
+        ComponentCondition::new(|ammo: &Ammo| ammo.0 > 0)
+            .missing(true)
+            .validate(&memory)
+    );
+    world = memory.into_world();
+
+    // borrowed world works the same way for short-lived decision making.
+    let condition = ComponentCondition::new(|health: &Health| health.0 >= 1.0);
+    let consideration = ComponentConsideration::new(|ammo: &Ammo| ammo.0 as Scalar).missing(-1.0);
+    let mut memory = EntityMemory::new(&mut world, 1);
+    assert!(condition.validate(&memory));
+    assert_eq!(consideration.score(&memory), -1.0);
+    memory.component_mut(|health: &mut Health| health.0 = 0.5);
+    assert!(!condition.validate(&memory));
+    assert_eq!(world.health[&1], Health(0.5));
+}
+
+#[test]
+fn test_sequencer() {
+    let mut memory = false;
+
+    let mut sequencer = Sequencer::new(
+        vec![
+            SequencerState::new(
+                ClosureCondition::new(|m| *m),
+                ClosureTask::default().enter(|m| *m = false),
+            ),
+            SequencerState::new(
+                ClosureCondition::new(|m| !m),
+                ClosureTask::default().enter(|m| *m = true),
+            ),
+        ],
+        true,
+        true,
+    );
+
+    assert!(sequencer.process(&mut memory));
+    assert!(memory);
+    assert!(sequencer.process(&mut memory));
+    assert!(!memory);
+    assert!(sequencer.process(&mut memory));
+    assert!(memory);
+}
+
+#[test]
+fn test_sequencer_modes() {
+    #[derive(Default)]
+    struct Memory {
+        has_target: bool,
+        steps: Vec<&'static str>,
+        cancelled: usize,
+    }
+
+    fn step(name: &'static str, locked: bool) -> ClosureTask<Memory> {
+        ClosureTask::default()
+            .locked(move |_: &Memory| locked)
+            .enter(move |m: &mut Memory| m.steps.push(name))
+            .stop(|m: &mut Memory, reason| {
+                if reason == TaskStopReason::Cancelled {
+                    m.cancelled += 1;
+                }
+            })
+    }
+
+    fn make_sequencer(mode: SequencerMode) -> Sequencer<Memory> {
+        Sequencer::new(
+            vec![
+                SequencerState::new(
+                    ClosureCondition::new(|m: &Memory| m.has_target),
+                    step("aim", false),
+                ),
+                SequencerState::new(true, step("shoot", true)),
+            ],
+            false,
+            false,
+        )
+        .mode(mode)
+    }
+
+    // memory mode never checks passed states again.
+    let mut sequencer = make_sequencer(SequencerMode::Memory);
+    let mut memory = Memory {
+        has_target: true,
+        ..Default::default()
+    };
+    assert!(sequencer.process(&mut memory));
+    assert!(sequencer.process(&mut memory));
+    memory.has_target = false;
+    assert!(sequencer.is_locked(&memory));
+    assert!(!sequencer.process(&mut memory));
+    assert_eq!(sequencer.active_index(), Some(1));
+    assert_eq!(memory.cancelled, 0);
+
+    // reactive restart starts over from the first state, which waits for its condition.
+    let mut sequencer = make_sequencer(SequencerMode::ReactiveRestart);
+    let mut memory = Memory {
+        has_target: true,
+        ..Default::default()
+    };
+    assert!(sequencer.process(&mut memory));
+    assert!(sequencer.process(&mut memory));
+    assert_eq!(memory.steps, vec!["aim", "shoot"]);
+    memory.has_target = false;
+    assert!(!sequencer.is_locked(&memory));
+    assert!(sequencer.process(&mut memory));
+    assert_eq!(sequencer.active_index(), None);
+    assert_eq!(memory.cancelled, 1);
+    assert!(!sequencer.process(&mut memory));
+    assert!(!sequencer.process(&mut memory));
+    assert_eq!(sequencer.active_index(), None);
+    assert_eq!(memory.steps, vec!["aim", "shoot"]);
+    memory.has_target = true;
+    assert!(sequencer.process(&mut memory));
+    assert_eq!(sequencer.active_index(), Some(0));
+    assert!(sequencer.process(&mut memory));
+    assert_eq!(sequencer.active_index(), Some(1));
+    assert_eq!(memory.steps, vec!["aim", "shoot", "aim", "shoot"]);
+    // sequence stays reactive after restart.
+    memory.has_target = false;
+    assert!(sequencer.process(&mut memory));
+    assert_eq!(sequencer.active_index(), None);
+    assert_eq!(memory.cancelled, 2);
+
+    // reactive abort stops the sequence until it gets reset.
+    let mut sequencer = make_sequencer(SequencerMode::ReactiveAbort);
+    let mut memory = Memory {
+        has_target: true,
+        ..Default::default()
+    };
+    assert!(sequencer.process(&mut memory));
+    assert!(sequencer.process(&mut memory));
+    memory.has_target = false;
+    assert!(sequencer.process(&mut memory));
+    assert_eq!(sequencer.active_index(), None);
+    assert!(sequencer.is_aborted());
+    assert_eq!(memory.cancelled, 1);
+    assert!(!sequencer.process(&mut memory));
+    assert_eq!(sequencer.active_index(), None);
+    memory.has_target = true;
+    assert!(!sequencer.process(&mut memory));
+    assert_eq!(sequencer.active_index(), None);
+    assert_eq!(memory.steps, vec!["aim", "shoot"]);
+    assert!(sequencer.reset(&mut memory, true));
+    assert!(!sequencer.is_aborted());
+    assert!(sequencer.process(&mut memory));
+    assert_eq!(sequencer.active_index(), Some(0));
+    assert_eq!(memory.steps, vec!["aim", "shoot", "aim"]);
+    // entering sequence again also clears abort.
+    assert!(sequencer.process(&mut memory));
+    memory.has_target = false;
+    assert!(sequencer.process(&mut memory));
+    assert!(sequencer.is_aborted());
+    memory.has_target = true;
+    sequencer.on_enter(&mut memory);
+    assert_eq!(sequencer.active_index(), Some(0));
+
+    // reactive sequence in behavior tree aborts its running step, even if it is locked.
+    let mut tree = BehaviorTree::selector(true)
+        .node(
+            BehaviorTree::sequence_mode(true, SequencerMode::ReactiveAbort)
+                .node(BehaviorTree::state(
+                    ClosureCondition::new(|m: &Memory| m.has_target),
+                    step("aim", false),
+                ))
+                .node(BehaviorTree::state(true, step("shoot", true))),
+        )
+        .node(BehaviorTree::state(true, step("idle", false)))
+        .build();
+    let mut memory = Memory {
+        has_target: true,
+        ..Default::default()
+    };
+    assert!(tree.on_process(&mut memory));
+    assert!(tree.on_process(&mut memory));
+    assert!(tree.is_locked(&memory));
+    memory.has_target = false;
+    assert!(!tree.is_locked(&memory));
+    assert!(tree.on_process(&mut memory));
+    assert_eq!(memory.steps, vec!["aim", "shoot"]);
+    assert_eq!(memory.cancelled, 1);
+}
+
+#[test]
+fn test_selector() {
+    let mut memory = false;
+
+    let mut selector = Selector::new(vec![
+        SelectorState::new(
+            ClosureCondition::new(|m| *m),
+            ClosureTask::default().enter(|m| *m = false),
+        ),
+        SelectorState::new(
+            ClosureCondition::new(|m| !m),
+            ClosureTask::default().enter(|m| *m = true),
+        ),
+    ]);
+
+    assert!(selector.process(&mut memory));
+    assert!(memory);
+    assert!(selector.process(&mut memory));
+    assert!(!memory);
+    assert!(selector.process(&mut memory));
+    assert!(memory);
+}
+
+#[test]
+fn test_selector_abort_modes() {
+    #[derive(Default)]
+    struct Memory {
+        danger: bool,
+        hungry: bool,
+        log: Vec<(&'static str, TaskStopReason)>,
+    }
+
+    fn activity(name: &'static str) -> ClosureTask<Memory> {
+        ClosureTask::default()
+            .locked(|_: &Memory| true)
+            .stop(move |m: &mut Memory, reason| m.log.push((name, reason)))
+    }
+
+    fn make_selector(flee: SelectorAbortMode, eat: SelectorAbortMode) -> Selector<Memory> {
+        Selector::new(vec![
+            SelectorState::new(
+                ClosureCondition::new(|m: &Memory| m.danger),
+                activity("flee"),
+            )
+            .abort_mode(flee),
+            SelectorState::new(
+                ClosureCondition::new(|m: &Memory| m.hungry),
+                activity("eat"),
+            )
+            .abort_mode(eat),
+            SelectorState::new(true, activity("idle")),
+        ])
+    }
+
+    // no aborts keeps locked states running.
+    let mut selector = make_selector(SelectorAbortMode::None, SelectorAbortMode::None);
+    let mut memory = Memory::default();
+    assert!(selector.process(&mut memory));
+    assert_eq!(selector.active_index(), Some(2));
+    memory.danger = true;
+    assert!(!selector.process(&mut memory));
+    assert_eq!(selector.active_index(), Some(2));
+
+    // lower priority abort preempts locked state.
+    let mut selector = make_selector(SelectorAbortMode::LowerPriority, SelectorAbortMode::None);
+    let mut memory = Memory::default();
+    assert!(selector.process(&mut memory));
+    memory.hungry = true;
+    assert!(!selector.process(&mut memory));
+    assert_eq!(selector.active_index(), Some(2));
+    memory.danger = true;
+    assert!(selector.process(&mut memory));
+    assert_eq!(selector.active_index(), Some(0));
+    assert_eq!(memory.log, vec![("idle", TaskStopReason::Replaced)]);
+    // flee doesn't abort itself, so it keeps running when danger is gone.
+    memory.danger = false;
+    assert!(selector.is_locked(&memory));
+    assert!(!selector.process(&mut memory));
+    assert_eq!(selector.active_index(), Some(0));
+
+    // self abort stops state as soon as its condition is not met.
+    let mut selector = make_selector(SelectorAbortMode::Both, SelectorAbortMode::Itself);
+    let mut memory = Memory {
+        hungry: true,
+        ..Default::default()
+    };
+    assert!(selector.process(&mut memory));
+    assert_eq!(selector.active_index(), Some(1));
+    memory.hungry = false;
+    assert!(!selector.is_locked(&memory));
+    assert!(selector.process(&mut memory));
+    assert_eq!(selector.active_index(), Some(2));
+    memory.danger = true;
+    assert!(selector.process(&mut memory));
+    memory.danger = false;
+    assert!(selector.process(&mut memory));
+    assert_eq!(selector.active_index(), Some(2));
+    assert_eq!(
+        memory.log,
+        vec![
+            ("eat", TaskStopReason::Cancelled),
+            ("idle", TaskStopReason::Replaced),
+            ("flee", TaskStopReason::Cancelled),
+        ]
+    );
+
+    // abort modes in behavior tree selector.
+    let mut tree = BehaviorTree::selector(true)
+        .node(BehaviorTree::abort(
+            SelectorAbortMode::LowerPriority,
+            BehaviorTree::state(
+                ClosureCondition::new(|m: &Memory| m.danger),
+                activity("flee"),
+            ),
+        ))
+        .node(BehaviorTree::state(true, activity("idle")))
+        .build();
+    let mut memory = Memory::default();
+    assert!(tree.on_process(&mut memory));
+    memory.danger = true;
+    assert!(tree.on_process(&mut memory));
+    assert_eq!(memory.log, vec![("idle", TaskStopReason::Replaced)]);
+
+    // nested selector is not locked when its locked state is going to be aborted.
+    let inner = Selector::new(vec![
+        SelectorState::new(
+            ClosureCondition::new(|m: &Memory| m.danger),
+            activity("flee"),
+        )
+        .abort_mode(SelectorAbortMode::LowerPriority),
+        SelectorState::new(true, activity("idle")),
+    ]);
+    let mut selector = Selector::new(vec![
+        SelectorState::new(
+            ClosureCondition::new(|m: &Memory| m.hungry),
+            activity("eat"),
+        ),
+        SelectorState::new(true, inner),
+    ]);
+    let mut memory = Memory::default();
+    assert!(selector.process(&mut memory));
+    assert_eq!(selector.active_index(), Some(1));
+    memory.hungry = true;
+    assert!(selector.is_locked(&memory));
+    assert!(!selector.process(&mut memory));
+    assert_eq!(selector.active_index(), Some(1));
+    memory.danger = true;
+    assert!(!selector.is_locked(&memory));
+    assert!(selector.process(&mut memory));
+    assert_eq!(selector.active_index(), Some(0));
+    assert_eq!(memory.log, vec![("idle", TaskStopReason::Cancelled)]);
+}
+
+#[test]
+fn test_parallelizer() {
+    let mut memory = false;
+
+    let mut parallelizer = Parallelizer::new(vec![
+        ParallelizerState::new(
+            ClosureCondition::new(|m| *m),
+            ClosureTask::default().enter(|m| *m = false),
+        ),
+        ParallelizerState::new(
+            ClosureCondition::new(|m| !m),
+            ClosureTask::default().enter(|m| *m = true),
+        ),
+    ]);
+
+    assert!(parallelizer.process(&mut memory));
+    assert!(memory);
+    assert!(parallelizer.process(&mut memory));
+    assert!(!memory);
+    assert!(parallelizer.process(&mut memory));
+    assert!(memory);
+}
+
+#[test]
+fn test_behavior_tree_parallel_nodes() {
+    // parallel node made from child nodes runs all of them.
+    let tree = BehaviorTree::parallel_nodes(
+        true,
+        vec![
+            BehaviorTree::state(
+                true,
+                ClosureTask::default().enter(|m: &mut (u8, u8)| m.0 += 1),
+            ),
+            BehaviorTree::state(
+                true,
+                ClosureTask::default().enter(|m: &mut (u8, u8)| m.1 += 1),
+            ),
+        ],
+    );
+    assert!(matches!(tree, BehaviorTree::Parallel { .. }));
+    let mut tree = tree.build();
+    let mut memory = (0, 0);
+    assert!(tree.on_process(&mut memory));
+    assert_eq!(memory, (1, 1));
+}
+
+#[test]
+fn test_parallelizer_policies() {
+    #[derive(Default)]
+    struct Memory {
+        move_distance: usize,
+        aim_time: usize,
+        can_aim: bool,
+        aiming: bool,
+        cancelled: usize,
+        arrived: bool,
+    }
+
+    fn movement() -> ClosureTask<Memory> {
+        ClosureTask::default()
+            .locked(|m: &Memory| m.move_distance > 0)
+            .update(|m: &mut Memory| m.move_distance = m.move_distance.saturating_sub(1))
+    }
+
+    fn aiming() -> ClosureTask<Memory> {
+        ClosureTask::default()
+            .locked(|m: &Memory| m.aim_time > 0)
+            .enter(|m: &mut Memory| m.aiming = true)
+            .update(|m: &mut Memory| m.aim_time = m.aim_time.saturating_sub(1))
+            .stop(|m: &mut Memory, reason| {
+                m.aiming = false;
+                if reason == TaskStopReason::Cancelled {
+                    m.cancelled += 1;
+                }
+            })
+    }
+
+    fn states() -> Vec<ParallelizerState<Memory>> {
+        vec![
+            ParallelizerState::new(true, movement()),
+            ParallelizerState::new(ClosureCondition::new(|m: &Memory| m.can_aim), aiming()),
+        ]
+    }
+
+    // require all succeeds only after both states complete.
+    let mut parallelizer = Parallelizer::new(states()).policy(ParallelizerPolicy::require_all());
+    let mut memory = Memory {
+        move_distance: 1,
+        aim_time: 2,
+        can_aim: true,
+        ..Default::default()
+    };
+    parallelizer.on_enter(&mut memory);
+    assert!(memory.aiming);
+    parallelizer.on_update(&mut memory);
+    assert!(parallelizer.is_locked(&memory));
+    parallelizer.process(&mut memory);
+    assert_eq!(parallelizer.outcome(), None);
+    parallelizer.on_update(&mut memory);
+    assert!(!parallelizer.is_locked(&memory));
+    assert!(parallelizer.process(&mut memory));
+    assert_eq!(parallelizer.outcome(), Some(ParallelizerOutcome::Succeeded));
+    assert_eq!(memory.cancelled, 0);
+    assert!(!parallelizer.process(&mut memory));
+
+    // require all fails on first failed state and cancels the rest.
+    let mut memory = Memory {
+        move_distance: 5,
+        aim_time: 5,
+        can_aim: true,
+        ..Default::default()
+    };
+    parallelizer.on_enter(&mut memory);
+    assert_eq!(parallelizer.outcome(), None);
+    memory.can_aim = false;
+    assert!(!parallelizer.is_locked(&memory));
+    assert!(parallelizer.process(&mut memory));
+    assert_eq!(parallelizer.outcome(), Some(ParallelizerOutcome::Failed));
+    assert_eq!(memory.cancelled, 1);
+    assert!(!parallelizer.is_active());
+
+    // fail on all needs every state to fail.
+    let mut parallelizer = Parallelizer::new(states())
+        .policy(ParallelizerPolicy::default().failure(ParallelizerFailurePolicy::FailOnAll));
+    let mut memory = Memory {
+        move_distance: 1,
+        ..Default::default()
+    };
+    parallelizer.on_enter(&mut memory);
+    assert_eq!(parallelizer.outcome(), None);
+    parallelizer.on_update(&mut memory);
+    parallelizer.process(&mut memory);
+    assert_eq!(parallelizer.outcome(), Some(ParallelizerOutcome::Succeeded));
+
+    // states can be left running after policy resolves.
+    let policy = ParallelizerPolicy::require_one().cancel_remaining(false);
+    assert_eq!(policy.get_success(), ParallelizerSuccessPolicy::RequireOne);
+    assert_eq!(policy.get_failure(), ParallelizerFailurePolicy::FailOnAll);
+    assert!(!policy.get_cancel_remaining());
+    let mut parallelizer = Parallelizer::new(states()).policy(policy);
+    let mut memory = Memory {
+        move_distance: 1,
+        aim_time: 2,
+        can_aim: true,
+        ..Default::default()
+    };
+    parallelizer.on_enter(&mut memory);
+    parallelizer.on_update(&mut memory);
+    assert!(parallelizer.process(&mut memory));
+    assert_eq!(parallelizer.outcome(), Some(ParallelizerOutcome::Succeeded));
+    assert!(memory.aiming);
+    assert!(parallelizer.is_locked(&memory));
+    parallelizer.on_update(&mut memory);
+    assert!(!parallelizer.is_locked(&memory));
+    assert!(parallelizer.process(&mut memory));
+    assert!(!memory.aiming);
+    assert_eq!(memory.cancelled, 0);
+
+    // move while aiming ends cleanly when movement finishes.
+    let mut tree = BehaviorTree::sequence(true)
+        .node(
+            BehaviorTree::parallel_policy(true, ParallelizerPolicy::require_one())
+                .node(BehaviorTree::state(true, movement()))
+                .node(BehaviorTree::state(
+                    ClosureCondition::new(|m: &Memory| m.can_aim),
+                    aiming(),
+                )),
+        )
+        .node(BehaviorTree::state(
+            true,
+            ClosureTask::default().enter(|m: &mut Memory| m.arrived = true),
+        ))
+        .build();
+    let mut memory = Memory {
+        move_distance: 2,
+        aim_time: 10,
+        can_aim: true,
+        ..Default::default()
+    };
+    assert!(tree.on_process(&mut memory));
+    assert!(memory.aiming);
+    tree.on_update(&mut memory);
+    tree.on_process(&mut memory);
+    assert!(!memory.arrived);
+    tree.on_update(&mut memory);
+    assert!(tree.on_process(&mut memory));
+    assert!(memory.arrived);
+    assert!(!memory.aiming);
+    assert_eq!(memory.cancelled, 1);
+}
+
+#[test]
+fn test_mcts() {
+    // players take 1 to 3 sticks and one who takes the last stick wins.
+    #[derive(Clone)]
+    struct Memory {
+        sticks: usize,
+        my_turn: bool,
+        taken: usize,
+    }
+
+    struct Nim;
+
+    impl MctsModel<Memory, usize> for Nim {
+        fn actions(&self, memory: &Memory) -> Vec<usize> {
+            (1..=memory.sticks.min(3)).collect()
+        }
+
+        fn apply(&self, memory: &mut Memory, action: &usize) {
+            memory.sticks -= action;
+            memory.my_turn = !memory.my_turn;
+        }
+
+        fn reward(&self, memory: &Memory) -> Scalar {
+            if memory.my_turn { 0.0 } else { 1.0 }
+        }
+
+        fn is_opponent_turn(&self, memory: &Memory) -> bool {
+            !memory.my_turn
+        }
+    }
+
+    let take = |count| {
+        ClosureTask::default().enter(move |m: &mut Memory| {
+            m.sticks -= count;
+            m.taken += count;
+        })
+    };
+    let make_mcts = |seed| {
+        Mcts::new(Nim)
+            .state(1, take(1))
+            .state(2, take(2))
+            .state(3, take(3))
+            .iterations(2000)
+            .seed(seed)
+    };
+
+    // winning move leaves opponent with multiple of 4 sticks.
+    for (sticks, best) in [(5, 1), (6, 2), (7, 3), (9, 1)] {
+        let mut mcts = make_mcts(sticks as u64);
+        let mut memory = Memory {
+            sticks,
+            my_turn: true,
+            taken: 0,
+        };
+        assert_eq!(mcts.decide(&mut memory), Some(best));
+        assert_eq!(memory.taken, best);
+        let statistics = mcts.statistics();
+        assert_eq!(statistics.len(), 3);
+        let visits = statistics.iter().map(|s| s.visits).sum::<usize>();
+        assert_eq!(visits, 2000);
+    }
+
+    // same seed gives the same search.
+    let memory = Memory {
+        sticks: 8,
+        my_turn: true,
+        taken: 0,
+    };
+    let mut a = make_mcts(7);
+    let mut b = make_mcts(7);
+    a.search(&memory);
+    b.search(&memory);
+    assert_eq!(a.statistics(), b.statistics());
+
+    // only actions with states are considered.
+    let mut mcts = Mcts::new(Nim).state(2, take(2)).iterations(10);
+    let mut memory = Memory {
+        sticks: 1,
+        my_turn: true,
+        taken: 0,
+    };
+    assert_eq!(mcts.search(&memory), None);
+    assert!(!mcts.process(&mut memory));
+
+    let mut mcts = make_mcts(0)
+        .iterations(usize::MAX)
+        .time_limit(std::time::Duration::from_millis(10));
+    let mut memory = Memory {
+        sticks: 20,
+        my_turn: true,
+        taken: 0,
+    };
+    assert!(mcts.on_process(&mut memory));
+    assert!(mcts.active_state().is_some());
+    assert!(mcts.change_mind(None, &mut memory));
+    assert_eq!(mcts.active_state(), None);
+
+    // NaN rewards are treated as the lowest ones.
+    struct Nan;
+
+    impl MctsModel<Memory, usize> for Nan {
+        fn actions(&self, memory: &Memory) -> Vec<usize> {
+            if memory.taken == 0 {
+                vec![1, 2]
+            } else {
+                vec![]
+            }
+        }
+
+        fn apply(&self, memory: &mut Memory, action: &usize) {
+            memory.taken = *action;
+        }
+
+        fn reward(&self, memory: &Memory) -> Scalar {
+            if memory.taken == 1 { Scalar::NAN } else { 0.5 }
+        }
+    }
+
+    let mut mcts = Mcts::new(Nan)
+        .state(1, take(1))
+        .state(2, take(2))
+        .iterations(100);
+    let memory = Memory {
+        sticks: 5,
+        my_turn: true,
+        taken: 0,
+    };
+    assert_eq!(mcts.search(&memory), Some(2));
+}
+
+#[test]
+fn test_lod() {
+    const DELTA_TIME: Scalar = 1.0;
+
+    struct Memory {
+        time_since_last_meal: Scalar,
+        hunger: Scalar,
+    }
+
+    let mut lod = Lod::default()
+        // level 0 means agent is not in area and we optimize AI processing by not doing any work,
+        // so on task exit we just estimate how much more hungry agent can get during task time.
+        .level(ClosureTask::default().exit(|m: &mut LodMemory<Memory>| {
+            m.memory.hunger -= m.memory.time_since_last_meal;
+            println!("* Background hunger estimation: {}", m.memory.hunger);
+        }))
+        // level 1 means agent is in area and we want to accurately change its hunger level.
+        .level(ClosureTask::default().update(|m: &mut LodMemory<Memory>| {
+            m.memory.hunger -= DELTA_TIME;
+            println!("* Foreground hunger calculation: {}", m.memory.hunger);
+        }))
+        .build();
+
+    let mut memory = LodMemory {
+        lod_level: 0,
+        memory: Memory {
+            time_since_last_meal: 0.0,
+            hunger: 10.0,
+        },
+    };
+
+    // we start with agent running in the background.
+    assert_eq!(lod.active_index(), None);
+    assert!(lod.process(&mut memory));
+    assert_eq!(lod.active_index(), Some(0));
+    // agent will now run in foreground and we assume 5 seconds have passed since last meal.
+    memory.lod_level = 1;
+    memory.memory.time_since_last_meal = 5.0;
+    assert!(lod.process(&mut memory));
+    assert_eq!(lod.active_index(), Some(1));
+    assert_eq!(memory.memory.hunger, 5.0);
+    lod.update(&mut memory);
+    assert_eq!(memory.memory.hunger, 4.0);
+}
+
+#[test]
+fn test_lod_controller() {
+    struct Memory {
+        importance: Scalar,
+        ticks: [usize; 3],
+    }
+
+    let lod = Lod::default()
+        .level(ClosureTask::default().update(|m: &mut LodMemory<Memory>| m.memory.ticks[0] += 1))
+        .level(ClosureTask::default().update(|m: &mut LodMemory<Memory>| m.memory.ticks[1] += 1))
+        .level(ClosureTask::default().update(|m: &mut LodMemory<Memory>| m.memory.ticks[2] += 1))
+        .build();
+    let mut controller =
+        LodController::new(ClosureConsideration::new(|m: &Memory| m.importance), lod)
+            .thresholds(vec![0.3, 0.7])
+            .hysteresis(0.05)
+            .frequency(0, 10)
+            .frequency(1, 3);
+    assert_eq!(controller.get_frequency(0), 10);
+    assert_eq!(controller.get_frequency(1), 3);
+    assert_eq!(controller.get_frequency(2), 1);
+
+    assert_eq!(
+        controller.evaluate(
+            &Memory {
+                importance: 0.5,
+                ticks: [0; 3]
+            },
+            0
+        ),
+        1
+    );
+    assert_eq!(
+        controller.evaluate(
+            &Memory {
+                importance: 0.9,
+                ticks: [0; 3]
+            },
+            0
+        ),
+        2
+    );
+    assert_eq!(
+        controller.evaluate(
+            &Memory {
+                importance: 0.0,
+                ticks: [0; 3]
+            },
+            2
+        ),
+        0
+    );
+    // within hysteresis range current level is kept.
+    assert_eq!(
+        controller.evaluate(
+            &Memory {
+                importance: 0.32,
+                ticks: [0; 3]
+            },
+            0
+        ),
+        0
+    );
+    assert_eq!(
+        controller.evaluate(
+            &Memory {
+                importance: 0.28,
+                ticks: [0; 3]
+            },
+            1
+        ),
+        1
+    );
+
+    let mut memory = LodMemory {
+        lod_level: 0,
+        memory: Memory {
+            importance: 0.0,
+            ticks: [0; 3],
+        },
+    };
+    let tick = |controller: &mut LodController<Memory>, memory: &mut LodMemory<Memory>| {
+        controller.on_process(memory);
+        controller.on_update(memory);
+    };
+    for _ in 0..20 {
+        tick(&mut controller, &mut memory);
+    }
+    assert_eq!(memory.lod_level, 0);
+    assert_eq!(memory.memory.ticks, [2, 0, 0]);
+
+    memory.memory.importance = 1.0;
+    for _ in 0..5 {
+        tick(&mut controller, &mut memory);
+    }
+    assert_eq!(memory.lod_level, 2);
+    assert_eq!(memory.memory.ticks, [2, 0, 5]);
+
+    memory.memory.importance = 0.5;
+    for _ in 0..6 {
+        tick(&mut controller, &mut memory);
+    }
+    assert_eq!(memory.lod_level, 1);
+    assert_eq!(controller.tick(), 6);
+    assert_eq!(memory.memory.ticks, [2, 2, 5]);
+}
+
+#[test]
+fn test_lod_handoff() {
+    type Memory = LodMemory<Vec<(usize, usize, usize)>>;
+
+    struct Plan {
+        level: usize,
+        step: usize,
+    }
+
+    impl Task<Memory> for Plan {
+        fn on_update(&mut self, _: &mut Memory) {
+            self.step += 1;
+        }
+    }
+
+    impl LodHandoff<Vec<(usize, usize, usize)>> for Plan {
+        fn export_summary(&self, _: &Memory, summary: &mut LodSummary) {
+            summary.data.set("step".to_owned(), self.step);
+        }
+
+        fn import_summary(&mut self, memory: &mut Memory, summary: &LodSummary) {
+            assert_eq!(summary.to_level, self.level);
+            self.step = *summary.data.get::<usize>("step").unwrap();
+            memory
+                .memory
+                .push((summary.from_level, summary.to_level, self.step));
+        }
+    }
+
+    let mut lod = Lod::default()
+        .level_handoff(Plan { level: 0, step: 0 })
+        .level_handoff(Plan { level: 1, step: 0 })
+        .level(NoTask)
+        .build();
+    let mut memory = LodMemory {
+        lod_level: 1,
+        memory: vec![],
+    };
+    assert!(lod.process(&mut memory));
+    lod.update(&mut memory);
+    lod.update(&mut memory);
+    memory.lod_level = 0;
+    assert!(lod.process(&mut memory));
+    assert_eq!(memory.memory, vec![(1, 0, 2)]);
+    lod.update(&mut memory);
+    memory.lod_level = 1;
+    assert!(lod.process(&mut memory));
+    assert_eq!(memory.memory, vec![(1, 0, 2), (0, 1, 3)]);
+
+    // level without handoff breaks the chain.
+    memory.lod_level = 2;
+    assert!(lod.process(&mut memory));
+    memory.lod_level = 0;
+    assert!(lod.process(&mut memory));
+    assert_eq!(memory.memory.len(), 2);
+
+    // summary is not passed when level was not replaced.
+    memory.lod_level = 1;
+    assert!(lod.process(&mut memory));
+    assert_eq!(memory.memory.len(), 3);
+    lod.on_stop(&mut memory, TaskStopReason::Cancelled);
+    memory.lod_level = 0;
+    assert!(lod.process(&mut memory));
+    assert_eq!(memory.memory.len(), 3);
+}
+
+#[test]
+fn test_behavior_tree() {
+    struct Memory {
+        mode: bool,
+        counter: usize,
+    }
+
+    struct Countdown(pub usize);
+
+    impl Task<Memory> for Countdown {
+        fn is_locked(&self, memory: &Memory) -> bool {
+            memory.counter > 0
+        }
+
+        fn on_enter(&mut self, memory: &mut Memory) {
+            memory.counter = self.0;
+        }
+
+        fn on_update(&mut self, memory: &mut Memory) {
+            memory.counter = memory.counter.max(1) - 1;
+        }
+    }
+
+    struct FlipMode;
+
+    impl Task<Memory> for FlipMode {
+        fn on_enter(&mut self, memory: &mut Memory) {
+            memory.mode = !memory.mode;
+        }
+    }
+
+    struct IsMode(pub bool);
+
+    impl Condition<Memory> for IsMode {
+        fn validate(&self, memory: &Memory) -> bool {
+            memory.mode == self.0
+        }
+    }
+
+    // we define a tree that will perform ping-pong with delay:
+    // first we wait 2 turns, flip memory state, then wait 1 turn and flip back memory state.
+    let mut tree = BehaviorTree::selector(true)
+        .node(
+            BehaviorTree::sequence(IsMode(true))
+                .node(BehaviorTree::state(true, Countdown(2)))
+                .node(BehaviorTree::state(true, FlipMode)),
+        )
+        .node(
+            BehaviorTree::sequence(IsMode(false))
+                .node(BehaviorTree::state(true, Countdown(1)))
+                .node(BehaviorTree::state(true, FlipMode)),
+        )
+        .build();
+
+    let mut memory = Memory {
+        mode: true,
+        counter: 0,
+    };
+
+    assert!(tree.on_process(&mut memory));
+    assert!(memory.mode);
+    assert_eq!(memory.counter, 2);
+
+    assert!(!tree.on_process(&mut memory));
+    tree.on_update(&mut memory);
+    assert!(memory.mode);
+    assert_eq!(memory.counter, 1);
+
+    assert!(!tree.on_process(&mut memory));
+    tree.on_update(&mut memory);
+    assert!(memory.mode);
+    assert_eq!(memory.counter, 0);
+
+    assert!(tree.on_process(&mut memory));
+
+    assert!(tree.on_process(&mut memory));
+    assert!(!memory.mode);
+    assert_eq!(memory.counter, 1);
+
+    assert!(!tree.on_process(&mut memory));
+    tree.on_update(&mut memory);
+    assert!(!memory.mode);
     assert_eq!(memory.counter, 0);
 
     assert!(tree.on_process(&mut memory));
@@ -724,28 +2699,215 @@ fn test_journaled_transaction_nested_isolated_commit() {
         false,
     );
 
-    let mut transaction = JournaledTransactionTask::new(sequence);
+    let mut transaction = JournaledTransactionTask::new(sequence);
+    let mut memory = Memory::default();
+
+    transaction.on_enter(&mut memory);
+    assert_eq!(memory.outer, 1);
+    assert_eq!(memory.inner, 0);
+    assert_eq!(memory.journal.depth(), 1);
+
+    transaction.on_process(&mut memory);
+    assert_eq!(memory.outer, 1);
+    assert_eq!(memory.inner, 10);
+    assert_eq!(memory.journal.depth(), 2);
+
+    transaction.on_process(&mut memory);
+    assert_eq!(memory.outer, 1);
+    assert_eq!(memory.inner, 10);
+    assert_eq!(memory.journal.depth(), 1);
+
+    transaction.on_stop(&mut memory, TaskStopReason::Cancelled);
+    assert_eq!(memory.outer, 0);
+    assert_eq!(memory.inner, 10);
+    assert_eq!(memory.journal.depth(), 0);
+}
+
+#[test]
+fn test_journaled_transaction_rollback_all() {
+    #[derive(Debug, Default)]
+    struct Memory {
+        value: i32,
+        journal: TransactionJournal<i32>,
+    }
+
+    impl TransactionalMemory for Memory {
+        type Undo = i32;
+
+        fn begin_transaction(&mut self) {
+            self.journal.begin();
+        }
+
+        fn commit_transaction(&mut self, policy: TransactionCommitPolicy) {
+            self.journal.commit(policy);
+        }
+
+        fn rollback_transaction(&mut self) {
+            for undo in self.journal.rollback() {
+                self.value = undo;
+            }
+        }
+
+        fn record_undo(&mut self, undo: Self::Undo) {
+            self.journal.record(undo);
+        }
+
+        fn transaction_depth(&self) -> usize {
+            self.journal.depth()
+        }
+    }
+
+    // default rollback of all transactions goes through every frame reported by depth.
     let mut memory = Memory::default();
+    for _ in 0..3 {
+        memory.begin_transaction();
+        memory.record_undo(memory.value);
+        memory.value += 1;
+    }
+    assert_eq!(memory.transaction_depth(), 3);
+    memory.rollback_all_transactions();
+    assert_eq!(memory.value, 0);
+    assert_eq!(memory.transaction_depth(), 0);
+}
 
-    transaction.on_enter(&mut memory);
-    assert_eq!(memory.outer, 1);
-    assert_eq!(memory.inner, 0);
-    assert_eq!(memory.journal.depth(), 1);
+#[test]
+fn test_journaled_transaction_builtin_memory() {
+    #[derive(Debug, Clone, PartialEq)]
+    struct Unregistered(usize);
+
+    let mut task =
+        JournaledTransactionTask::new(ClosureTask::default().enter(|m: &mut Blackboard| {
+            m.with("wood", |v: &mut usize| *v -= 1);
+            m.with("custom", |v: &mut Unregistered| v.0 += 1);
+            m.set("plank".to_owned(), 1usize);
+            m.remove("coal");
+        }));
+    let mut memory = Blackboard::default();
+    memory.set("wood".to_owned(), 2usize);
+    memory.set("coal".to_owned(), true);
+    memory.set("custom".to_owned(), Unregistered(0));
+    assert!(memory.is_cloneable::<usize>());
+    assert!(!memory.is_cloneable::<Unregistered>());
+
+    // types not registered as cloneable can't be mutated in-place during transaction, because
+    // they could not be restored on rollback.
+    task.on_enter(&mut memory);
+    assert_eq!(memory.get::<usize>("wood"), Some(&1));
+    assert_eq!(memory.get::<Unregistered>("custom"), Some(&Unregistered(0)));
+    assert!(memory.get_mut::<Unregistered>("custom").is_none());
+    assert!(memory.raw_mut("custom").is_none());
+    assert!(memory.get_mut::<usize>("wood").is_some());
+    task.on_stop(&mut memory, TaskStopReason::Cancelled);
+    assert_eq!(memory.transaction_depth(), 0);
+    assert_eq!(memory.get::<usize>("wood"), Some(&2));
+    assert!(memory.get_mut::<Unregistered>("custom").is_some());
 
-    transaction.on_process(&mut memory);
-    assert_eq!(memory.outer, 1);
-    assert_eq!(memory.inner, 10);
-    assert_eq!(memory.journal.depth(), 2);
+    memory.set_cloneable("custom".to_owned(), Unregistered(0));
+    assert!(memory.is_cloneable::<Unregistered>());
+    task.on_enter(&mut memory);
+    assert_eq!(memory.transaction_depth(), 1);
+    assert_eq!(memory.get::<Unregistered>("custom"), Some(&Unregistered(1)));
+    task.on_stop(&mut memory, TaskStopReason::Cancelled);
+    assert_eq!(memory.transaction_depth(), 0);
+    assert_eq!(memory.get::<usize>("wood"), Some(&2));
+    assert_eq!(memory.get::<bool>("coal"), Some(&true));
+    assert!(!memory.has_property("plank"));
+    assert_eq!(memory.get::<Unregistered>("custom"), Some(&Unregistered(0)));
 
-    transaction.on_process(&mut memory);
-    assert_eq!(memory.outer, 1);
-    assert_eq!(memory.inner, 10);
-    assert_eq!(memory.journal.depth(), 1);
+    task.on_enter(&mut memory);
+    task.on_stop(&mut memory, TaskStopReason::Replaced);
+    assert_eq!(memory.get::<Unregistered>("custom"), Some(&Unregistered(0)));
+    task.on_enter(&mut memory);
+    task.on_stop(&mut memory, TaskStopReason::Completed);
+    assert_eq!(memory.get::<usize>("wood"), Some(&1));
+    assert_eq!(memory.get::<Unregistered>("custom"), Some(&Unregistered(1)));
+    assert!(!memory.has_property("coal"));
+
+    let mut task =
+        JournaledTransactionTask::new(ClosureTask::default().enter(|m: &mut DataTable<usize>| {
+            *m.get_mut("wood").unwrap() -= 1;
+            m.set("plank".to_owned(), 1);
+            m.clear();
+        }));
+    let mut memory = DataTable::default();
+    memory.set("wood".to_owned(), 2);
+    task.on_enter(&mut memory);
+    assert!(memory.is_empty());
+    task.on_stop(&mut memory, TaskStopReason::Cancelled);
+    assert_eq!(memory.len(), 1);
+    assert_eq!(memory.get("wood"), Some(&2));
+}
 
-    transaction.on_stop(&mut memory, TaskStopReason::Cancelled);
-    assert_eq!(memory.outer, 0);
-    assert_eq!(memory.inner, 10);
-    assert_eq!(memory.journal.depth(), 0);
+#[test]
+fn test_journaled_transaction_savepoints() {
+    let mut memory = DataTable::<usize>::default();
+    memory.set("ore".to_owned(), 3);
+    memory.set("ingot".to_owned(), 0);
+
+    let mut smelt =
+        JournaledTransactionTask::new(ClosureTask::default().enter(|m: &mut DataTable<usize>| {
+            m.with("ore", |v| *v -= 1);
+            m.with("ingot", |v| *v += 1);
+        }))
+        .savepoint("smelt");
+    assert_eq!(smelt.get_savepoint(), Some("smelt"));
+
+    // without active transaction savepoint task opens its own frame.
+    smelt.on_enter(&mut memory);
+    assert_eq!(memory.transaction_depth(), 1);
+    smelt.on_stop(&mut memory, TaskStopReason::Cancelled);
+    assert_eq!(memory.transaction_depth(), 0);
+    assert_eq!(memory.get("ore"), Some(&3));
+
+    memory.begin_transaction();
+    memory.with("ore", |v| *v -= 1);
+    smelt.on_enter(&mut memory);
+    assert_eq!(memory.transaction_depth(), 1);
+    assert_eq!(memory.get("ore"), Some(&1));
+    smelt.on_stop(&mut memory, TaskStopReason::Cancelled);
+    assert_eq!(memory.transaction_depth(), 1);
+    assert_eq!(memory.get("ore"), Some(&2));
+    assert_eq!(memory.get("ingot"), Some(&0));
+    assert!(!memory.rollback_to_savepoint("smelt"));
+
+    smelt.on_enter(&mut memory);
+    smelt.on_stop(&mut memory, TaskStopReason::Completed);
+    assert!(!memory.release_savepoint("smelt"));
+    assert_eq!(memory.get("ore"), Some(&1));
+    assert_eq!(memory.get("ingot"), Some(&1));
+
+    memory.begin_transaction();
+    memory.set("sword".to_owned(), 1);
+    assert_eq!(memory.transaction_depth(), 2);
+    memory.rollback_all_transactions();
+    assert_eq!(memory.transaction_depth(), 0);
+    assert_eq!(memory.get("ore"), Some(&3));
+    assert_eq!(memory.get("ingot"), Some(&0));
+    assert!(!memory.has_row("sword"));
+
+    let mut journal = TransactionJournal::default();
+    assert!(!journal.savepoint("a"));
+    journal.begin();
+    journal.record(1);
+    assert!(journal.savepoint("a"));
+    journal.record(2);
+    assert!(journal.savepoint("b"));
+    journal.record(3);
+    assert_eq!(journal.savepoints().collect::<Vec<_>>(), vec!["a", "b"]);
+    assert_eq!(
+        journal.rollback_to_savepoint("a").collect::<Vec<_>>(),
+        vec![3, 2]
+    );
+    assert_eq!(journal.savepoints().collect::<Vec<_>>(), vec!["a"]);
+    assert_eq!(journal.rollback_to_savepoint("b").count(), 0);
+    journal.begin();
+    journal.record(4);
+    journal.commit(TransactionCommitPolicy::MergeIntoParent);
+    assert_eq!(journal.entries(), &[1, 4]);
+    assert_eq!(journal.savepoint_entries("a"), &[4]);
+    assert!(journal.release_savepoint("a"));
+    assert!(!journal.has_savepoint("a"));
+    assert_eq!(journal.all_entries().count(), 2);
 }
 
 #[test]
@@ -818,6 +2980,439 @@ fn test_sequencer_stop_reasons() {
     assert_eq!(cancelled_reasons, vec![TaskStopReason::Cancelled]);
 }
 
+#[test]
+fn test_behavior_tree_decorators() {
+    #[derive(Default)]
+    struct Memory {
+        time: Scalar,
+        flag: bool,
+        a: usize,
+        b: usize,
+        cancelled: usize,
+    }
+
+    fn flag() -> ClosureCondition<Memory> {
+        ClosureCondition::new(|m: &Memory| m.flag)
+    }
+
+    fn inc_a() -> ClosureTask<Memory> {
+        ClosureTask::default().enter(|m: &mut Memory| m.a += 1)
+    }
+
+    fn inc_b() -> ClosureTask<Memory> {
+        ClosureTask::default().enter(|m: &mut Memory| m.b += 1)
+    }
+
+    fn locked_inc_a() -> ClosureTask<Memory> {
+        inc_a().locked(|_| true).stop(|m: &mut Memory, reason| {
+            if reason == TaskStopReason::Cancelled {
+                m.cancelled += 1;
+            }
+        })
+    }
+
+    // inverter.
+    let mut tree = BehaviorTree::selector(true)
+        .node(BehaviorTree::inverter(BehaviorTree::state(flag(), inc_a())))
+        .node(BehaviorTree::state(true, inc_b()))
+        .build();
+    let mut memory = Memory::default();
+    // inverted node succeeds, but task of failed wrapped node does not run.
+    assert!(tree.on_process(&mut memory));
+    assert_eq!((memory.a, memory.b), (0, 0));
+    memory.flag = true;
+    assert!(tree.on_process(&mut memory));
+    assert_eq!((memory.a, memory.b), (0, 1));
+    let inverted_sequence = || {
+        BehaviorTree::sequence(true)
+            .node(BehaviorTree::inverter(BehaviorTree::state(flag(), inc_a())))
+            .node(BehaviorTree::state(true, inc_b()))
+            .build()
+    };
+    let mut tree = inverted_sequence();
+    let mut memory = Memory::default();
+    assert!(tree.on_process(&mut memory));
+    assert!(tree.on_process(&mut memory));
+    assert_eq!((memory.a, memory.b), (0, 1));
+    let mut tree = inverted_sequence();
+    let mut memory = Memory {
+        flag: true,
+        ..Default::default()
+    };
+    tree.on_process(&mut memory);
+    assert_eq!(memory.a, 0);
+    let mut memory = Memory {
+        flag: true,
+        ..Default::default()
+    };
+    let mut tree = BehaviorTree::inverter(BehaviorTree::state(flag(), inc_a())).build();
+    tree.on_enter(&mut memory);
+    assert_eq!((memory.a, memory.b), (1, 0));
+
+    // repeat.
+    let mut tree = BehaviorTree::sequence(true)
+        .node(BehaviorTree::repeat(3, BehaviorTree::state(true, inc_a())))
+        .node(BehaviorTree::state(true, inc_b()))
+        .build();
+    let mut memory = Memory::default();
+    for _ in 0..3 {
+        assert!(tree.on_process(&mut memory));
+        assert_eq!(memory.b, 0);
+    }
+    assert_eq!(memory.a, 3);
+    assert!(tree.on_process(&mut memory));
+    assert_eq!((memory.a, memory.b), (3, 1));
+
+    // repeat until.
+    let mut tree = BehaviorTree::sequence(true)
+        .node(BehaviorTree::repeat_until(
+            ClosureCondition::new(|m: &Memory| m.a >= 2),
+            BehaviorTree::state(true, inc_a()),
+        ))
+        .node(BehaviorTree::state(true, inc_b()))
+        .build();
+    let mut memory = Memory::default();
+    assert!(tree.on_process(&mut memory));
+    assert!(tree.on_process(&mut memory));
+    assert_eq!((memory.a, memory.b), (2, 0));
+    assert!(tree.on_process(&mut memory));
+    assert_eq!((memory.a, memory.b), (2, 1));
+
+    // retry.
+    let mut tree = BehaviorTree::sequence(true)
+        .node(BehaviorTree::retry(
+            2,
+            ClosureCondition::new(|m: &Memory| m.a >= 10),
+            BehaviorTree::state(true, inc_a()),
+        ))
+        .node(BehaviorTree::state(true, inc_b()))
+        .build();
+    let mut memory = Memory::default();
+    for _ in 0..3 {
+        assert!(tree.on_process(&mut memory));
+    }
+    assert_eq!((memory.a, memory.b), (3, 0));
+    assert!(tree.on_process(&mut memory));
+    assert_eq!((memory.a, memory.b), (3, 1));
+
+    // cooldown.
+    let mut tree = BehaviorTree::selector(true)
+        .node(BehaviorTree::cooldown(
+            2.0,
+            |m: &Memory| m.time,
+            BehaviorTree::state(flag(), inc_a()),
+        ))
+        .node(BehaviorTree::state(true, inc_b()))
+        .build();
+    let mut memory = Memory {
+        flag: true,
+        ..Default::default()
+    };
+    assert!(tree.on_process(&mut memory));
+    assert_eq!((memory.a, memory.b), (1, 0));
+    memory.flag = false;
+    assert!(tree.on_process(&mut memory));
+    assert_eq!((memory.a, memory.b), (1, 1));
+    memory.flag = true;
+    memory.time = 1.0;
+    assert!(!tree.on_process(&mut memory));
+    assert_eq!((memory.a, memory.b), (1, 1));
+    memory.time = 2.0;
+    assert!(tree.on_process(&mut memory));
+    assert_eq!((memory.a, memory.b), (2, 1));
+    let mut task = CooldownTask::new(
+        locked_inc_a(),
+        2.0,
+        Arc::new(|m: &Memory| m.time) as TaskClock<Memory>,
+    );
+    task.on_stop(&mut memory, TaskStopReason::Cancelled);
+    assert_eq!(memory.cancelled, 0);
+    assert!(task.is_ready(&memory));
+
+    // timeout.
+    let mut tree = BehaviorTree::sequence(true)
+        .node(BehaviorTree::timeout(
+            1.0,
+            |m: &Memory| m.time,
+            BehaviorTree::state(true, locked_inc_a()),
+        ))
+        .node(BehaviorTree::state(true, inc_b()))
+        .build();
+    let mut memory = Memory::default();
+    assert!(tree.on_process(&mut memory));
+    memory.time = 0.5;
+    assert!(!tree.on_process(&mut memory));
+    assert!(tree.is_locked(&memory));
+    memory.time = 1.0;
+    assert!(!tree.is_locked(&memory));
+    assert!(tree.on_process(&mut memory));
+    assert_eq!((memory.a, memory.b, memory.cancelled), (1, 1, 1));
+
+    // always succeed.
+    let mut tree = BehaviorTree::sequence(true)
+        .node(BehaviorTree::always_succeed(BehaviorTree::state(
+            flag(),
+            inc_a(),
+        )))
+        .node(BehaviorTree::state(true, inc_b()))
+        .build();
+    let mut memory = Memory::default();
+    assert!(tree.on_process(&mut memory));
+    assert!(tree.on_process(&mut memory));
+    assert_eq!((memory.a, memory.b), (0, 1));
+
+    // guard.
+    let mut tree = BehaviorTree::sequence(true)
+        .node(BehaviorTree::guard(
+            flag(),
+            BehaviorTree::state(true, locked_inc_a()),
+        ))
+        .node(BehaviorTree::state(true, inc_b()))
+        .build();
+    let mut memory = Memory {
+        flag: true,
+        ..Default::default()
+    };
+    assert!(tree.on_process(&mut memory));
+    assert_eq!(memory.a, 1);
+    assert!(!tree.on_process(&mut memory));
+    tree.on_update(&mut memory);
+    memory.flag = false;
+    tree.on_update(&mut memory);
+    assert_eq!(memory.cancelled, 1);
+    assert!(!tree.is_locked(&memory));
+    assert!(tree.on_process(&mut memory));
+    assert_eq!((memory.a, memory.b), (1, 1));
+}
+
+#[test]
+fn test_behavior_tree_library() {
+    let instances = Arc::new(Mutex::new(0));
+    let instances2 = instances.clone();
+    let library = BehaviorTreeLibrary::<usize>::default()
+        .define("flee", move || {
+            *instances2.lock().unwrap() += 1;
+            BehaviorTree::state(
+                ClosureCondition::new(|m: &usize| *m > 0),
+                ClosureTask::default().enter(|m: &mut usize| *m = 0),
+            )
+        })
+        .define("guard", || {
+            BehaviorTree::selector(true)
+                .node(BehaviorTree::subtree("flee"))
+                .node(BehaviorTree::state(true, NoTask))
+        })
+        .define("patrol", || {
+            BehaviorTree::sequence(true)
+                .node(BehaviorTree::subtree("flee"))
+                .node(BehaviorTree::inverter(BehaviorTree::subtree("flee")))
+        });
+
+    assert_eq!(library.validate(), Ok(()));
+    *instances.lock().unwrap() = 0;
+    let mut guard = library.build("guard").unwrap();
+    let patrol = library.instantiate("patrol").unwrap();
+    assert_eq!(*instances.lock().unwrap(), 3);
+    assert_eq!(patrol.children().len(), 2);
+    assert!(matches!(
+        patrol.children()[1].children()[0],
+        BehaviorTree::State { .. }
+    ));
+    let mut memory = 1;
+    assert!(guard.on_process(&mut memory));
+    assert_eq!(memory, 0);
+
+    let tree = library
+        .resolve(BehaviorTree::selector(true).node(BehaviorTree::subtree("missing")))
+        .unwrap_err();
+    assert_eq!(
+        tree,
+        BehaviorTreeLibraryError::TreeDoesNotExists("missing".to_owned())
+    );
+    // unresolved references never run.
+    let mut tree = BehaviorTree::selector(true)
+        .node(BehaviorTree::subtree("flee"))
+        .build();
+    let mut memory = 1;
+    assert!(!tree.on_process(&mut memory));
+    assert_eq!(memory, 1);
+
+    let mut library = library
+        .define("a", || {
+            BehaviorTree::sequence(true).node(BehaviorTree::subtree("b"))
+        })
+        .define("b", || {
+            BehaviorTree::selector(true)
+                .node(BehaviorTree::subtree("flee"))
+                .node(BehaviorTree::subtree("a"))
+        });
+    assert_eq!(
+        library.instantiate("a").unwrap_err(),
+        BehaviorTreeLibraryError::RecursiveReference(vec![
+            "a".to_owned(),
+            "b".to_owned(),
+            "a".to_owned(),
+        ])
+    );
+    assert!(library.validate().is_err());
+    assert!(library.instantiate("guard").is_ok());
+    library.remove("b");
+    library.add("b", || BehaviorTree::subtree("flee"));
+    assert_eq!(library.validate(), Ok(()));
+}
+
+#[test]
+fn test_behavior_tree_dsl() {
+    struct Memory {
+        time: Scalar,
+        enemy: bool,
+        attacks: usize,
+        steps: usize,
+    }
+
+    let registry = BehaviorTreeRegistry::default()
+        .condition("enemy", || ClosureCondition::new(|m: &Memory| m.enemy))
+        .condition("tired", || ClosureCondition::new(|m: &Memory| m.steps >= 3))
+        .task("attack", || {
+            ClosureTask::default().enter(|m: &mut Memory| m.attacks += 1)
+        })
+        .task("walk", || {
+            ClosureTask::default().enter(|m: &mut Memory| m.steps += 1)
+        })
+        .clock(|m: &Memory| m.time);
+    assert!(registry.has_condition("enemy"));
+    assert!(!registry.has_task("enemy"));
+
+    let text = r#"
+        ; attack enemies, otherwise patrol.
+        (selector
+          (cooldown :duration 2
+            (state attack :if enemy))
+          (sequence :mode reactive-restart
+            (state walk)
+            (inverter (state walk :if tired))))
+    "#;
+    let tree = registry.parse(text).unwrap();
+    let printed = tree.to_text();
+    assert_eq!(
+        printed,
+        "(selector
+  (cooldown :duration 2
+    (state attack :if enemy))
+  (sequence :mode reactive-restart
+    (state walk)
+    (inverter
+      (state walk :if tired))))"
+    );
+    assert_eq!(registry.parse(&printed).unwrap().to_text(), printed);
+
+    let mut tree = tree.build();
+    let mut memory = Memory {
+        time: 0.0,
+        enemy: true,
+        attacks: 0,
+        steps: 0,
+    };
+    assert!(tree.on_process(&mut memory));
+    assert_eq!(memory.attacks, 1);
+
+    let text = "(parallel :success require-all :failure fail-on-first :cancel-remaining true
+  (repeat :count 3
+    (state walk))
+  (retry :count 2 :until enemy
+    (always-succeed
+      (state attack)))
+  (abort :mode both
+    (guard :if enemy
+      (subtree patrol)))
+  (repeat :until tired
+    (transaction
+      (state walk :if false))))";
+    let tree = registry.parse(text).unwrap();
+    assert_eq!(tree.to_text(), text);
+
+    let error = |text: &str| registry.parse(text).unwrap_err();
+    assert_eq!(
+        error("(selector\n  (state run :if enemy))"),
+        BehaviorTreeDslError {
+            kind: BehaviorTreeDslErrorKind::UnknownTask("run".to_owned()),
+            line: 2,
+            column: 10,
+        }
+    );
+    assert_eq!(
+        error("(selector\n  (state run :if enemy))").to_string(),
+        "Unknown task `run` at line 2, column 10"
+    );
+    assert_eq!(
+        error("(selector\n  (state walk\n    :if hungry))"),
+        BehaviorTreeDslError {
+            kind: BehaviorTreeDslErrorKind::UnknownCondition("hungry".to_owned()),
+            line: 3,
+            column: 9,
+        }
+    );
+    assert_eq!(
+        error("(loop (state walk))"),
+        BehaviorTreeDslError {
+            kind: BehaviorTreeDslErrorKind::UnknownNodeKind("loop".to_owned()),
+            line: 1,
+            column: 2,
+        }
+    );
+    assert_eq!(
+        error("(sequence :mode eager)").kind,
+        BehaviorTreeDslErrorKind::InvalidValue {
+            property: "mode".to_owned(),
+            value: "eager".to_owned(),
+        }
+    );
+    assert_eq!(
+        error("(inverter (state walk) (state walk))").kind,
+        BehaviorTreeDslErrorKind::TooManyNodes
+    );
+    assert_eq!(
+        error("(selector :if enemy :if enemy)").kind,
+        BehaviorTreeDslErrorKind::DuplicateProperty("if".to_owned())
+    );
+    assert_eq!(
+        error("(selector\n  (state walk)").kind,
+        BehaviorTreeDslErrorKind::UnexpectedEndOfInput
+    );
+    assert_eq!(
+        BehaviorTreeRegistry::<Memory>::default()
+            .parse("(timeout :duration 1 (selector))")
+            .unwrap_err()
+            .kind,
+        BehaviorTreeDslErrorKind::MissingClock
+    );
+
+    let nested =
+        |depth: usize| "(inverter ".repeat(depth - 1) + "(state walk)" + &")".repeat(depth - 1);
+    assert!(registry.parse(&nested(BEHAVIOR_TREE_DSL_MAX_DEPTH)).is_ok());
+    assert_eq!(
+        error(&nested(BEHAVIOR_TREE_DSL_MAX_DEPTH + 1)),
+        BehaviorTreeDslError {
+            kind: BehaviorTreeDslErrorKind::TooDeep,
+            line: 1,
+            column: BEHAVIOR_TREE_DSL_MAX_DEPTH * 10 + 1,
+        }
+    );
+
+    let tree = BehaviorTree::<Memory>::selector(true).node(BehaviorTree::state(
+        ClosureCondition::new(|m: &Memory| m.enemy),
+        NoTask,
+    ));
+    assert_eq!(
+        tree.to_text(),
+        "(selector\n  (state <unnamed> :if <unnamed>))"
+    );
+    assert_eq!(
+        registry.parse(&tree.to_text()).unwrap_err().kind,
+        BehaviorTreeDslErrorKind::UnexpectedCharacter('<')
+    );
+}
+
 #[test]
 fn test_transactional_behavior_tree() {
     struct Memory {
@@ -852,16 +3447,406 @@ fn test_transactional_behavior_tree() {
     // of the sequence would trigger commit hooks (if they were set).
 }
 
+#[test]
+fn test_blackboard_change_tracking() {
+    let changes = Arc::new(Mutex::new(Vec::new()));
+    let mut memory = Blackboard::default();
+    let subscription = {
+        let changes = changes.clone();
+        memory.subscribe(move |change| {
+            changes
+                .lock()
+                .unwrap()
+                .push((change.name.to_owned(), change.kind));
+        })
+    };
+
+    memory.set("a".to_owned(), 1usize);
+    memory.set("b".to_owned(), false);
+    let version = memory.version();
+    assert_eq!(memory.changed_since(version).count(), 0);
+
+    assert_eq!(memory.with("a", |v: &mut usize| *v += 1), Some(()));
+    assert_eq!(memory.with("a", |v: &mut bool| *v = true), None);
+    assert!(memory.remove("b"));
+    assert!(!memory.remove("c"));
+    let mut changed = memory.changed_since(version).collect::<Vec<_>>();
+    changed.sort();
+    assert_eq!(changed, vec!["a", "b"]);
+    assert!(memory.property_version("b").unwrap() > version);
+    assert_eq!(memory.property_version("c"), None);
+
+    assert!(memory.unsubscribe(subscription));
+    assert!(!memory.unsubscribe(subscription));
+    memory.clear();
+    assert_eq!(
+        changes.lock().unwrap().as_slice(),
+        &[
+            ("a".to_owned(), BlackboardChangeKind::Set),
+            ("b".to_owned(), BlackboardChangeKind::Set),
+            ("a".to_owned(), BlackboardChangeKind::Modified),
+            ("b".to_owned(), BlackboardChangeKind::Removed),
+        ]
+    );
+    assert!(memory.has_changed_since("a", version + 2));
+}
+
+#[test]
+fn test_scoped_blackboard() {
+    let mut global = Blackboard::default();
+    global.set("danger".to_owned(), 0usize);
+    global.set("name".to_owned(), "world".to_owned());
+    let mut squad = Blackboard::default();
+    squad.set("danger".to_owned(), 1usize);
+    squad.set("orders".to_owned(), "hold".to_owned());
+
+    let mut memory = ScopedBlackboard::default()
+        .scope("global", global)
+        .scope("squad", squad)
+        .scope("agent", Blackboard::default())
+        .write_rule("orders", BlackboardWriteTarget::Owner)
+        .write_rule("name", BlackboardWriteTarget::Scope("missing".to_owned()));
+
+    assert_eq!(memory.len(), 3);
+    assert_eq!(memory.get::<usize>("danger"), Some(&1));
+    assert_eq!(memory.property_scope("danger"), Some("squad"));
+
+    // in-place mutations follow write target rules, so they don't reach outer scopes.
+    assert_eq!(memory.get::<usize>("danger"), Some(&1));
+    assert_eq!(memory.get_mut::<usize>("danger"), None);
+    assert!(memory.raw_mut("danger").is_none());
+    assert_eq!(memory.with("danger", |v: &mut usize| *v += 1), None);
+    assert!(!memory.remove("danger"));
+    assert_eq!(memory.get::<usize>("danger"), Some(&1));
+
+    // default write target shadows outer scopes.
+    let version = memory.version();
+    memory.set("danger".to_owned(), 2usize);
+    assert!(memory.has_changed_since("danger", version));
+    assert_eq!(memory.get::<usize>("danger"), Some(&2));
+    assert_eq!(
+        memory.scope_ref("squad").unwrap().get::<usize>("danger"),
+        Some(&1)
+    );
+    assert!(memory.remove("danger"));
+    assert_eq!(memory.get::<usize>("danger"), Some(&1));
+    assert!(!memory.remove("danger"));
+    assert_eq!(memory.with("danger", |v: &mut usize| *v += 1), None);
+
+    // owner write target mutates shared scope.
+    assert_eq!(
+        memory.with("orders", |v: &mut String| *v = "attack".to_owned()),
+        Some(())
+    );
+    assert_eq!(
+        memory
+            .scope_ref("squad")
+            .unwrap()
+            .get::<String>("orders")
+            .unwrap(),
+        "attack"
+    );
+
+    let mut changed = memory.changed_since(version).collect::<Vec<_>>();
+    changed.sort();
+    assert_eq!(changed, vec!["danger", "orders"]);
+
+    // writes to missing scopes create them.
+    memory.set("name".to_owned(), "agent".to_owned());
+    assert_eq!(memory.get::<String>("name").unwrap(), "agent");
+    assert_eq!(memory.property_scope("name"), Some("missing"));
+    let (name, missing) = memory.pop_scope().unwrap();
+    assert_eq!(name, "missing");
+    assert_eq!(missing.get::<String>("name").unwrap(), "agent");
+    assert_eq!(memory.get::<String>("name").unwrap(), "world");
+
+    let squad = memory
+        .replace_scope("squad", Blackboard::default())
+        .unwrap();
+    assert_eq!(squad.get::<usize>("danger"), Some(&1));
+    assert_eq!(memory.get::<usize>("danger"), Some(&0));
+    let scopes = memory.into_scopes();
+    assert_eq!(
+        scopes
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>(),
+        vec!["global", "squad", "agent"]
+    );
+}
+
+#[test]
+fn test_scoped_blackboard_access() {
+    fn danger<B: BlackboardAccess>(memory: &B) -> Scalar {
+        memory.get::<usize>("danger").copied().unwrap_or_default() as Scalar
+    }
+
+    fn raise_danger<B: BlackboardAccess>(memory: &mut B) {
+        if memory.with("danger", |v: &mut usize| *v += 1).is_none() {
+            memory.set("danger".to_owned(), 1usize);
+        }
+    }
+
+    let mut plain = Blackboard::default();
+    plain.set("danger".to_owned(), 1usize);
+    let mut global = Blackboard::default();
+    global.set("danger".to_owned(), 1usize);
+    let mut scoped = ScopedBlackboard::default()
+        .scope("global", global)
+        .scope("agent", Blackboard::default())
+        .write_rule("danger", BlackboardWriteTarget::Owner);
+
+    assert_eq!(
+        ClosureConsideration::new(danger::<Blackboard>).score(&plain),
+        1.0
+    );
+    assert_eq!(
+        ClosureConsideration::new(danger::<ScopedBlackboard>).score(&scoped),
+        1.0
+    );
+    raise_danger(&mut plain);
+    raise_danger(&mut scoped);
+    assert_eq!(danger(&plain), 2.0);
+    assert_eq!(danger(&scoped), 2.0);
+    assert_eq!(
+        scoped.scope_ref("global").unwrap().get::<usize>("danger"),
+        Some(&2)
+    );
+    assert!(!scoped.scope_ref("agent").unwrap().has_property("danger"));
+}
+
+#[test]
+fn test_scoped_blackboard_transactions() {
+    let mut global = Blackboard::default();
+    global.set("alarm".to_owned(), false);
+    let mut memory = ScopedBlackboard::default()
+        .scope("global", global)
+        .scope("agent", Blackboard::default())
+        .write_rule("alarm", BlackboardWriteTarget::Owner);
+    let changes = Arc::new(Mutex::new(vec![]));
+    let changes2 = changes.clone();
+    memory.subscribe(move |change| {
+        changes2
+            .lock()
+            .unwrap()
+            .push((change.name.to_owned(), change.kind))
+    });
+
+    let mut task =
+        JournaledTransactionTask::new(ClosureTask::default().enter(|m: &mut ScopedBlackboard| {
+            m.with("alarm", |v: &mut bool| *v = true);
+            m.set("target".to_owned(), 1usize);
+        }));
+    task.on_enter(&mut memory);
+    assert_eq!(memory.transaction_depth(), 1);
+    assert_eq!(memory.get::<bool>("alarm"), Some(&true));
+    assert_eq!(memory.get::<usize>("target"), Some(&1));
+
+    // scopes added during transaction join it.
+    memory.push_scope("squad", Blackboard::default());
+    assert_eq!(
+        memory.scope_ref("squad").unwrap().transaction_depth(),
+        memory.transaction_depth()
+    );
+    memory
+        .scope_mut("squad")
+        .unwrap()
+        .set("orders".to_owned(), 0usize);
+    let version = memory.version();
+    task.on_stop(&mut memory, TaskStopReason::Cancelled);
+    assert_eq!(memory.transaction_depth(), 0);
+    assert_eq!(memory.get::<bool>("alarm"), Some(&false));
+    assert!(!memory.has_property("target"));
+    assert!(!memory.has_property("orders"));
+    assert!(memory.has_changed_since("alarm", version));
+    assert!(memory.has_changed_since("target", version));
+    assert_eq!(
+        changes.lock().unwrap().as_slice(),
+        &[
+            ("alarm".to_owned(), BlackboardChangeKind::Modified),
+            ("target".to_owned(), BlackboardChangeKind::Set),
+            ("alarm".to_owned(), BlackboardChangeKind::Set),
+            ("target".to_owned(), BlackboardChangeKind::Removed),
+            ("orders".to_owned(), BlackboardChangeKind::Removed),
+        ][..]
+    );
+}
+
+#[test]
+fn test_datatable_queries() {
+    #[derive(Debug, Clone, PartialEq)]
+    struct Item {
+        kind: String,
+        value: Scalar,
+    }
+
+    impl Item {
+        fn new(kind: &str, value: Scalar) -> Self {
+            Self {
+                kind: kind.to_owned(),
+                value,
+            }
+        }
+    }
+
+    fn sorted<'a>(iter: impl Iterator<Item = (&'a str, &'a Item)>) -> Vec<&'a str> {
+        let mut result = iter.map(|(name, _)| name).collect::<Vec<_>>();
+        result.sort();
+        result
+    }
+
+    let mut memory = DataTable::default();
+    memory.set("sword".to_owned(), Item::new("weapon", 10.0));
+    memory.set("bread".to_owned(), Item::new("food", 1.0));
+    memory.add_index("kind", |item: &Item| item.kind.clone());
+    memory.set("axe".to_owned(), Item::new("weapon", 7.0));
+    assert!(memory.has_index("kind"));
+    assert_eq!(
+        sorted(memory.indexed("kind", "weapon")),
+        vec!["axe", "sword"]
+    );
+    assert_eq!(sorted(memory.indexed("kind", "food")), vec!["bread"]);
+    assert_eq!(memory.indexed("missing", "food").count(), 0);
+
+    memory.get_mut("axe").unwrap().kind = "tool".to_owned();
+    assert_eq!(sorted(memory.indexed("kind", "weapon")), vec!["sword"]);
+    assert_eq!(sorted(memory.indexed("kind", "tool")), vec!["axe"]);
+    memory.with("bread", |item| item.kind = "tool".to_owned());
+    assert_eq!(sorted(memory.indexed("kind", "tool")), vec!["axe", "bread"]);
+    assert_eq!(memory.indexed("kind", "food").count(), 0);
+
+    memory.begin_transaction();
+    memory.remove("sword");
+    memory.set("axe".to_owned(), Item::new("weapon", 7.0));
+    assert_eq!(sorted(memory.indexed("kind", "weapon")), vec!["axe"]);
+    memory.rollback_transaction();
+    assert_eq!(sorted(memory.indexed("kind", "weapon")), vec!["sword"]);
+    assert_eq!(sorted(memory.indexed("kind", "tool")), vec!["axe", "bread"]);
+
+    assert!(memory.all(|item| item.value > 0.0));
+    assert_eq!(
+        sorted(memory.filter(|item| item.value > 5.0)),
+        vec!["axe", "sword"]
+    );
+    assert_eq!(
+        memory
+            .iter_sorted_by_score(|item| item.value)
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>(),
+        vec!["sword", "axe", "bread"],
+    );
+    memory.get_mut("axe").unwrap().value = Scalar::NAN;
+    assert_eq!(
+        memory
+            .iter_sorted_by_score(|item| item.value)
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>(),
+        vec!["sword", "bread", "axe"],
+    );
+
+    let condition =
+        DataTableCondition::new(|m: &DataTable<Item>| m, |item: &Item| item.kind == "food");
+    let consideration =
+        DataTableConsideration::new(|m: &DataTable<Item>| m, |item: &Item| item.value);
+    assert!(!condition.validate(&memory));
+    assert_eq!(consideration.score(&memory), 10.0);
+    memory.get_mut("sword").unwrap().value = Scalar::NAN;
+    assert_eq!(consideration.score(&memory), 1.0);
+    memory.set("apple".to_owned(), Item::new("food", 2.0));
+    assert!(condition.validate(&memory));
+    memory.clear();
+    assert!(!condition.validate(&memory));
+    assert_eq!(consideration.score(&memory), 0.0);
+    assert_eq!(memory.indexed("kind", "food").count(), 0);
+}
+
+#[cfg(all(feature = "json", feature = "csv"))]
+#[test]
+fn test_datatable_import() {
+    use crate::memory::datatable::DataTableImportError;
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Recipe {
+        name: String,
+        wood: usize,
+        score: Scalar,
+    }
+
+    let memory = DataTable::<Recipe>::from_json(
+        r#"{
+            "plank": { "name": "plank", "wood": 1, "score": 0.5 },
+            "chair": { "name": "chair", "wood": 4, "score": 0.8 }
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(memory.len(), 2);
+    assert_eq!(memory.get("chair").unwrap().wood, 4);
+    assert!(matches!(
+        DataTable::<Recipe>::from_json("[]"),
+        Err(DataTableImportError::Json(_))
+    ));
+
+    let csv = "name,wood,score\nplank,1,0.5\nchair,4,0.8\n";
+    let mut memory = DataTable::<Recipe>::from_csv(csv, "name").unwrap();
+    assert_eq!(memory.len(), 2);
+    assert_eq!(
+        memory.get("plank"),
+        Some(&Recipe {
+            name: "plank".to_owned(),
+            wood: 1,
+            score: 0.5,
+        })
+    );
+    assert_eq!(
+        memory.best_by_score(|recipe| recipe.score).unwrap().0,
+        "chair"
+    );
+    let error = memory.import_csv(csv, "missing").unwrap_err();
+    assert!(matches!(
+        error,
+        DataTableImportError::KeyColumnDoesNotExists(_)
+    ));
+    assert_eq!(
+        error.to_string(),
+        "There is no column `missing` in CSV data"
+    );
+    assert!(std::error::Error::source(&error).is_none());
+    let error = memory
+        .import_csv("name,wood,score\ntable,many,1.0\n", "name")
+        .unwrap_err();
+    assert!(matches!(error, DataTableImportError::Csv(_)));
+    assert!(std::error::Error::source(&error).is_some());
+}
+
 #[test]
 fn test_send_sync() {
     check_send_sync::<Blackboard>();
+    check_send_sync::<ScopedBlackboard>();
     check_send_sync::<DataTable<()>>();
     check_send_sync::<Reasoner<()>>();
     check_send_sync::<Machinery<()>>();
     check_send_sync::<Planner<()>>();
+    check_send_sync::<Mcts<()>>();
     check_send_sync::<Sequencer<()>>();
     check_send_sync::<Selector<()>>();
     check_send_sync::<Parallelizer<()>>();
+    check_send_sync::<AgentPool<(), Machinery<()>>>();
+    #[cfg(feature = "async")]
+    check_send_sync::<crate::task::AsyncTask<(), usize>>();
+    check_send_sync::<ComponentCondition<()>>();
+    check_send_sync::<ComponentConsideration<()>>();
     check_send_sync::<Lod<()>>();
+    check_send_sync::<LodController<()>>();
     check_send_sync::<BehaviorTreeTask<()>>();
+    check_send_sync::<BehaviorTreeLibrary<()>>();
+    check_send_sync::<RepeatTask<()>>();
+    check_send_sync::<RetryTask<()>>();
+    check_send_sync::<CooldownTask<()>>();
+    check_send_sync::<CooldownCondition<()>>();
+    check_send_sync::<TimeoutTask<()>>();
+    check_send_sync::<GuardTask<()>>();
+    check_send_sync::<AlwaysSucceedTask<()>>();
+    check_send_sync::<InverterTask<()>>();
+    check_send_sync::<DataTableCondition<(), usize>>();
+    check_send_sync::<DataTableConsideration<(), usize>>();
 }